@@ -0,0 +1,354 @@
+//! Append-only JSONL audit log of every freeze and resume
+
+use crate::logging::{needs_rotation, rotate_logs, rotated_path};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rotate once the audit log would grow past this size
+pub const MAX_AUDIT_BYTES: u64 = 1024 * 1024;
+/// Number of rotated audit logs kept (`audit.jsonl.1`, `audit.jsonl.2`)
+pub const KEEP_AUDIT_LOGS: usize = 2;
+
+/// `%LOCALAPPDATA%\SmartFreeze\audit.jsonl`, or the temp dir if LOCALAPPDATA is unset
+pub fn default_audit_path() -> PathBuf {
+    let mut path = match std::env::var_os("LOCALAPPDATA") {
+        Some(local) => PathBuf::from(local).join("SmartFreeze"),
+        None => std::env::temp_dir(),
+    };
+    path.push("audit.jsonl");
+    path
+}
+
+/// What was done to a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Freeze,
+    Resume,
+    /// Started again from its saved path after being killed while frozen
+    Relaunch,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix seconds
+    pub timestamp: u64,
+    pub pid: u32,
+    /// Empty if the process wasn't in the last enumeration
+    pub name: String,
+    pub action: AuditAction,
+    /// Threads suspended or resumed (0 for throttling)
+    pub threads: usize,
+}
+
+impl AuditEntry {
+    pub fn new(pid: u32, name: &str, action: AuditAction, threads: usize) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self {
+            timestamp,
+            pid,
+            name: name.to_string(),
+            action,
+            threads,
+        }
+    }
+}
+
+/// Appends `AuditEntry` lines to a size-capped, rotated file
+pub struct AuditLogger {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    /// Serializes rotation and appends from concurrent batch freezes
+    write_lock: Mutex<()>,
+}
+
+impl AuditLogger {
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            keep,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Logger at the default path with the default cap
+    pub fn with_default_path() -> Self {
+        Self::new(default_audit_path(), MAX_AUDIT_BYTES, KEEP_AUDIT_LOGS)
+    }
+
+    /// Append one entry, rotating first if it would push the file past the cap
+    pub fn record(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let current = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if needs_rotation(current, line.len() as u64, self.max_bytes) {
+            rotate_logs(&self.path, self.keep)?;
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// The last `n` entries, oldest first, including rotated files
+    ///
+    /// Lines that don't parse (e.g. a write cut short by a crash) are skipped.
+    pub fn read_recent(&self, n: usize) -> Result<Vec<AuditEntry>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let mut recent = VecDeque::with_capacity(n);
+
+        let oldest_first = (1..=self.keep)
+            .rev()
+            .map(|index| rotated_path(&self.path, index))
+            .chain(std::iter::once(self.path.clone()));
+
+        for path in oldest_first {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            {
+                if recent.len() == n {
+                    recent.pop_front();
+                }
+                recent.push_back(entry);
+            }
+        }
+
+        Ok(recent.into())
+    }
+
+    /// The last `n` entries, newest first (for `--history`)
+    pub fn history(&self, n: usize) -> Result<Vec<AuditEntry>> {
+        let mut entries = self.read_recent(n)?;
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+/// Unix seconds as `YYYY-MM-DD HH:MM:SS` (UTC)
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// `--history` table: time, action, PID and name, one row per entry
+pub fn format_history(entries: &[AuditEntry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<19}  {:<8} {:<8} {:>7}  Name",
+        "Time (UTC)", "Action", "PID", "Threads"
+    );
+    let _ = writeln!(out, "{}", "-".repeat(70));
+    for entry in entries {
+        let action = match entry.action {
+            AuditAction::Freeze => "freeze",
+            AuditAction::Resume => "resume",
+            AuditAction::Relaunch => "relaunch",
+        };
+        let _ = writeln!(
+            out,
+            "{:<19}  {:<8} {:<8} {:>7}  {}",
+            format_timestamp(entry.timestamp),
+            action,
+            entry.pid,
+            entry.threads,
+            if entry.name.is_empty() {
+                "?"
+            } else {
+                &entry.name
+            }
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "smartfreeze_audit_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_record_writes_one_json_object_per_line() {
+        let dir = test_dir("lines");
+        let path = dir.join("audit.jsonl");
+        let logger = AuditLogger::new(path.clone(), MAX_AUDIT_BYTES, KEEP_AUDIT_LOGS);
+
+        logger
+            .record(&AuditEntry::new(42, "chrome.exe", AuditAction::Freeze, 31))
+            .unwrap();
+        logger
+            .record(&AuditEntry::new(42, "chrome.exe", AuditAction::Resume, 31))
+            .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["pid"], 42);
+        assert_eq!(first["name"], "chrome.exe");
+        assert_eq!(first["action"], "freeze");
+        assert_eq!(first["threads"], 31);
+        assert!(first["timestamp"].as_u64().unwrap() > 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_recent_spans_rotated_files() {
+        let dir = test_dir("recent");
+        let path = dir.join("audit.jsonl");
+        // Small enough that every entry after the first rotates
+        let logger = AuditLogger::new(path.clone(), 10, 2);
+
+        for pid in 1..=4 {
+            logger
+                .record(&AuditEntry::new(pid, "app.exe", AuditAction::Freeze, 1))
+                .unwrap();
+        }
+        fs::write(
+            &path,
+            format!("{}not json\n", fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+
+        let pids: Vec<u32> = logger
+            .read_recent(2)
+            .unwrap()
+            .iter()
+            .map(|e| e.pid)
+            .collect();
+        assert_eq!(pids, vec![3, 4]);
+
+        // Entry 1 was rotated out past `keep`
+        let pids: Vec<u32> = logger
+            .read_recent(10)
+            .unwrap()
+            .iter()
+            .map(|e| e.pid)
+            .collect();
+        assert_eq!(pids, vec![2, 3, 4]);
+        assert!(logger.read_recent(0).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_history_is_newest_first() {
+        let dir = test_dir("history");
+        let logger = AuditLogger::new(dir.join("audit.jsonl"), MAX_AUDIT_BYTES, 2);
+
+        for (timestamp, pid, action) in [
+            (1_700_000_000, 1, AuditAction::Freeze),
+            (1_700_000_060, 2, AuditAction::Freeze),
+            (1_700_000_120, 1, AuditAction::Resume),
+            (1_700_000_180, 2, AuditAction::Resume),
+        ] {
+            let entry = AuditEntry {
+                timestamp,
+                ..AuditEntry::new(pid, "app.exe", action, 3)
+            };
+            logger.record(&entry).unwrap();
+        }
+
+        let history = logger.history(3).unwrap();
+        let summary: Vec<(u64, u32, AuditAction)> = history
+            .iter()
+            .map(|e| (e.timestamp, e.pid, e.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1_700_000_180, 2, AuditAction::Resume),
+                (1_700_000_120, 1, AuditAction::Resume),
+                (1_700_000_060, 2, AuditAction::Freeze),
+            ]
+        );
+
+        let table = format_history(&history);
+        let rows: Vec<&str> = table.lines().skip(2).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("2023-11-14 22:16:20  resume   2 "));
+        assert!(rows[2].ends_with("app.exe"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20");
+    }
+
+    #[test]
+    fn test_read_recent_without_log_is_empty() {
+        let dir = test_dir("missing");
+        let logger = AuditLogger::new(dir.join("audit.jsonl"), MAX_AUDIT_BYTES, 2);
+
+        assert!(logger.read_recent(5).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,174 +1,337 @@
 //! Process categorization logic
 
 use crate::process::ProcessCategory;
-use std::collections::HashMap;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Critical system processes (matched by exact name)
+const CRITICAL_PROCESSES: &[&str] = &[
+    "system",
+    "smss.exe",
+    "csrss.exe",
+    "wininit.exe",
+    "services.exe",
+    "lsass.exe",
+    "svchost.exe",
+    "winlogon.exe",
+    "explorer.exe",
+    "dwm.exe",
+    "searchhost.exe",
+    "startmenuexperiencehost.exe",
+];
+
+/// Input, IME and accessibility processes (matched by exact name)
+///
+/// These own keyboard hooks or the text input pipeline; suspending one can
+/// leave typing or assistive tools dead until it is resumed.
+const INPUT_PROCESSES: &[&str] = &[
+    "ctfmon.exe",
+    "textinputhost.exe",
+    "tabtip.exe",
+    "osk.exe",
+    "magnify.exe",
+    "narrator.exe",
+    "sethc.exe",
+    "utilman.exe",
+    "atbroker.exe",
+];
+
+/// Code-signing subjects whose binaries are treated as critical (matched by prefix)
+///
+/// "Microsoft Corporation" is deliberately absent: it also signs Teams, Edge
+/// and Office, which are fine to freeze.
+const PROTECTED_PUBLISHERS: &[&str] = &[
+    "microsoft windows",
+    "nvidia corporation",
+    "advanced micro devices",
+    "intel corporation",
+    "realtek semiconductor",
+];
+
+/// Game launchers and anti-cheat (matched by name substring)
+const GAMING_NAMES: &[&str] = &[
+    "steam",
+    "epic",
+    "origin",
+    "gog",
+    "battle.net",
+    "battlenet",
+    "uplay",
+    "ubisoft",
+    "easyanticheat",
+    "battleye",
+    "vanguard",
+];
+
+/// Game library folders (matched by path substring)
+const GAMING_PATHS: &[&str] = &[
+    "\\steam\\",
+    "\\steamapps\\",
+    "\\steamlibrary\\",
+    "\\epic games\\",
+    "\\epicgames\\",
+    "\\origin games\\",
+    "\\gog galaxy\\",
+    "\\gog games\\",
+    "\\battle.net\\",
+    "\\ubisoft\\",
+    "\\ea games\\",
+    "\\riot games\\",
+    "\\games\\",
+    "\\my games\\",
+];
+
+/// Games hosted by a generic runtime (javaw.exe, python.exe), matched by command-line substring
+const GAMING_COMMAND_LINES: &[&str] = &[
+    "net.minecraft",
+    "minecraft",
+    "lunarclient",
+    "tlauncher",
+    "prismlauncher",
+    "multimc",
+    "runelite",
+    "renpy",
+    "pygame",
+    "\\steamapps\\",
+];
+
+const COMMUNICATION_APPS: &[&str] = &[
+    "discord",
+    "slack",
+    "teams",
+    "telegram",
+    "signal",
+    "whatsapp",
+    "zoom",
+    "skype",
+    "mumble",
+    "teamspeak",
+    "ventrilo",
+    "element",
+    "riot",
+];
+
+const BACKGROUND_SERVICES: &[&str] = &[
+    "updater",
+    "update",
+    "helper",
+    "sync",
+    "backup",
+    "nvidia",
+    "amd",
+    "geforce",
+    "radeon",
+    "onedrive",
+    "dropbox",
+    "google drive",
+    "toolbox",
+];
+
+const PRODUCTIVITY_APPS: &[&str] = &[
+    "chrome",
+    "firefox",
+    "edge",
+    "opera",
+    "brave",
+    "vivaldi",
+    "excel",
+    "word",
+    "powerpoint",
+    "outlook",
+    "onenote",
+    "vscode",
+    "code",
+    "pycharm",
+    "intellij",
+    "rider",
+    "sublime",
+    "spotify",
+    "vlc",
+    "itunes",
+    "notion",
+    "obsidian",
+];
+
+/// Precedence of the built-in rules; higher priorities are checked first
+const CRITICAL_PRIORITY: u32 = 100;
+const GAMING_PRIORITY: u32 = 80;
+const COMMUNICATION_PRIORITY: u32 = 60;
+const BACKGROUND_PRIORITY: u32 = 40;
+const PRODUCTIVITY_PRIORITY: u32 = 20;
 
 /// Trait for categorizing processes
 pub trait ProcessCategorizer: Send + Sync {
     /// Categorize a process based on its attributes
     fn categorize(&self, pid: u32, name: &str, path: &str) -> ProcessCategory;
 
+    /// Categorize a process and explain which rule decided it
+    fn categorize_with_reason(
+        &self,
+        pid: u32,
+        name: &str,
+        path: &str,
+    ) -> (ProcessCategory, String) {
+        let category = self.categorize(pid, name, path);
+        (category, format!("categorized as {}", category.as_str()))
+    }
+
+    /// Categorize using the command line too, when the enumerator could read it
+    fn categorize_with_cmdline(
+        &self,
+        pid: u32,
+        name: &str,
+        path: &str,
+        _command_line: Option<&str>,
+    ) -> (ProcessCategory, String) {
+        self.categorize_with_reason(pid, name, path)
+    }
+
+    /// Every rule matching the name or path and why it matched, in precedence
+    /// order; `categorize` picks the first (before parent inheritance)
+    fn categorize_all_matches(&self, _name: &str, _path: &str) -> Vec<(ProcessCategory, String)> {
+        Vec::new()
+    }
+
     /// Check if a process is critical
     fn is_critical(&self, name: &str) -> bool;
+
+    /// Category implied by the binary's code-signing publisher, if any
+    fn categorize_publisher(&self, publisher: &str) -> Option<ProcessCategory> {
+        is_protected_publisher(publisher).then_some(ProcessCategory::Critical)
+    }
+
+    /// Record a parent/child relationship seen during enumeration
+    fn update_parent_map(&mut self, _pid: u32, _parent_pid: u32) {}
+
+    /// Record a process's name and path seen during enumeration
+    fn record_process(&mut self, _pid: u32, _name: &str, _path: &str) {}
+
+    /// Forget processes that are no longer in the latest snapshot
+    fn retain_processes(&mut self, _live: &HashSet<u32>) {}
 }
 
+/// How many ancestor levels are checked for Gaming inheritance
+const MAX_INHERIT_DEPTH: usize = 3;
+
 /// Default implementation of process categorization
 pub struct DefaultCategorizer {
+    /// The built-in rules, highest priority first
+    rules: Vec<CategoryRule>,
     parent_map: HashMap<u32, u32>,
+    /// PID -> (name, path) of processes seen during enumeration
+    known_processes: HashMap<u32, (String, String)>,
 }
 
 impl DefaultCategorizer {
     pub fn new() -> Self {
         Self {
+            rules: CategoryRules::builtin().by_priority().rules,
             parent_map: HashMap::new(),
+            known_processes: HashMap::new(),
         }
     }
 
-    pub fn update_parent_map(&mut self, pid: u32, parent_pid: u32) {
-        if pid != 0 {
-            self.parent_map.insert(pid, parent_pid);
-        }
-    }
+    /// Find the nearest ancestor (up to `MAX_INHERIT_DEPTH` levels) that is a game
+    fn gaming_ancestor(&self, pid: u32) -> Option<u32> {
+        let mut current = pid;
 
-    fn is_gaming_by_name(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
+        for _ in 0..MAX_INHERIT_DEPTH {
+            let parent = match self.parent_map.get(&current) {
+                Some(&parent) if parent != 0 && parent != pid && parent != current => parent,
+                _ => return None,
+            };
 
-        // Game launchers
-        if name_lower.contains("steam")
-            || name_lower.contains("epic")
-            || name_lower.contains("origin")
-            || name_lower.contains("gog")
-            || name_lower.contains("battle.net")
-            || name_lower.contains("battlenet")
-            || name_lower.contains("uplay")
-            || name_lower.contains("ubisoft")
-        {
-            return true;
-        }
+            if let Some((name, path)) = self.known_processes.get(&parent) {
+                if self.categorize_by_rules(name, path).0 == ProcessCategory::Gaming {
+                    return Some(parent);
+                }
+            }
 
-        // Anti-cheat
-        if name_lower.contains("easyanticheat")
-            || name_lower.contains("battleye")
-            || name_lower.contains("vanguard")
-        {
-            return true;
+            current = parent;
         }
 
-        // Common game patterns
-        if name_lower.contains("game") && name_lower.contains(".exe") {
-            return true;
-        }
-
-        false
+        None
     }
 
-    fn is_gaming_by_path(&self, path: &str) -> bool {
-        let path_lower = path.to_lowercase();
+    /// Categorize from the process's own name and path only
+    ///
+    /// The first matching rule wins, except that a name that looks like a game
+    /// executable beats every rule ranked below Gaming.
+    fn categorize_by_rules(&self, name: &str, path: &str) -> (ProcessCategory, String) {
+        let matched = self
+            .rules
+            .iter()
+            .find_map(|rule| Some((rule, rule.match_reason(name, path, None)?)));
 
-        let gaming_paths = [
-            "\\steam\\",
-            "\\steamapps\\",
-            "\\steamlibrary\\",
-            "\\epic games\\",
-            "\\epicgames\\",
-            "\\origin games\\",
-            "\\gog galaxy\\",
-            "\\gog games\\",
-            "\\battle.net\\",
-            "\\ubisoft\\",
-            "\\ea games\\",
-            "\\riot games\\",
-            "\\games\\",
-            "\\my games\\",
-        ];
+        match matched {
+            Some((rule, _)) if rule.category == ProcessCategory::Critical => (
+                ProcessCategory::Critical,
+                if is_input_process(name) {
+                    "input or accessibility process".to_string()
+                } else {
+                    "critical system process".to_string()
+                },
+            ),
+            Some((rule, reason)) if rule.priority >= GAMING_PRIORITY => (rule.category, reason),
+            _ if looks_like_game(name) => (ProcessCategory::Gaming, GAME_NAME_REASON.to_string()),
+            Some((rule, reason)) => (rule.category, reason),
+            None => (ProcessCategory::Unknown, "no rule matched".to_string()),
+        }
+    }
+}
 
-        gaming_paths
-            .iter()
-            .any(|&pattern| path_lower.contains(pattern))
-    }
-
-    fn is_communication(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-
-        let communication_apps = [
-            "discord",
-            "slack",
-            "teams",
-            "telegram",
-            "signal",
-            "whatsapp",
-            "zoom",
-            "skype",
-            "mumble",
-            "teamspeak",
-            "ventrilo",
-            "element",
-            "riot",
-        ];
+/// Why a name matching `looks_like_game` is categorized as Gaming
+const GAME_NAME_REASON: &str = "name looks like a game executable";
 
-        communication_apps
-            .iter()
-            .any(|&app| name_lower.contains(app))
-    }
-
-    fn is_background_service(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-
-        let background_services = [
-            "updater",
-            "update",
-            "helper",
-            "sync",
-            "backup",
-            "nvidia",
-            "amd",
-            "geforce",
-            "radeon",
-            "onedrive",
-            "dropbox",
-            "google drive",
-            "toolbox",
-        ];
+/// Common game naming pattern, e.g. `coolgame.exe`
+fn looks_like_game(name: &str) -> bool {
+    normalize_name(name).contains("game")
+}
 
-        background_services
-            .iter()
-            .any(|&service| name_lower.contains(service))
-    }
-
-    fn is_productivity(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-
-        let productivity_apps = [
-            "chrome",
-            "firefox",
-            "edge",
-            "opera",
-            "brave",
-            "vivaldi",
-            "excel",
-            "word",
-            "powerpoint",
-            "outlook",
-            "onenote",
-            "vscode",
-            "code",
-            "pycharm",
-            "intellij",
-            "rider",
-            "sublime",
-            "spotify",
-            "vlc",
-            "itunes",
-            "notion",
-            "obsidian",
-        ];
+/// Keyboard hook, IME or accessibility host (see `INPUT_PROCESSES`)
+fn is_input_process(name: &str) -> bool {
+    let name = normalize_name(name);
+    INPUT_PROCESSES
+        .iter()
+        .any(|input| normalize_name(input) == name)
+}
 
-        productivity_apps
-            .iter()
-            .any(|&app| name_lower.contains(app))
+/// Lowercase a process name and drop a trailing `.exe`
+///
+/// Every name matcher compares normalized names, so `Steam`, `steam.exe`
+/// and `STEAM.EXE` are the same process and rules may omit the extension.
+pub fn normalize_name(raw: &str) -> String {
+    let lower = raw.trim().to_lowercase();
+    match lower.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => lower,
     }
 }
 
+/// Whether binaries signed by `publisher` must never be frozen
+pub fn is_protected_publisher(publisher: &str) -> bool {
+    let publisher = publisher.to_lowercase();
+    PROTECTED_PUBLISHERS
+        .iter()
+        .any(|protected| publisher.starts_with(protected))
+}
+
+/// First entry of `list` contained in the (lowercased) `text`
+fn find_substring(list: &[&'static str], text: &str) -> Option<&'static str> {
+    list.iter().copied().find(|pattern| text.contains(pattern))
+}
+
+fn name_reason(pattern: &str) -> String {
+    format!("matched name substring '{}'", pattern)
+}
+
+fn command_line_reason(pattern: &str) -> String {
+    format!("matched command line substring '{}'", pattern)
+}
+
 impl Default for DefaultCategorizer {
     fn default() -> Self {
         Self::new()
@@ -176,53 +339,326 @@ impl Default for DefaultCategorizer {
 }
 
 impl ProcessCategorizer for DefaultCategorizer {
-    fn categorize(&self, _pid: u32, name: &str, path: &str) -> ProcessCategory {
-        // Critical check first
-        if self.is_critical(name) {
-            return ProcessCategory::Critical;
+    fn categorize(&self, pid: u32, name: &str, path: &str) -> ProcessCategory {
+        self.categorize_with_reason(pid, name, path).0
+    }
+
+    fn categorize_with_reason(
+        &self,
+        pid: u32,
+        name: &str,
+        path: &str,
+    ) -> (ProcessCategory, String) {
+        let (category, reason) = self.categorize_by_rules(name, path);
+
+        // Children of a running game (anti-cheat helpers, sub-processes) are part of it
+        if category != ProcessCategory::Critical && category != ProcessCategory::Gaming {
+            if let Some(ancestor) = self.gaming_ancestor(pid) {
+                let ancestor_name = self
+                    .known_processes
+                    .get(&ancestor)
+                    .map(|(name, _)| name.as_str())
+                    .unwrap_or("unknown");
+                return (
+                    ProcessCategory::Gaming,
+                    format!("child of game {} (PID {})", ancestor_name, ancestor),
+                );
+            }
         }
 
-        // Gaming checks
-        if self.is_gaming_by_path(path) || self.is_gaming_by_name(name) {
-            return ProcessCategory::Gaming;
+        (category, reason)
+    }
+
+    fn categorize_with_cmdline(
+        &self,
+        pid: u32,
+        name: &str,
+        path: &str,
+        command_line: Option<&str>,
+    ) -> (ProcessCategory, String) {
+        let (category, reason) = self.categorize_with_reason(pid, name, path);
+        if category == ProcessCategory::Critical || category == ProcessCategory::Gaming {
+            return (category, reason);
         }
 
-        // Communication apps
-        if self.is_communication(name) {
-            return ProcessCategory::Communication;
+        let command_line = command_line.unwrap_or_default().to_lowercase();
+        match find_substring(GAMING_COMMAND_LINES, &command_line) {
+            Some(pattern) => (ProcessCategory::Gaming, command_line_reason(pattern)),
+            None => (category, reason),
         }
+    }
 
-        // Background services
-        if self.is_background_service(name) {
-            return ProcessCategory::BackgroundService;
+    fn categorize_all_matches(&self, name: &str, path: &str) -> Vec<(ProcessCategory, String)> {
+        let mut matches = Vec::new();
+        let mut heuristic_checked = false;
+        for rule in &self.rules {
+            if !heuristic_checked && rule.priority < GAMING_PRIORITY {
+                heuristic_checked = true;
+                if looks_like_game(name) {
+                    matches.push((ProcessCategory::Gaming, GAME_NAME_REASON.to_string()));
+                }
+            }
+            if let Some(reason) = rule.match_reason(name, path, None) {
+                matches.push((rule.category, reason));
+            }
         }
+        matches
+    }
 
-        // Productivity apps
-        if self.is_productivity(name) {
-            return ProcessCategory::Productivity;
+    fn is_critical(&self, name: &str) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.category == ProcessCategory::Critical && rule.matches_name(name))
+    }
+
+    fn update_parent_map(&mut self, pid: u32, parent_pid: u32) {
+        if pid != 0 {
+            self.parent_map.insert(pid, parent_pid);
         }
+    }
 
-        ProcessCategory::Unknown
+    fn record_process(&mut self, pid: u32, name: &str, path: &str) {
+        if pid != 0 {
+            self.known_processes
+                .insert(pid, (name.to_string(), path.to_string()));
+        }
     }
 
-    fn is_critical(&self, name: &str) -> bool {
-        let critical = [
-            "system",
-            "smss.exe",
-            "csrss.exe",
-            "wininit.exe",
-            "services.exe",
-            "lsass.exe",
-            "svchost.exe",
-            "winlogon.exe",
-            "explorer.exe",
-            "dwm.exe",
-            "textinputhost.exe",
-            "searchhost.exe",
-            "startmenuexperiencehost.exe",
-        ];
+    fn retain_processes(&mut self, live: &HashSet<u32>) {
+        self.parent_map.retain(|pid, _| live.contains(pid));
+        self.known_processes.retain(|pid, _| live.contains(pid));
+    }
+}
+
+/// A single categorization rule loaded from a rules file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryRule {
+    pub category: ProcessCategory,
+    /// Higher priorities are checked first; equal priorities keep file order
+    #[serde(default)]
+    pub priority: u32,
+    /// Substrings matched against the process name (case-insensitive, `.exe` optional)
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Substrings matched against the full executable path
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Process names matched exactly (case-insensitive, `.exe` optional)
+    #[serde(default)]
+    pub exact_names: Vec<String>,
+    /// Substrings matched against the command line, when it is known
+    #[serde(default)]
+    pub command_lines: Vec<String>,
+}
+
+impl CategoryRule {
+    fn new(category: ProcessCategory) -> Self {
+        Self {
+            category,
+            priority: 0,
+            names: Vec::new(),
+            paths: Vec::new(),
+            exact_names: Vec::new(),
+            command_lines: Vec::new(),
+        }
+    }
+
+    fn to_strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        let name = normalize_name(name);
 
-        critical.iter().any(|&c| name.eq_ignore_ascii_case(c))
+        self.exact_names.iter().any(|n| normalize_name(n) == name)
+            || self.names.iter().any(|n| name.contains(&normalize_name(n)))
+    }
+
+    fn matches(&self, name: &str, path: &str) -> bool {
+        self.match_reason(name, path, None).is_some()
+    }
+
+    /// Describe which entry of this rule matched, if any
+    fn match_reason(&self, name: &str, path: &str, command_line: Option<&str>) -> Option<String> {
+        let (kind, pattern) = self.find_match(name, path, command_line)?;
+        Some(match kind {
+            MatchKind::ExactName => format!("matched name {}", pattern),
+            MatchKind::Name => name_reason(pattern),
+            MatchKind::Path => format!("matched path {}", pattern),
+            MatchKind::CommandLine => command_line_reason(pattern),
+        })
+    }
+
+    /// First matching entry: exact names, then name, path and command-line substrings
+    fn find_match(
+        &self,
+        name: &str,
+        path: &str,
+        command_line: Option<&str>,
+    ) -> Option<(MatchKind, &str)> {
+        let name = normalize_name(name);
+        let path_lower = path.to_lowercase();
+
+        if let Some(exact) = self.exact_names.iter().find(|n| normalize_name(n) == name) {
+            return Some((MatchKind::ExactName, exact));
+        }
+        if let Some(pattern) = self
+            .names
+            .iter()
+            .find(|n| name.contains(&normalize_name(n)))
+        {
+            return Some((MatchKind::Name, pattern));
+        }
+        if let Some(pattern) = self
+            .paths
+            .iter()
+            .find(|p| path_lower.contains(&p.to_lowercase()))
+        {
+            return Some((MatchKind::Path, pattern));
+        }
+
+        let command_line = command_line?.to_lowercase();
+        self.command_lines
+            .iter()
+            .find(|c| command_line.contains(&c.to_lowercase()))
+            .map(|pattern| (MatchKind::CommandLine, pattern.as_str()))
+    }
+}
+
+/// Which list of a `CategoryRule` an entry came from
+enum MatchKind {
+    ExactName,
+    Name,
+    Path,
+    CommandLine,
+}
+
+/// Rules file layout: rules are evaluated by descending `priority`, then in
+/// file order; the first match wins
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryRules {
+    pub rules: Vec<CategoryRule>,
+}
+
+impl CategoryRules {
+    /// Sort into evaluation order (stable, so ties keep file order)
+    pub fn by_priority(mut self) -> Self {
+        self.rules.sort_by_key(|rule| Reverse(rule.priority));
+        self
+    }
+
+    /// The built-in keyword lists used by `DefaultCategorizer`
+    pub fn builtin() -> Self {
+        let mut critical = CategoryRule::new(ProcessCategory::Critical);
+        critical.priority = CRITICAL_PRIORITY;
+        critical.exact_names = CategoryRule::to_strings(CRITICAL_PROCESSES);
+
+        // Separate rule so a rules file can extend it with other hook or IME hosts
+        let mut input = CategoryRule::new(ProcessCategory::Critical);
+        input.priority = CRITICAL_PRIORITY;
+        input.exact_names = CategoryRule::to_strings(INPUT_PROCESSES);
+
+        let mut gaming = CategoryRule::new(ProcessCategory::Gaming);
+        gaming.priority = GAMING_PRIORITY;
+        gaming.names = CategoryRule::to_strings(GAMING_NAMES);
+        gaming.paths = CategoryRule::to_strings(GAMING_PATHS);
+        gaming.command_lines = CategoryRule::to_strings(GAMING_COMMAND_LINES);
+
+        let mut communication = CategoryRule::new(ProcessCategory::Communication);
+        communication.priority = COMMUNICATION_PRIORITY;
+        communication.names = CategoryRule::to_strings(COMMUNICATION_APPS);
+
+        let mut background = CategoryRule::new(ProcessCategory::BackgroundService);
+        background.priority = BACKGROUND_PRIORITY;
+        background.names = CategoryRule::to_strings(BACKGROUND_SERVICES);
+
+        let mut productivity = CategoryRule::new(ProcessCategory::Productivity);
+        productivity.priority = PRODUCTIVITY_PRIORITY;
+        productivity.names = CategoryRule::to_strings(PRODUCTIVITY_APPS);
+
+        Self {
+            rules: vec![
+                critical,
+                input,
+                gaming,
+                communication,
+                background,
+                productivity,
+            ],
+        }
+    }
+}
+
+/// Categorizer driven by user-supplied rules
+pub struct ConfigurableCategorizer {
+    rules: CategoryRules,
+}
+
+impl ConfigurableCategorizer {
+    pub fn new(rules: CategoryRules) -> Self {
+        Self {
+            rules: rules.by_priority(),
+        }
+    }
+
+    /// Load rules from a JSON file, falling back to the built-in lists if it doesn't exist
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(CategoryRules::builtin()));
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let rules: CategoryRules = serde_json::from_str(&content)?;
+        Ok(Self::new(rules))
+    }
+}
+
+impl ProcessCategorizer for ConfigurableCategorizer {
+    fn categorize(&self, _pid: u32, name: &str, path: &str) -> ProcessCategory {
+        self.rules
+            .rules
+            .iter()
+            .find(|rule| rule.matches(name, path))
+            .map(|rule| rule.category)
+            .unwrap_or(ProcessCategory::Unknown)
+    }
+
+    fn categorize_with_reason(
+        &self,
+        pid: u32,
+        name: &str,
+        path: &str,
+    ) -> (ProcessCategory, String) {
+        self.categorize_with_cmdline(pid, name, path, None)
+    }
+
+    fn categorize_with_cmdline(
+        &self,
+        _pid: u32,
+        name: &str,
+        path: &str,
+        command_line: Option<&str>,
+    ) -> (ProcessCategory, String) {
+        self.rules
+            .rules
+            .iter()
+            .find_map(|rule| Some((rule.category, rule.match_reason(name, path, command_line)?)))
+            .unwrap_or((ProcessCategory::Unknown, "no rule matched".to_string()))
+    }
+
+    fn categorize_all_matches(&self, name: &str, path: &str) -> Vec<(ProcessCategory, String)> {
+        self.rules
+            .rules
+            .iter()
+            .filter_map(|rule| Some((rule.category, rule.match_reason(name, path, None)?)))
+            .collect()
+    }
+
+    fn is_critical(&self, name: &str) -> bool {
+        self.rules
+            .rules
+            .iter()
+            .any(|rule| rule.category == ProcessCategory::Critical && rule.matches_name(name))
     }
 }
 
@@ -240,6 +676,50 @@ mod tests {
         assert!(!categorizer.is_critical("chrome.exe"));
     }
 
+    #[test]
+    fn test_input_and_accessibility_processes_are_critical() {
+        let default = DefaultCategorizer::new();
+        let configurable = ConfigurableCategorizer::new(CategoryRules::builtin());
+
+        for name in [
+            "ctfmon.exe",
+            "osk.exe",
+            "magnify.exe",
+            "narrator.exe",
+            "sethc.exe",
+        ] {
+            let path = format!("C:\\Windows\\System32\\{}", name);
+            assert_eq!(
+                default.categorize(1, name, &path),
+                ProcessCategory::Critical,
+                "{}",
+                name
+            );
+            assert_eq!(
+                configurable.categorize(1, name, &path),
+                ProcessCategory::Critical,
+                "{}",
+                name
+            );
+            assert!(default.is_critical(&name.to_uppercase()));
+        }
+    }
+
+    #[test]
+    fn test_input_rule_is_extensible() {
+        let mut rules = CategoryRules::builtin();
+        let input = rules
+            .rules
+            .iter_mut()
+            .find(|r| r.exact_names.iter().any(|n| n == "ctfmon.exe"))
+            .unwrap();
+        input.exact_names.push("myime.exe".to_string());
+
+        let categorizer = ConfigurableCategorizer::new(rules);
+        assert!(categorizer.is_critical("MyIME.exe"));
+        assert!(categorizer.is_critical("osk.exe"));
+    }
+
     #[test]
     fn test_gaming_detection_by_name() {
         let categorizer = DefaultCategorizer::new();
@@ -255,6 +735,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("STEAM.EXE"), "steam");
+        assert_eq!(normalize_name("Steam"), "steam");
+        assert_eq!(normalize_name(" battle.net "), "battle.net");
+        assert_eq!(normalize_name("exe"), "exe");
+    }
+
+    #[test]
+    fn test_name_spellings_categorize_identically() {
+        let categorizer = DefaultCategorizer::new();
+        for name in ["Steam", "steam.exe", "STEAM.EXE"] {
+            assert_eq!(
+                categorizer.categorize_with_reason(1234, name, ""),
+                (
+                    ProcessCategory::Gaming,
+                    "matched name substring 'steam'".to_string()
+                ),
+                "{}",
+                name
+            );
+        }
+
+        // Exact-name rules still need the whole name, with or without extension
+        for name in ["explorer", "Explorer.exe", "EXPLORER.EXE"] {
+            assert!(categorizer.is_critical(name), "{}", name);
+        }
+        assert!(!categorizer.is_critical("explorer2.exe"));
+    }
+
+    #[test]
+    fn test_rules_may_omit_or_include_extension() {
+        let mut with_ext = CategoryRule::new(ProcessCategory::Communication);
+        with_ext.names = vec!["Zoom.exe".to_string()];
+        let mut without_ext = CategoryRule::new(ProcessCategory::Critical);
+        without_ext.exact_names = vec!["MyService".to_string()];
+        let categorizer = ConfigurableCategorizer::new(CategoryRules {
+            rules: vec![with_ext, without_ext],
+        });
+
+        assert_eq!(
+            categorizer.categorize(1, "zoom.exe", ""),
+            ProcessCategory::Communication
+        );
+        assert_eq!(
+            categorizer.categorize(1, "ZOOM", ""),
+            ProcessCategory::Communication
+        );
+        assert!(categorizer.is_critical("myservice.exe"));
+    }
+
     #[test]
     fn test_gaming_detection_by_path() {
         let categorizer = DefaultCategorizer::new();
@@ -344,4 +875,432 @@ mod tests {
             ProcessCategory::Unknown
         );
     }
+
+    fn record(categorizer: &mut DefaultCategorizer, pid: u32, parent: u32, name: &str) {
+        categorizer.update_parent_map(pid, parent);
+        categorizer.record_process(pid, name, &format!("C:\\Apps\\{}", name));
+    }
+
+    #[test]
+    fn test_gaming_inherited_from_ancestors() {
+        let mut categorizer = DefaultCategorizer::new();
+        record(&mut categorizer, 100, 4, "game.exe");
+        record(&mut categorizer, 101, 100, "launcher.exe");
+        record(&mut categorizer, 102, 101, "helper.exe");
+
+        assert_eq!(
+            categorizer.categorize(101, "launcher.exe", "C:\\Apps\\launcher.exe"),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            categorizer.categorize(102, "helper.exe", "C:\\Apps\\helper.exe"),
+            ProcessCategory::Gaming
+        );
+    }
+
+    #[test]
+    fn test_gaming_inheritance_depth_limit() {
+        let mut categorizer = DefaultCategorizer::new();
+        record(&mut categorizer, 100, 4, "game.exe");
+        record(&mut categorizer, 101, 100, "a.exe");
+        record(&mut categorizer, 102, 101, "b.exe");
+        record(&mut categorizer, 103, 102, "c.exe");
+        record(&mut categorizer, 104, 103, "d.exe");
+
+        assert_eq!(
+            categorizer.categorize(103, "c.exe", "C:\\Apps\\c.exe"),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            categorizer.categorize(104, "d.exe", "C:\\Apps\\d.exe"),
+            ProcessCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn test_gaming_inheritance_missing_parent_and_cycles() {
+        let mut categorizer = DefaultCategorizer::new();
+        // Parent 50 was never recorded
+        record(&mut categorizer, 200, 50, "orphan.exe");
+        // 300 and 301 are each other's parent
+        record(&mut categorizer, 300, 301, "x.exe");
+        record(&mut categorizer, 301, 300, "y.exe");
+
+        assert_eq!(
+            categorizer.categorize(200, "orphan.exe", "C:\\Apps\\orphan.exe"),
+            ProcessCategory::Unknown
+        );
+        assert_eq!(
+            categorizer.categorize(300, "x.exe", "C:\\Apps\\x.exe"),
+            ProcessCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn test_critical_child_of_game_stays_critical() {
+        let mut categorizer = DefaultCategorizer::new();
+        record(&mut categorizer, 100, 4, "game.exe");
+        record(&mut categorizer, 101, 100, "csrss.exe");
+
+        assert_eq!(
+            categorizer.categorize(101, "csrss.exe", "C:\\Windows\\csrss.exe"),
+            ProcessCategory::Critical
+        );
+    }
+
+    #[test]
+    fn test_configurable_categorizer_from_file() {
+        let path = std::env::temp_dir().join("smartfreeze_test_rules.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "rules": [
+                    {"category": "Critical", "exact_names": ["myvpn.exe"]},
+                    {"category": "Gaming", "names": ["MyEmulator"], "paths": ["\\roms\\"]},
+                    {"category": "BackgroundService", "names": ["customsync"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let categorizer = ConfigurableCategorizer::from_file(&path).unwrap();
+
+        assert_eq!(
+            categorizer.categorize(1, "myemulator-x64.exe", "C:\\Emu\\myemulator-x64.exe"),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            categorizer.categorize(1, "player.exe", "D:\\ROMS\\player.exe"),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            categorizer.categorize(1, "CustomSync.exe", "C:\\Tools\\CustomSync.exe"),
+            ProcessCategory::BackgroundService
+        );
+        assert!(categorizer.is_critical("MyVPN.exe"));
+        // Built-in keywords are not applied when a rules file is present
+        assert_eq!(
+            categorizer.categorize(1, "discord.exe", "C:\\Discord\\discord.exe"),
+            ProcessCategory::Unknown
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_configurable_categorizer_missing_file_uses_builtin() {
+        let path = std::env::temp_dir().join("smartfreeze_missing_rules.json");
+        let _ = std::fs::remove_file(&path);
+
+        let categorizer = ConfigurableCategorizer::from_file(&path).unwrap();
+
+        assert!(categorizer.is_critical("explorer.exe"));
+        assert_eq!(
+            categorizer.categorize(1, "Discord.exe", "C:\\Discord\\Discord.exe"),
+            ProcessCategory::Communication
+        );
+        assert_eq!(
+            categorizer.categorize(1, "MyGame.exe", "D:\\SteamLibrary\\MyGame.exe"),
+            ProcessCategory::Gaming
+        );
+    }
+
+    #[test]
+    fn test_configurable_categorizer_invalid_file() {
+        let path = std::env::temp_dir().join("smartfreeze_invalid_rules.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let result = ConfigurableCategorizer::from_file(&path);
+        assert!(matches!(
+            result,
+            Err(crate::SmartFreezeError::Serialization(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_categorize_with_reason_branches() {
+        let categorizer = DefaultCategorizer::new();
+        let reason = |name: &str, path: &str| categorizer.categorize_with_reason(1234, name, path);
+
+        assert_eq!(
+            reason("explorer.exe", "C:\\Windows\\explorer.exe"),
+            (
+                ProcessCategory::Critical,
+                "critical system process".to_string()
+            )
+        );
+        assert_eq!(
+            reason("CTFMON.EXE", "C:\\Windows\\System32\\ctfmon.exe"),
+            (
+                ProcessCategory::Critical,
+                "input or accessibility process".to_string()
+            )
+        );
+        assert_eq!(
+            reason("MyGame.exe", "D:\\SteamLibrary\\steamapps\\MyGame.exe"),
+            (
+                ProcessCategory::Gaming,
+                "matched path \\steamapps\\".to_string()
+            )
+        );
+        assert_eq!(
+            reason("EasyAntiCheat.exe", "C:\\EAC\\EasyAntiCheat.exe"),
+            (
+                ProcessCategory::Gaming,
+                "matched name substring 'easyanticheat'".to_string()
+            )
+        );
+        assert_eq!(
+            reason("coolgame.exe", "C:\\Apps\\coolgame.exe"),
+            (
+                ProcessCategory::Gaming,
+                "name looks like a game executable".to_string()
+            )
+        );
+        assert_eq!(
+            reason("Discord.exe", "C:\\Discord\\Discord.exe"),
+            (
+                ProcessCategory::Communication,
+                "matched name substring 'discord'".to_string()
+            )
+        );
+        assert_eq!(
+            reason("OneDrive.exe", "C:\\OneDrive\\OneDrive.exe"),
+            (
+                ProcessCategory::BackgroundService,
+                "matched name substring 'onedrive'".to_string()
+            )
+        );
+        assert_eq!(
+            reason("chrome.exe", "C:\\Chrome\\chrome.exe"),
+            (
+                ProcessCategory::Productivity,
+                "matched name substring 'chrome'".to_string()
+            )
+        );
+        assert_eq!(
+            reason("mystery.exe", "C:\\mystery.exe"),
+            (ProcessCategory::Unknown, "no rule matched".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_name_reports_every_match() {
+        let name = "RiotClientServices.exe";
+        let path = "C:\\Riot Games\\Riot Client\\RiotClientServices.exe";
+        let expected = vec![
+            (
+                ProcessCategory::Gaming,
+                "matched path \\riot games\\".to_string(),
+            ),
+            (
+                ProcessCategory::Communication,
+                "matched name substring 'riot'".to_string(),
+            ),
+        ];
+
+        let default = DefaultCategorizer::new();
+        assert_eq!(default.categorize(1, name, path), ProcessCategory::Gaming);
+        assert_eq!(default.categorize_all_matches(name, path), expected);
+
+        let configurable = ConfigurableCategorizer::new(CategoryRules::builtin());
+        assert_eq!(
+            configurable.categorize(1, name, path),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(configurable.categorize_all_matches(name, path), expected);
+    }
+
+    #[test]
+    fn test_rule_priority_overrides_file_order() {
+        let rules: CategoryRules = serde_json::from_str(
+            r#"{"rules": [
+                {"category": "Communication", "names": ["riot"]},
+                {"category": "Gaming", "names": ["riot"], "priority": 10},
+                {"category": "Productivity", "names": ["client"], "priority": 10}
+            ]}"#,
+        )
+        .unwrap();
+        let categorizer = ConfigurableCategorizer::new(rules);
+
+        // Highest priority wins; equal priorities keep file order
+        assert_eq!(
+            categorizer.categorize(1, "RiotClient.exe", ""),
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            categorizer.categorize_all_matches("RiotClient.exe", ""),
+            vec![
+                (
+                    ProcessCategory::Gaming,
+                    "matched name substring 'riot'".to_string()
+                ),
+                (
+                    ProcessCategory::Productivity,
+                    "matched name substring 'client'".to_string()
+                ),
+                (
+                    ProcessCategory::Communication,
+                    "matched name substring 'riot'".to_string()
+                ),
+            ]
+        );
+        assert!(categorizer
+            .categorize_all_matches("mystery.exe", "C:\\mystery.exe")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_game_name_heuristic_ranks_below_gaming_rules() {
+        let categorizer = DefaultCategorizer::new();
+
+        assert_eq!(
+            categorizer
+                .categorize_all_matches("gamebar_update.exe", "C:\\Apps\\gamebar_update.exe"),
+            vec![
+                (
+                    ProcessCategory::Gaming,
+                    "name looks like a game executable".to_string()
+                ),
+                (
+                    ProcessCategory::BackgroundService,
+                    "matched name substring 'update'".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            categorizer.categorize(1, "gamebar_update.exe", "C:\\Apps\\gamebar_update.exe"),
+            ProcessCategory::Gaming
+        );
+    }
+
+    #[test]
+    fn test_exited_game_no_longer_inherited() {
+        let mut categorizer = DefaultCategorizer::new();
+        record(&mut categorizer, 100, 4, "game.exe");
+        record(&mut categorizer, 101, 100, "overlay.exe");
+        assert_eq!(
+            categorizer.categorize(101, "overlay.exe", "C:\\Apps\\overlay.exe"),
+            ProcessCategory::Gaming
+        );
+
+        // The game exits while its overlay keeps running
+        categorizer.retain_processes(&HashSet::from([101]));
+        assert_eq!(
+            categorizer.categorize(101, "overlay.exe", "C:\\Apps\\overlay.exe"),
+            ProcessCategory::Unknown
+        );
+
+        // Its PID is reused by an unrelated app
+        record(&mut categorizer, 100, 4, "notepad.exe");
+        assert_eq!(
+            categorizer.categorize(101, "overlay.exe", "C:\\Apps\\overlay.exe"),
+            ProcessCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn test_categorize_with_reason_inherited() {
+        let mut categorizer = DefaultCategorizer::new();
+        categorizer.record_process(100, "steam.exe", "C:\\Steam\\steam.exe");
+        categorizer.record_process(200, "helper.exe", "C:\\Steam\\bin\\helper.exe");
+        categorizer.update_parent_map(300, 100);
+
+        assert_eq!(
+            categorizer.categorize_with_reason(300, "crashpad.exe", "C:\\crashpad.exe"),
+            (
+                ProcessCategory::Gaming,
+                "child of game steam.exe (PID 100)".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_configurable_categorize_with_reason() {
+        let categorizer = ConfigurableCategorizer::new(CategoryRules::builtin());
+
+        assert_eq!(
+            categorizer.categorize_with_reason(1, "Explorer.exe", ""),
+            (
+                ProcessCategory::Critical,
+                "matched name explorer.exe".to_string()
+            )
+        );
+        assert_eq!(
+            categorizer.categorize_with_reason(1, "x.exe", "D:\\SteamLibrary\\x.exe"),
+            (
+                ProcessCategory::Gaming,
+                "matched path \\steamlibrary\\".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_game_jar_command_line_is_gaming() {
+        let javaw = "C:\\Program Files\\Java\\bin\\javaw.exe";
+        let command_line = "\"javaw.exe\" -Xmx4G -cp C:\\Users\\User\\AppData\\Roaming\\.minecraft\\versions\\1.20.4\\1.20.4.jar net.minecraft.client.main.Main";
+
+        let categorizer = DefaultCategorizer::new();
+        assert_eq!(
+            categorizer.categorize_with_cmdline(1, "javaw.exe", javaw, None),
+            (ProcessCategory::Unknown, "no rule matched".to_string())
+        );
+        assert_eq!(
+            categorizer.categorize_with_cmdline(1, "javaw.exe", javaw, Some(command_line)),
+            (
+                ProcessCategory::Gaming,
+                "matched command line substring 'net.minecraft'".to_string()
+            )
+        );
+        // A command line never overrides a critical process
+        assert_eq!(
+            categorizer
+                .categorize_with_cmdline(1, "explorer.exe", "", Some(command_line))
+                .0,
+            ProcessCategory::Critical
+        );
+
+        let configurable = ConfigurableCategorizer::new(CategoryRules::builtin());
+        assert_eq!(
+            configurable
+                .categorize_with_cmdline(1, "javaw.exe", javaw, Some(command_line))
+                .0,
+            ProcessCategory::Gaming
+        );
+        assert_eq!(
+            configurable
+                .categorize_with_cmdline(1, "javaw.exe", javaw, Some("javaw.exe -jar tool.jar"))
+                .0,
+            ProcessCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn test_publisher_categorization() {
+        let categorizer = DefaultCategorizer::new();
+
+        for publisher in [
+            "Microsoft Windows",
+            "Microsoft Windows Publisher",
+            "NVIDIA Corporation",
+            "Advanced Micro Devices, Inc.",
+            "Realtek Semiconductor Corp.",
+        ] {
+            assert_eq!(
+                categorizer.categorize_publisher(publisher),
+                Some(ProcessCategory::Critical),
+                "{}",
+                publisher
+            );
+        }
+
+        assert_eq!(
+            categorizer.categorize_publisher("Microsoft Corporation"),
+            None
+        );
+        assert_eq!(categorizer.categorize_publisher("Discord Inc."), None);
+        assert_eq!(categorizer.categorize_publisher(""), None);
+    }
 }
@@ -1,6 +1,13 @@
 //! CLI argument parsing and configuration
 
-use clap::Parser;
+use crate::freeze_engine::FreezeMode;
+use crate::persistence::{FileStatePersistence, StateLocation, STATE_PATH_ENV};
+use crate::{Result, SmartFreezeError};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
 /// CLI arguments
 #[derive(Parser, Debug)]
@@ -29,6 +36,42 @@ pub struct Args {
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Check which processes have all threads suspended (slower; shown with --verbose)
+    #[arg(long)]
+    pub detect_suspended: bool,
+
+    /// List processes that can't be opened as "<PID n>" placeholders (never frozen)
+    #[arg(long)]
+    pub show_inaccessible: bool,
+
+    /// Sort by CPU usage instead of memory and show a CPU column
+    #[arg(long)]
+    pub top_cpu: bool,
+
+    /// Only list (or freeze) processes using at least this much CPU (percent of one core)
+    #[arg(long, value_name = "PERCENT")]
+    pub min_cpu: Option<f64>,
+
+    /// With --format json, print the flat process list instead of the freeze plan
+    #[arg(long)]
+    pub json_legacy: bool,
+
+    /// Show how one process (name substring or PID) is categorized and whether it would be frozen
+    #[arg(long, value_name = "NAME_OR_PID")]
+    pub explain: Option<String>,
+
+    /// Pick processes to freeze/resume from a live list (needs the `tui` feature)
+    #[arg(long)]
+    pub interactive: bool,
+
+    /// List canned demo processes instead of real ones; runs on any OS (needs the `testing` feature)
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Redraw the table every --interval seconds until Ctrl+C
+    #[arg(long)]
+    pub watch: bool,
+
     /// Action to perform on processes
     #[arg(long, value_enum)]
     pub action: Option<Action>,
@@ -37,25 +80,318 @@ pub struct Args {
     #[arg(long)]
     pub pid: Option<u32>,
 
+    /// With --action freeze --pid, also freeze critical, foreground or unlisted processes
+    #[arg(long)]
+    pub force: bool,
+
+    /// Freeze/resume every process whose name contains this (used with --action)
+    #[arg(long, value_name = "SUBSTRING", conflicts_with = "pid")]
+    pub name: Option<String>,
+
     /// Run as background daemon with system tray
     #[arg(short, long)]
     pub daemon: bool,
 
+    /// Resume every process recorded in the saved state file, then clear it
+    #[arg(long)]
+    pub resume_all: bool,
+
+    /// List processes recorded as frozen in the saved state file
+    #[arg(long)]
+    pub list_frozen: bool,
+
+    /// Print whether the saved state file records this PID as frozen; exits 0 if
+    /// it does, 1 if not, 6 if the file can't be read. The record isn't checked
+    /// against running processes, so a PID that has exited since can still be `yes`
+    #[arg(long, value_name = "PID")]
+    pub is_frozen: Option<u32>,
+
+    /// Saved state file used by the daemon, --resume-all, --list-frozen and --is-frozen
+    /// (default: $SMARTFREEZE_STATE, else smartfreeze_state.json in the temp dir)
+    #[arg(long, value_name = "PATH")]
+    pub state_file: Option<PathBuf>,
+
+    /// Keep the saved state in HKCU\Software\SmartFreeze instead of a file, so
+    /// temp-dir cleanup can't lose it; pass it to every command sharing the state
+    #[arg(long, conflicts_with = "state_file")]
+    pub state_registry: bool,
+
+    /// Show the last N freezes and resumes from the audit log (default: 20)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "20")]
+    pub history: Option<usize>,
+
     /// Install to Windows startup (auto-start on boot)
     #[arg(long)]
     pub install_startup: bool,
 
-    /// Uninstall from Windows startup
+    /// Uninstall from Windows startup, resuming anything left frozen
     #[arg(long)]
     pub uninstall_startup: bool,
 
-    /// Check interval in seconds for daemon mode (default: 60)
+    /// Register an elevated logon task instead of the Run key (requires administrator)
+    #[arg(long)]
+    pub install_task: bool,
+
+    /// Remove the elevated logon task
+    #[arg(long)]
+    pub uninstall_task: bool,
+
+    /// Install/uninstall startup for all users (HKLM, requires administrator)
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// Check interval in seconds for daemon and watch mode (default: 60)
     #[arg(long, default_value_t = 60)]
     pub interval: u64,
 
     /// Keep communication apps running (Discord, Teams, Slack, etc.)
     #[arg(long)]
     pub keep_communication: bool,
+
+    /// With --keep-communication, still freeze the helper processes of communication apps
+    #[arg(long)]
+    pub freeze_comm_children: bool,
+
+    /// Keep apps with a visible (non-minimized) window running, e.g. on a second monitor
+    #[arg(long)]
+    pub keep_visible_windows: bool,
+
+    /// In daemon mode, also treat any fullscreen foreground app as a game
+    #[arg(long)]
+    pub trigger_fullscreen: bool,
+
+    /// In daemon mode, freeze only enough processes to reach this much free RAM
+    #[arg(long, value_name = "MB")]
+    pub target_free_mb: Option<u64>,
+
+    /// Never freeze more than this much memory in total (largest processes first)
+    #[arg(long, value_name = "MB")]
+    pub max_total_freeze_mb: Option<u64>,
+
+    /// In daemon mode, show a desktop notification after freezing or resuming
+    #[arg(long)]
+    pub notifications: bool,
+
+    /// In daemon mode, only log what would be frozen; nothing is suspended or saved
+    #[arg(long)]
+    pub daemon_dry_run: bool,
+
+    /// In daemon mode, start frozen apps again from their saved path if they were killed
+    #[arg(long)]
+    pub relaunch_killed: bool,
+
+    /// In daemon mode, empty frozen apps' working sets and report the RAM actually reclaimed
+    #[arg(long)]
+    pub trim_after_freeze: bool,
+
+    /// In daemon mode, global hotkey toggling auto-freeze (default: Ctrl+Alt+F)
+    #[arg(long, value_name = "COMBO")]
+    pub hotkey: Option<String>,
+
+    /// In daemon mode, wait this long after a game starts before freezing
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    pub freeze_delay_secs: u64,
+
+    /// In daemon mode, resume a process once it has been frozen this long
+    #[arg(long, value_name = "SECS")]
+    pub max_freeze_secs: Option<u64>,
+
+    /// In daemon mode, also freeze after this long without keyboard or mouse input
+    #[arg(long, value_name = "SECS")]
+    pub idle_trigger_secs: Option<u64>,
+
+    /// Freeze mode to use (rejected if the platform controller can't perform it)
+    #[arg(long, value_enum)]
+    pub mode: Option<FreezeMode>,
+
+    /// With --mode soft, also suspend processes after lowering their priority
+    #[arg(long)]
+    pub escalate_soft_freeze: bool,
+
+    /// Append every freeze decision (input processes and reasons) to this file
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+
+    /// Load categorization rules from a JSON file (built-in rules if missing)
+    #[arg(long, value_name = "FILE")]
+    pub rules: Option<PathBuf>,
+
+    /// Disable colored table output (also honored via the NO_COLOR env var)
+    #[arg(long)]
+    pub no_color: bool,
+}
+
+/// Persistent defaults from `smartfreeze.toml`; command-line flags win over them
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    pub threshold: Option<u64>,
+    pub format: Option<OutputFormat>,
+    pub top: Option<usize>,
+    pub interval: Option<u64>,
+    pub keep_communication: Option<bool>,
+    pub freeze_comm_children: Option<bool>,
+    pub keep_visible_windows: Option<bool>,
+    pub trigger_fullscreen: Option<bool>,
+    pub notifications: Option<bool>,
+    pub relaunch_killed: Option<bool>,
+    pub trim_after_freeze: Option<bool>,
+    pub target_free_mb: Option<u64>,
+    pub max_total_freeze_mb: Option<u64>,
+    pub max_freeze_secs: Option<u64>,
+    pub freeze_delay_secs: Option<u64>,
+    pub idle_trigger_secs: Option<u64>,
+    pub mode: Option<FreezeMode>,
+    pub escalate_soft_freeze: Option<bool>,
+    pub rules: Option<PathBuf>,
+    pub no_color: Option<bool>,
+    pub hotkey: Option<String>,
+}
+
+impl FileConfig {
+    /// `%APPDATA%\SmartFreeze\smartfreeze.toml`, or the temp dir if APPDATA is unset
+    pub fn default_path() -> PathBuf {
+        let mut path = match std::env::var_os("APPDATA") {
+            Some(appdata) => PathBuf::from(appdata).join("SmartFreeze"),
+            None => std::env::temp_dir(),
+        };
+        path.push("smartfreeze.toml");
+        path
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Load the config file, falling back to built-in defaults if it is missing
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Saved state location: the registry with `--state-registry` (Windows only),
+/// otherwise the state file
+pub fn resolve_state_location(args: &Args) -> StateLocation {
+    #[cfg(windows)]
+    if args.state_registry {
+        return StateLocation::Registry;
+    }
+    StateLocation::File(resolve_state_path(args))
+}
+
+/// State file location: `--state-file`, then `SMARTFREEZE_STATE`, then the temp dir
+pub fn resolve_state_path(args: &Args) -> PathBuf {
+    state_path_from(args.state_file.as_deref(), std::env::var_os(STATE_PATH_ENV))
+}
+
+fn state_path_from(flag: Option<&Path>, env: Option<OsString>) -> PathBuf {
+    match (flag, env) {
+        (Some(path), _) => path.to_path_buf(),
+        (None, Some(path)) if !path.is_empty() => PathBuf::from(path),
+        _ => FileStatePersistence::default_path(),
+    }
+}
+
+/// Exit codes of the action, `--resume-all` and `--explain` paths, for scripts
+pub const EXIT_SUCCESS: i32 = 0;
+/// Any failure without a more specific code
+pub const EXIT_FAILURE: i32 = 1;
+/// Bad or unusable arguments (also what clap uses for parse errors)
+pub const EXIT_INVALID_ARGS: i32 = 2;
+pub const EXIT_NOT_FOUND: i32 = 3;
+pub const EXIT_ACCESS_DENIED: i32 = 4;
+/// A batch operation where some processes succeeded and some failed
+pub const EXIT_PARTIAL_FAILURE: i32 = 5;
+/// The saved state file exists but couldn't be read or parsed (`--is-frozen`)
+pub const EXIT_STATE_UNREADABLE: i32 = 6;
+
+/// Exit code reporting `err`
+pub fn exit_code_for(err: &SmartFreezeError) -> i32 {
+    match err {
+        SmartFreezeError::ProcessNotFound(_) => EXIT_NOT_FOUND,
+        SmartFreezeError::AccessDenied { .. } => EXIT_ACCESS_DENIED,
+        // Refused without --force, or asked for something this system can't do
+        SmartFreezeError::ProtectedProcess { .. }
+        | SmartFreezeError::UnsupportedMode(_)
+        | SmartFreezeError::InvalidHotkey { .. }
+        | SmartFreezeError::Config(_) => EXIT_INVALID_ARGS,
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// Exit code for a batch: success, partial failure, or the first error's code
+/// when nothing succeeded
+pub fn batch_exit_code(succeeded: usize, errors: &[SmartFreezeError]) -> i32 {
+    match errors.first() {
+        None => EXIT_SUCCESS,
+        Some(_) if succeeded > 0 => EXIT_PARTIAL_FAILURE,
+        Some(first) => exit_code_for(first),
+    }
+}
+
+impl Args {
+    /// Parse the command line, filling options it doesn't set from the config file
+    pub fn parse_with_config() -> Self {
+        let matches = Self::command().get_matches();
+        let args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+        let path = FileConfig::default_path();
+        match FileConfig::load_from(&path) {
+            Ok(config) => args.with_config_defaults(&matches, &config),
+            Err(e) => {
+                eprintln!("Warning: ignoring {}: {}", path.display(), e);
+                args
+            }
+        }
+    }
+
+    /// Apply file values to every option not given on the command line
+    pub fn with_config_defaults(mut self, matches: &ArgMatches, config: &FileConfig) -> Self {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        macro_rules! fill {
+            ($field:ident) => {
+                if !from_cli(stringify!($field)) {
+                    if let Some(value) = config.$field.clone() {
+                        self.$field = value;
+                    }
+                }
+            };
+            ($field:ident, optional) => {
+                if !from_cli(stringify!($field)) && config.$field.is_some() {
+                    self.$field = config.$field.clone();
+                }
+            };
+        }
+
+        fill!(threshold);
+        fill!(format);
+        fill!(top);
+        fill!(interval);
+        fill!(keep_communication);
+        fill!(freeze_comm_children);
+        fill!(keep_visible_windows);
+        fill!(trigger_fullscreen);
+        fill!(notifications);
+        fill!(relaunch_killed);
+        fill!(trim_after_freeze);
+        fill!(no_color);
+        fill!(freeze_delay_secs);
+        fill!(escalate_soft_freeze);
+        fill!(target_free_mb, optional);
+        fill!(max_total_freeze_mb, optional);
+        fill!(max_freeze_secs, optional);
+        fill!(idle_trigger_secs, optional);
+        fill!(mode, optional);
+        fill!(rules, optional);
+        fill!(hotkey, optional);
+
+        self
+    }
 }
 
 /// Actions that can be performed on processes
@@ -68,7 +404,8 @@ pub enum Action {
 }
 
 /// Output format options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Human-readable table format
     Table,
@@ -76,4 +413,201 @@ pub enum OutputFormat {
     Json,
     /// CSV format
     Csv,
+    /// GitHub-flavored Markdown table
+    Markdown,
+    /// Newline-delimited JSON (one compact object per process)
+    Ndjson,
+    /// Prometheus text exposition format (memory gauges)
+    Prometheus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+threshold = 250
+interval = 30
+keep_communication = true
+format = "json"
+mode = "throttle"
+hotkey = "Ctrl+Shift+F9"
+"#;
+
+    fn parse(argv: &[&str], config: &FileConfig) -> Args {
+        let matches = Args::command().try_get_matches_from(argv).unwrap();
+        Args::from_arg_matches(&matches)
+            .unwrap()
+            .with_config_defaults(&matches, config)
+    }
+
+    #[test]
+    fn test_config_file_fills_defaults() {
+        let config = FileConfig::from_toml(SAMPLE).unwrap();
+        let args = parse(&["smart-freeze"], &config);
+
+        assert_eq!(args.threshold, 250);
+        assert_eq!(args.interval, 30);
+        assert!(args.keep_communication);
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.mode, Some(FreezeMode::Throttle));
+        assert_eq!(args.hotkey.as_deref(), Some("Ctrl+Shift+F9"));
+        // Not in the file: built-in default
+        assert_eq!(args.top, 10);
+    }
+
+    #[test]
+    fn test_cli_overrides_config_file() {
+        let config = FileConfig::from_toml(SAMPLE).unwrap();
+        let args = parse(
+            &["smart-freeze", "--threshold", "500", "--format", "csv"],
+            &config,
+        );
+
+        assert_eq!(args.threshold, 500);
+        assert_eq!(args.format, OutputFormat::Csv);
+        assert_eq!(args.interval, 30);
+    }
+
+    #[test]
+    fn test_missing_config_file_uses_builtin_defaults() {
+        let path = std::env::temp_dir().join("smartfreeze_test_missing.toml");
+        let config = FileConfig::load_from(&path).unwrap();
+        assert_eq!(config, FileConfig::default());
+
+        let args = parse(&["smart-freeze"], &config);
+        assert_eq!(args.threshold, 100);
+        assert_eq!(args.interval, 60);
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_keys() {
+        assert!(FileConfig::from_toml("treshold = 5").is_err());
+    }
+
+    #[test]
+    fn test_state_path_precedence() {
+        let flag = Path::new("D:\\flag.json");
+        let env = || Some(OsString::from("D:\\env.json"));
+
+        assert_eq!(state_path_from(Some(flag), env()), flag);
+        assert_eq!(state_path_from(None, env()), PathBuf::from("D:\\env.json"));
+        assert_eq!(
+            state_path_from(None, None),
+            FileStatePersistence::default_path()
+        );
+        // An empty variable counts as unset
+        assert_eq!(
+            state_path_from(None, Some(OsString::new())),
+            FileStatePersistence::default_path()
+        );
+    }
+
+    #[test]
+    fn test_state_registry_excludes_state_file() {
+        assert!(Args::try_parse_from(["smart-freeze", "--state-registry"]).is_ok());
+        assert!(Args::try_parse_from([
+            "smart-freeze",
+            "--state-registry",
+            "--state-file",
+            "D:\\state.json"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_history_count_is_optional() {
+        let config = FileConfig::default();
+        assert_eq!(parse(&["smart-freeze"], &config).history, None);
+        assert_eq!(
+            parse(&["smart-freeze", "--history"], &config).history,
+            Some(20)
+        );
+        assert_eq!(
+            parse(&["smart-freeze", "--history", "5"], &config).history,
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_exit_code_for_each_error() {
+        let cases = [
+            (SmartFreezeError::ProcessNotFound(7), EXIT_NOT_FOUND),
+            (
+                SmartFreezeError::AccessDenied { pid: 7 },
+                EXIT_ACCESS_DENIED,
+            ),
+            (
+                SmartFreezeError::ProtectedProcess {
+                    pid: 7,
+                    reason: "foreground process".to_string(),
+                },
+                EXIT_INVALID_ARGS,
+            ),
+            (
+                SmartFreezeError::UnsupportedMode(FreezeMode::Throttle),
+                EXIT_INVALID_ARGS,
+            ),
+            (
+                SmartFreezeError::InvalidHotkey {
+                    hotkey: "Ctrl+".to_string(),
+                    reason: "missing key",
+                },
+                EXIT_INVALID_ARGS,
+            ),
+            (
+                SmartFreezeError::Config(toml::from_str::<FileConfig>("bogus = 1").unwrap_err()),
+                EXIT_INVALID_ARGS,
+            ),
+            (SmartFreezeError::AlreadyFrozen { pid: 7 }, EXIT_FAILURE),
+            (
+                SmartFreezeError::FreezeFailed {
+                    pid: 7,
+                    reason: "SuspendThread failed".to_string(),
+                },
+                EXIT_FAILURE,
+            ),
+            (
+                SmartFreezeError::ResumeFailed {
+                    pid: 7,
+                    reason: "no threads".to_string(),
+                },
+                EXIT_FAILURE,
+            ),
+            (
+                SmartFreezeError::Win32 {
+                    call: "OpenProcess",
+                    code: 5,
+                },
+                EXIT_FAILURE,
+            ),
+            (
+                SmartFreezeError::Io(std::io::Error::other("disk")),
+                EXIT_FAILURE,
+            ),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(exit_code_for(&error), code, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_batch_exit_code() {
+        assert_eq!(batch_exit_code(3, &[]), EXIT_SUCCESS);
+        assert_eq!(
+            batch_exit_code(2, &[SmartFreezeError::AccessDenied { pid: 7 }]),
+            EXIT_PARTIAL_FAILURE
+        );
+        assert_eq!(
+            batch_exit_code(
+                0,
+                &[
+                    SmartFreezeError::AccessDenied { pid: 7 },
+                    SmartFreezeError::ProcessNotFound(8),
+                ]
+            ),
+            EXIT_ACCESS_DENIED
+        );
+    }
 }
@@ -0,0 +1,220 @@
+//! Settings resolved from the command line and `smartfreeze.toml`
+
+use crate::cli::{resolve_state_location, Args};
+use crate::freeze_engine::FreezeConfig;
+use crate::persistence::StateLocation;
+use std::path::PathBuf;
+
+#[cfg(windows)]
+use crate::categorization::{ConfigurableCategorizer, DefaultCategorizer};
+#[cfg(windows)]
+use crate::freeze_engine::FreezeEngine;
+#[cfg(windows)]
+use crate::trace::FileDecisionTrace;
+#[cfg(windows)]
+use crate::windows::{WindowsProcessController, WindowsProcessEnumerator};
+
+/// When the daemon looks for games and how the user toggles it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerConfig {
+    /// Seconds between checks in daemon and watch mode
+    pub interval_secs: u64,
+    /// Global hotkey toggling auto-freeze (`None` uses the default combo)
+    pub hotkey: Option<String>,
+}
+
+/// Everything needed to build an engine and run any mode
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub freeze: FreezeConfig,
+    pub triggers: TriggerConfig,
+    /// Where frozen PIDs are persisted for crash recovery
+    pub state: StateLocation,
+    /// Categorization rules file (built-in rules if `None`)
+    pub rules_path: Option<PathBuf>,
+    /// Decision trace file
+    pub trace_path: Option<PathBuf>,
+    /// Mark processes already suspended by another tool or an earlier run
+    pub detect_suspended: bool,
+    /// List processes that couldn't be opened instead of hiding them
+    pub show_inaccessible: bool,
+}
+
+impl Config {
+    /// Map parsed arguments (file defaults already applied) onto a config
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            freeze: FreezeConfig {
+                min_memory_mb: args.threshold,
+                keep_communication: args.keep_communication,
+                freeze_comm_children: args.freeze_comm_children,
+                keep_visible_windows: args.keep_visible_windows,
+                trigger_on_fullscreen: args.trigger_fullscreen,
+                mode: args.mode,
+                escalate_soft_freeze: args.escalate_soft_freeze,
+                min_cpu_percent: args.min_cpu,
+                target_free_mb: args.target_free_mb,
+                max_total_freeze_mb: args.max_total_freeze_mb,
+                max_freeze_secs: args.max_freeze_secs,
+                freeze_delay_secs: args.freeze_delay_secs,
+                idle_trigger_secs: args.idle_trigger_secs,
+                notifications: args.notifications,
+                dry_run: args.daemon_dry_run,
+                relaunch_killed: args.relaunch_killed,
+                trim_after_freeze: args.trim_after_freeze,
+                self_pid: Some(std::process::id()),
+                ..Default::default()
+            },
+            triggers: TriggerConfig {
+                interval_secs: args.interval,
+                hotkey: args.hotkey.clone(),
+            },
+            state: resolve_state_location(args),
+            rules_path: args.rules.clone(),
+            trace_path: args.trace.clone(),
+            detect_suspended: args.detect_suspended,
+            show_inaccessible: args.show_inaccessible,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl FreezeEngine<WindowsProcessEnumerator, WindowsProcessController, DefaultCategorizer> {
+    /// Engine over the live system, with the configured rules and trace
+    ///
+    /// Fails only if `rules_path` exists but can't be loaded.
+    pub fn from_config(config: &Config) -> crate::Result<Self> {
+        let mut enumerator = match &config.rules_path {
+            Some(path) => WindowsProcessEnumerator::with_categorizer(Box::new(
+                ConfigurableCategorizer::from_file(path)?,
+            )),
+            None => WindowsProcessEnumerator::new(),
+        };
+        enumerator.set_detect_suspended(config.detect_suspended);
+        enumerator.set_show_inaccessible(config.show_inaccessible);
+
+        let mut engine = Self::new(
+            enumerator,
+            WindowsProcessController::new(),
+            DefaultCategorizer::new(),
+            config.freeze.clone(),
+        );
+        if let Some(path) = &config.trace_path {
+            engine.set_trace(Box::new(FileDecisionTrace::new(path.clone())));
+        }
+        Ok(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::freeze_engine::FreezeMode;
+    use clap::Parser;
+
+    #[test]
+    fn test_from_args_defaults() {
+        let config = Config::from_args(&Args::parse_from(["smart-freeze"]));
+        let defaults = FreezeConfig::default();
+
+        assert_eq!(config.freeze.min_memory_mb, defaults.min_memory_mb);
+        assert_eq!(
+            config.freeze.min_process_age_secs,
+            defaults.min_process_age_secs
+        );
+        assert!(config.freeze.current_session_only);
+        assert!(!config.freeze.dry_run);
+        assert_eq!(config.freeze.mode, None);
+        assert_eq!(config.freeze.self_pid, Some(std::process::id()));
+        assert_eq!(config.triggers.interval_secs, 60);
+        assert_eq!(config.triggers.hotkey, None);
+        assert_eq!(config.rules_path, None);
+    }
+
+    #[test]
+    fn test_from_args_maps_freeze_flags() {
+        let args = Args::parse_from([
+            "smart-freeze",
+            "--threshold",
+            "250",
+            "--keep-communication",
+            "--freeze-comm-children",
+            "--keep-visible-windows",
+            "--trigger-fullscreen",
+            "--mode",
+            "soft",
+            "--escalate-soft-freeze",
+            "--min-cpu",
+            "2.5",
+            "--target-free-mb",
+            "4096",
+            "--max-total-freeze-mb",
+            "8192",
+            "--max-freeze-secs",
+            "1800",
+            "--freeze-delay-secs",
+            "20",
+            "--idle-trigger-secs",
+            "600",
+            "--notifications",
+            "--daemon-dry-run",
+            "--relaunch-killed",
+            "--trim-after-freeze",
+        ]);
+        let freeze = Config::from_args(&args).freeze;
+
+        assert_eq!(freeze.min_memory_mb, 250);
+        assert!(freeze.keep_communication);
+        assert!(freeze.freeze_comm_children);
+        assert!(freeze.keep_visible_windows);
+        assert!(freeze.trigger_on_fullscreen);
+        assert_eq!(freeze.mode, Some(FreezeMode::Soft));
+        assert!(freeze.escalate_soft_freeze);
+        assert_eq!(freeze.min_cpu_percent, Some(2.5));
+        assert_eq!(freeze.target_free_mb, Some(4096));
+        assert_eq!(freeze.max_total_freeze_mb, Some(8192));
+        assert_eq!(freeze.max_freeze_secs, Some(1800));
+        assert_eq!(freeze.freeze_delay_secs, 20);
+        assert_eq!(freeze.idle_trigger_secs, Some(600));
+        assert!(freeze.notifications);
+        assert!(freeze.dry_run);
+        assert!(freeze.relaunch_killed);
+        assert!(freeze.trim_after_freeze);
+    }
+
+    #[test]
+    fn test_from_args_maps_daemon_and_path_flags() {
+        let args = Args::parse_from([
+            "smart-freeze",
+            "--interval",
+            "15",
+            "--hotkey",
+            "Ctrl+Shift+F9",
+            "--state-file",
+            "state.json",
+            "--rules",
+            "rules.json",
+            "--trace",
+            "trace.log",
+            "--detect-suspended",
+            "--show-inaccessible",
+        ]);
+        let config = Config::from_args(&args);
+
+        assert_eq!(
+            config.triggers,
+            TriggerConfig {
+                interval_secs: 15,
+                hotkey: Some("Ctrl+Shift+F9".to_string()),
+            }
+        );
+        assert_eq!(
+            config.state,
+            StateLocation::File(PathBuf::from("state.json"))
+        );
+        assert_eq!(config.rules_path, Some(PathBuf::from("rules.json")));
+        assert_eq!(config.trace_path, Some(PathBuf::from("trace.log")));
+        assert!(config.detect_suspended);
+        assert!(config.show_inaccessible);
+    }
+}
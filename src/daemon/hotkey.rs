@@ -0,0 +1,199 @@
+//! Global hotkey toggling auto-freeze from inside a game
+
+use super::notify::{notify_auto_freeze_toggled, show_now};
+use super::service::toggle_auto_freeze;
+use super::state::DaemonState;
+use crate::{Result, SmartFreezeError};
+use std::mem;
+use std::ptr;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use windows_sys::Win32::Foundation::GetLastError;
+use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT,
+    MOD_SHIFT, MOD_WIN, VK_F1,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    GetMessageW, PostThreadMessageW, MSG, WM_HOTKEY, WM_QUIT,
+};
+
+/// Combo used when the config file doesn't set `hotkey`
+pub const DEFAULT_HOTKEY: &str = "Ctrl+Alt+F";
+/// ID passed to `RegisterHotKey` (unique within the listener thread)
+const HOTKEY_ID: i32 = 1;
+
+/// A modifier set and virtual-key code accepted by `RegisterHotKey`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: HOT_KEY_MODIFIERS,
+    pub vk: u32,
+}
+
+impl Hotkey {
+    /// Parse a combo like "Ctrl+Alt+F": modifiers, then one letter, digit or F1-F24
+    pub fn parse(combo: &str) -> Result<Self> {
+        let invalid = |reason| SmartFreezeError::InvalidHotkey {
+            hotkey: combo.to_string(),
+            reason,
+        };
+
+        let mut modifiers = 0;
+        let mut vk = None;
+
+        for part in combo.split('+').map(str::trim) {
+            let modifier = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => MOD_CONTROL,
+                "alt" => MOD_ALT,
+                "shift" => MOD_SHIFT,
+                "win" => MOD_WIN,
+                _ => 0,
+            };
+            if modifier != 0 {
+                modifiers |= modifier;
+                continue;
+            }
+
+            if vk.is_some() {
+                return Err(invalid("more than one key"));
+            }
+            vk = Some(key_code(part).ok_or_else(|| invalid("unknown key"))?);
+        }
+
+        let vk = vk.ok_or_else(|| invalid("no key"))?;
+        if modifiers == 0 {
+            return Err(invalid("needs at least one modifier"));
+        }
+        Ok(Self { modifiers, vk })
+    }
+}
+
+/// Virtual-key code of a letter, digit or function key name
+fn key_code(key: &str) -> Option<u32> {
+    let upper = key.to_ascii_uppercase();
+    let mut chars = upper.chars();
+
+    match (chars.next(), chars.next()) {
+        // Letter and digit virtual-key codes equal their ASCII codes
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(c as u32),
+        (Some('F'), Some(_)) => match upper[1..].parse::<u32>() {
+            Ok(n @ 1..=24) => Some(VK_F1 as u32 + n - 1),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Listens for the hotkey on its own thread; unregisters it when dropped
+pub struct HotkeyListener {
+    thread_id: u32,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    /// Register `hotkey` and toggle auto-freeze each time it is pressed
+    ///
+    /// `RegisterHotKey` posts `WM_HOTKEY` to the registering thread, so that
+    /// thread runs its own message loop rather than sharing the tray's.
+    pub fn spawn(hotkey: Hotkey, state: Arc<Mutex<DaemonState>>) -> Result<Self> {
+        let (registered_tx, registered_rx) = mpsc::channel();
+
+        let thread = thread::spawn(move || unsafe {
+            let registered = RegisterHotKey(
+                ptr::null_mut(),
+                HOTKEY_ID,
+                hotkey.modifiers | MOD_NOREPEAT,
+                hotkey.vk,
+            ) != 0;
+            let _ = registered_tx.send(if registered {
+                Ok(GetCurrentThreadId())
+            } else {
+                Err(GetLastError())
+            });
+            if !registered {
+                return;
+            }
+
+            let mut msg: MSG = mem::zeroed();
+            while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+                if msg.message == WM_HOTKEY && msg.wParam == HOTKEY_ID as usize {
+                    let enabled = toggle_auto_freeze(&state);
+                    show_now(notify_auto_freeze_toggled(enabled));
+                }
+            }
+
+            UnregisterHotKey(ptr::null_mut(), HOTKEY_ID);
+        });
+
+        match registered_rx.recv() {
+            Ok(Ok(thread_id)) => Ok(Self {
+                thread_id,
+                thread: Some(thread),
+            }),
+            Ok(Err(code)) => {
+                let _ = thread.join();
+                Err(SmartFreezeError::Win32 {
+                    call: "RegisterHotKey",
+                    code,
+                })
+            }
+            // The thread died before reporting back
+            Err(_) => Err(SmartFreezeError::Win32 {
+                call: "RegisterHotKey",
+                code: 0,
+            }),
+        }
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        unsafe {
+            PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{VK_F, VK_F12};
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(
+            Hotkey::parse("Ctrl+Alt+F").unwrap(),
+            Hotkey {
+                modifiers: MOD_CONTROL | MOD_ALT,
+                vk: VK_F as u32,
+            }
+        );
+        assert_eq!(
+            Hotkey::parse(" shift + win + f12 ").unwrap(),
+            Hotkey {
+                modifiers: MOD_SHIFT | MOD_WIN,
+                vk: VK_F12 as u32,
+            }
+        );
+        assert_eq!(Hotkey::parse("Alt+9").unwrap().vk, '9' as u32);
+        assert_eq!(Hotkey::parse(DEFAULT_HOTKEY), Hotkey::parse("Ctrl+Alt+F"));
+    }
+
+    #[test]
+    fn test_parse_hotkey_rejects_invalid() {
+        for combo in ["F", "Ctrl+Alt", "Ctrl+F+G", "Ctrl+F25", "Ctrl+Space", ""] {
+            assert!(
+                matches!(
+                    Hotkey::parse(combo),
+                    Err(SmartFreezeError::InvalidHotkey { .. })
+                ),
+                "{:?} should be rejected",
+                combo
+            );
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Daemon mode - automatic process freezing when gaming
 
+mod hotkey;
+mod notify;
 mod service;
 mod state;
 mod tray;
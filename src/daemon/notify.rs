@@ -0,0 +1,214 @@
+//! Desktop notifications for freeze/resume events
+
+use std::mem;
+use std::ptr;
+use std::thread;
+use std::time::{Duration, Instant};
+use windows_sys::Win32::UI::Shell::{
+    Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_TIP, NIIF_INFO, NIIF_NOSOUND, NIM_ADD, NIM_DELETE,
+    NOTIFYICONDATAW,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DestroyWindow, LoadIconW, HWND_MESSAGE, IDI_APPLICATION,
+};
+
+/// Minimum gap between two notifications
+const MIN_INTERVAL: Duration = Duration::from_secs(30);
+/// How long the balloon's temporary icon stays in the notification area
+const DISPLAY_TIME: Duration = Duration::from_secs(6);
+/// Icon ID of the temporary icon (distinct from the tray's own)
+const NOTIFY_ICON_ID: u32 = 0x5346;
+
+/// Title and text of a notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Message shown after a freeze pass
+pub fn notify_freeze_summary(count: usize, memory_mb: u64) -> Notification {
+    Notification {
+        title: "SmartFreeze".to_string(),
+        body: format!(
+            "SmartFreeze froze {} {}, freed {}",
+            count,
+            if count == 1 { "process" } else { "processes" },
+            format_memory(memory_mb)
+        ),
+    }
+}
+
+/// Message shown after frozen processes are resumed
+pub fn notify_resume_summary(count: usize) -> Notification {
+    Notification {
+        title: "SmartFreeze".to_string(),
+        body: format!(
+            "SmartFreeze resumed {} {}",
+            count,
+            if count == 1 { "process" } else { "processes" }
+        ),
+    }
+}
+
+/// Message confirming a hotkey toggle of auto-freeze
+pub fn notify_auto_freeze_toggled(enabled: bool) -> Notification {
+    Notification {
+        title: "SmartFreeze".to_string(),
+        body: format!(
+            "Auto-freeze {}",
+            if enabled { "enabled" } else { "disabled" }
+        ),
+    }
+}
+
+/// "512 MB" below a gigabyte, "2.1 GB" above
+fn format_memory(memory_mb: u64) -> String {
+    if memory_mb < 1024 {
+        format!("{} MB", memory_mb)
+    } else {
+        format!("{:.1} GB", memory_mb as f64 / 1024.0)
+    }
+}
+
+/// Shows notifications when enabled, at most one per `MIN_INTERVAL`
+pub struct Notifier {
+    enabled: bool,
+    last_shown: Option<Instant>,
+}
+
+impl Notifier {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_shown: None,
+        }
+    }
+
+    /// Whether a notification may be shown at `now`; records it if so
+    fn allow(&mut self, now: Instant) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if let Some(last) = self.last_shown {
+            if now.saturating_duration_since(last) < MIN_INTERVAL {
+                return false;
+            }
+        }
+        self.last_shown = Some(now);
+        true
+    }
+
+    /// Show a notification in the background (dropped if rate-limited)
+    pub fn show(&mut self, notification: Notification) {
+        if self.allow(Instant::now()) {
+            thread::spawn(move || show_balloon(&notification));
+        }
+    }
+}
+
+/// Show a notification in the background, bypassing the rate limit
+///
+/// For direct replies to user input, which must never be dropped.
+pub fn show_now(notification: Notification) {
+    thread::spawn(move || show_balloon(&notification));
+}
+
+/// Copy `text` into a fixed-size, null-terminated UTF-16 field
+fn copy_wide(dest: &mut [u16], text: &str) {
+    let max = dest.len() - 1;
+    for (slot, unit) in dest.iter_mut().zip(text.encode_utf16().take(max)) {
+        *slot = unit;
+    }
+}
+
+/// Show a balloon from a temporary notification-area icon, then remove it
+fn show_balloon(notification: &Notification) {
+    let class: Vec<u16> = "STATIC\0".encode_utf16().collect();
+
+    unsafe {
+        let hwnd = CreateWindowExW(
+            0,
+            class.as_ptr(),
+            ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null(),
+        );
+        if hwnd.is_null() {
+            return;
+        }
+
+        let mut data: NOTIFYICONDATAW = mem::zeroed();
+        data.cbSize = mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = hwnd;
+        data.uID = NOTIFY_ICON_ID;
+        data.uFlags = NIF_ICON | NIF_TIP | NIF_INFO;
+        data.hIcon = LoadIconW(ptr::null_mut(), IDI_APPLICATION);
+        data.dwInfoFlags = NIIF_INFO | NIIF_NOSOUND;
+        copy_wide(&mut data.szTip, &notification.title);
+        copy_wide(&mut data.szInfoTitle, &notification.title);
+        copy_wide(&mut data.szInfo, &notification.body);
+
+        if Shell_NotifyIconW(NIM_ADD, &data) != 0 {
+            thread::sleep(DISPLAY_TIME);
+            Shell_NotifyIconW(NIM_DELETE, &data);
+        }
+
+        DestroyWindow(hwnd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freeze_summary_text() {
+        assert_eq!(
+            notify_freeze_summary(14, 2150).body,
+            "SmartFreeze froze 14 processes, freed 2.1 GB"
+        );
+        assert_eq!(
+            notify_freeze_summary(1, 300).body,
+            "SmartFreeze froze 1 process, freed 300 MB"
+        );
+        assert_eq!(
+            notify_resume_summary(3).body,
+            "SmartFreeze resumed 3 processes"
+        );
+        assert_eq!(
+            notify_auto_freeze_toggled(false).body,
+            "Auto-freeze disabled"
+        );
+    }
+
+    #[test]
+    fn test_notifier_rate_limit() {
+        let start = Instant::now();
+        let mut notifier = Notifier::new(true);
+
+        assert!(notifier.allow(start));
+        assert!(!notifier.allow(start + Duration::from_secs(5)));
+        assert!(notifier.allow(start + MIN_INTERVAL));
+    }
+
+    #[test]
+    fn test_disabled_notifier_never_shows() {
+        let mut notifier = Notifier::new(false);
+        assert!(!notifier.allow(Instant::now()));
+    }
+
+    #[test]
+    fn test_copy_wide_truncates() {
+        let mut field = [0u16; 4];
+        copy_wide(&mut field, "abcdef");
+        assert_eq!(field, [b'a' as u16, b'b' as u16, b'c' as u16, 0]);
+    }
+}
@@ -1,101 +1,322 @@
 //! Daemon service implementation
 
+use super::hotkey::{Hotkey, HotkeyListener, DEFAULT_HOTKEY};
+use super::notify::{notify_freeze_summary, notify_resume_summary, Notifier};
 use super::state::DaemonState;
-use super::tray::run_system_tray;
-use crate::categorization::DefaultCategorizer;
-use crate::freeze_engine::{FreezeConfig, FreezeEngine};
-use crate::persistence::{FileStatePersistence, PersistentState, StatePersistence};
-use crate::windows::{WindowsProcessController, WindowsProcessEnumerator};
-use std::sync::{Arc, Mutex};
+use super::tray::SystemTray;
+use crate::audit::AuditLogger;
+use crate::categorization::ProcessCategorizer;
+use crate::config::Config;
+use crate::freeze_engine::{
+    FreezeEngine, ProcessController, ProcessEnumerator, ResumeOutcome, SessionReport,
+};
+use crate::logging::RotatingFileLogger;
+use crate::persistence::{
+    resume_all_from_state, DaemonLock, FileStatePersistence, FrozenProcess, PersistentState,
+    StateLocation, StatePersistence, TraySettings,
+};
+use crate::process::ProcessInfo;
+use crate::windows::{StartupOptions, WindowsProcessController};
+use crate::SmartFreezeError;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use windows_sys::Win32::Foundation::BOOL;
+use windows_sys::Win32::System::Console::{
+    SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT,
+    CTRL_SHUTDOWN_EVENT,
+};
+
+/// How many processes the tray offers to pin
+const PIN_MENU_SIZE: usize = 10;
+
+/// Daemon state shared with the console control handler
+static SHUTDOWN_STATE: OnceLock<Arc<Mutex<DaemonState>>> = OnceLock::new();
+/// State location chosen by `run_daemon` (`--state-registry`, `--state-file` or
+/// `SMARTFREEZE_STATE`)
+static STATE_LOCATION: OnceLock<StateLocation> = OnceLock::new();
+
+/// Persistence for the daemon's saved state
+fn state_persistence() -> Box<dyn StatePersistence> {
+    match STATE_LOCATION.get() {
+        Some(location) => location.open(),
+        None => Box::new(FileStatePersistence::with_default_path()),
+    }
+}
 
 /// Run daemon mode
-pub fn run_daemon(interval_secs: u64, threshold_mb: u64, keep_communication: bool) {
+pub fn run_daemon(config: Config) {
+    if let Err(e) = RotatingFileLogger::init_default() {
+        eprintln!("[SmartFreeze] Warning: Failed to set up logging: {}", e);
+    }
+
+    // Only one daemon may own the state file; held until run_daemon returns
+    let _lock = match DaemonLock::acquire() {
+        Ok(lock) => lock,
+        Err(e) => {
+            log::error!("✗ {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Create persistent state manager
-    let persistence = FileStatePersistence::with_default_path();
+    log::info!("Saved state: {}", config.state);
+    let _ = STATE_LOCATION.set(config.state.clone());
+    let persistence = state_persistence();
 
     // Try to recover from previous crash
-    recover_from_crash(&persistence);
+    recover_from_crash(persistence.as_ref());
 
-    // Create daemon state
-    let state = Arc::new(Mutex::new(DaemonState::new()));
+    // Create daemon state, honoring the saved enabled preference
+    let settings = TraySettings::load_from(&TraySettings::default_path()).unwrap_or_else(|e| {
+        log::warn!("Failed to load settings: {}", e);
+        TraySettings::default()
+    });
+    if !settings.enabled {
+        log::info!("Auto-freeze is disabled (saved preference)");
+    }
+    let state = Arc::new(Mutex::new(DaemonState::with_enabled(settings.enabled)));
     let state_clone = state.clone();
 
-    // Start monitoring thread
-    thread::spawn(move || {
-        monitor_loop(state_clone, interval_secs, threshold_mb, keep_communication);
-    });
+    // Resume everything on Ctrl+C, console close, logoff or shutdown
+    let _ = SHUTDOWN_STATE.set(state.clone());
+    if unsafe { SetConsoleCtrlHandler(Some(console_ctrl_handler), 1) } == 0 {
+        log::warn!("Failed to install console control handler");
+    }
 
-    // Run system tray on main thread
-    println!("[SmartFreeze] Starting system tray...");
-    if let Err(e) = run_system_tray(state) {
-        eprintln!("[SmartFreeze] System tray error: {}", e);
+    // Used if the tray re-installs startup, so the saved command keeps these options
+    let startup_options = StartupOptions {
+        interval: config.triggers.interval_secs,
+        threshold: config.freeze.min_memory_mb,
+        keep_communication: config.freeze.keep_communication,
+        ..StartupOptions::default()
     }
-}
+    .with_state(&config.state);
 
-fn recover_from_crash(persistence: &FileStatePersistence) {
-    if let Ok(Some(old_state)) = persistence.load() {
-        let valid = old_state.get_valid_processes();
-        if !valid.is_empty() {
-            println!(
-                "[SmartFreeze] Recovering from previous crash ({} terminated processes)...",
-                valid.len()
-            );
-            let controller = WindowsProcessController::new();
-            let mut restarted = 0;
-            let mut failed = 0;
-
-            for frozen in valid {
-                match controller.restart_process(&frozen.exe_path) {
-                    Ok(new_pid) => {
-                        println!(
-                            "[SmartFreeze] ✓ Restarted {} (new PID: {})",
-                            frozen.name, new_pid
-                        );
-                        restarted += 1;
-                    }
-                    Err(_) => {
-                        eprintln!("[SmartFreeze] ✗ Failed to restart {}", frozen.name);
-                        failed += 1;
-                    }
+    let hotkey = config.triggers.hotkey.as_deref().unwrap_or(DEFAULT_HOTKEY);
+    let hotkey = match Hotkey::parse(hotkey) {
+        Ok(hotkey) => Some(hotkey),
+        Err(e) => {
+            log::warn!("{}; the toggle hotkey is disabled", e);
+            None
+        }
+    };
+
+    // Unregistered when run_daemon returns and the listener is dropped
+    let _hotkey_listener =
+        hotkey.and_then(
+            |hotkey| match HotkeyListener::spawn(hotkey, state.clone()) {
+                Ok(listener) => Some(listener),
+                Err(e) => {
+                    log::warn!("Failed to register hotkey: {}", e);
+                    None
                 }
+            },
+        );
+
+    log::info!("Starting system tray...");
+    let tray = SystemTray::new(state.clone(), startup_options, settings.icon_color);
+
+    match daemon_mode(tray) {
+        DaemonMode::Tray(tray) => {
+            // The tray owns the main thread; monitor in the background
+            let monitor = thread::spawn(move || {
+                monitor_loop(state_clone, config);
+            });
+            if let Err(e) = tray.run() {
+                log::error!("System tray error: {}", e);
+                log::info!("Running headless (press Ctrl+C to exit)");
+                let _ = monitor.join();
             }
+        }
+        DaemonMode::Headless => {
+            // Ctrl+C (handled above) resumes everything and exits
+            monitor_loop(state_clone, config);
+        }
+    }
+}
 
-            println!(
-                "[SmartFreeze] Recovery complete: {} restarted, {} failed",
-                restarted, failed
-            );
+/// Where `run_daemon` runs the monitor loop
+enum DaemonMode<T> {
+    /// The tray's event loop owns the main thread
+    Tray(T),
+    /// No tray: the monitor loop owns the main thread
+    Headless,
+}
+
+/// Fall back to headless mode when the tray can't be created
+fn daemon_mode<T>(tray: Result<T, Box<dyn std::error::Error>>) -> DaemonMode<T> {
+    match tray {
+        Ok(tray) => DaemonMode::Tray(tray),
+        Err(e) => {
+            log::warn!("System tray unavailable: {}", e);
+            log::info!("Running headless (press Ctrl+C to exit)");
+            DaemonMode::Headless
         }
-        let _ = persistence.delete();
     }
 }
 
-fn monitor_loop(
-    state: Arc<Mutex<DaemonState>>,
-    interval_secs: u64,
-    threshold_mb: u64,
-    keep_communication: bool,
-) {
-    println!("[SmartFreeze] Monitoring thread started");
-    println!("[SmartFreeze] Check interval: {}s", interval_secs);
-    println!("[SmartFreeze] Memory threshold: {}MB", threshold_mb);
-    println!(
-        "[SmartFreeze] Communication protection: {}",
-        if keep_communication { "ON" } else { "OFF" }
+fn recover_from_crash(persistence: &dyn StatePersistence) {
+    resume_saved_processes(persistence, "Recovery");
+}
+
+/// Resume everything this daemon froze before it exits
+///
+/// Holds the state lock throughout and disables auto-freeze, so the monitor
+/// thread can't freeze anything new while the process is shutting down.
+pub(super) fn resume_on_shutdown(state: &Mutex<DaemonState>) {
+    let mut state_guard = state.lock().unwrap_or_else(|e| e.into_inner());
+    state_guard.enabled = false;
+
+    resume_saved_processes(state_persistence().as_ref(), "Shutdown");
+
+    state_guard.clear_frozen();
+    state_guard.game_detected = false;
+    state_guard.gaming_pids.clear();
+}
+
+/// Flip auto-freeze on or off and save the preference; returns the new state
+pub(super) fn toggle_auto_freeze(state: &Mutex<DaemonState>) -> bool {
+    let enabled = {
+        let mut state_guard = state.lock().unwrap_or_else(|e| e.into_inner());
+        state_guard.toggle_enabled();
+        state_guard.is_enabled()
+    };
+
+    log::info!(
+        "Auto-freeze: {}",
+        if enabled { "ENABLED" } else { "DISABLED" }
     );
 
-    let persistence = FileStatePersistence::with_default_path();
-    let enumerator = WindowsProcessEnumerator::new();
+    // Leaves the other preferences (e.g. icon color) and unreadable files alone
+    if let Err(e) = TraySettings::save_enabled(&TraySettings::default_path(), enabled) {
+        log::warn!("Failed to save settings: {}", e);
+    }
+
+    enabled
+}
+
+/// Resume every process recorded in `persistence` and report the outcome
+fn resume_saved_processes(persistence: &dyn StatePersistence, context: &str) {
     let controller = WindowsProcessController::new();
-    let categorizer = DefaultCategorizer::new();
 
-    let config = FreezeConfig {
-        min_memory_mb: threshold_mb,
-        keep_communication,
+    match resume_all_from_state(persistence, &controller) {
+        Ok(Some(report))
+            if !report.resumed.is_empty()
+                || !report.failed.is_empty()
+                || !report.skipped.is_empty() =>
+        {
+            for (pid, reason) in &report.failed {
+                log::error!("  ✗ Failed to resume PID {}: {}", pid, reason);
+            }
+            for (pid, reason) in &report.skipped {
+                log::info!("  Skipped PID {}: {}", pid, reason);
+            }
+            log::info!(
+                "{} complete: {} resumed, {} failed, {} skipped",
+                context,
+                report.resumed.len(),
+                report.failed.len(),
+                report.skipped.len()
+            );
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to resume saved state: {}", e),
+    }
+}
+
+/// Console control handler; runs on a thread created by the system
+unsafe extern "system" fn console_ctrl_handler(ctrl_type: u32) -> BOOL {
+    match ctrl_type {
+        CTRL_C_EVENT | CTRL_BREAK_EVENT | CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT
+        | CTRL_SHUTDOWN_EVENT => {
+            log::info!("Shutting down...");
+            if let Some(state) = SHUTDOWN_STATE.get() {
+                resume_on_shutdown(state);
+            }
+            std::process::exit(0);
+        }
+        _ => 0,
+    }
+}
+
+fn monitor_loop(state: Arc<Mutex<DaemonState>>, mut config: Config) {
+    let interval_secs = config.triggers.interval_secs;
+    log::info!("Monitoring thread started");
+    log::info!("Check interval: {}s", interval_secs);
+    log::info!("Memory threshold: {}MB", config.freeze.min_memory_mb);
+    log::info!(
+        "Communication protection: {}",
+        if config.freeze.keep_communication {
+            "ON"
+        } else {
+            "OFF"
+        }
+    );
+    if let Some(target) = config.freeze.target_free_mb {
+        log::info!("Free memory target: {}MB", target);
+    }
+    if let Some(cap) = config.freeze.max_total_freeze_mb {
+        log::info!("Total freeze cap: {}MB", cap);
+    }
+    if let Some(mode) = config.freeze.mode {
+        log::info!("Freeze mode: {}", mode.as_str());
+    }
+    if let Some(secs) = config.freeze.idle_trigger_secs {
+        log::info!("Idle trigger: {}s", secs);
+    }
+
+    let max_freeze = config.freeze.max_freeze_secs.map(Duration::from_secs);
+    let freeze_delay = Duration::from_secs(config.freeze.freeze_delay_secs);
+    let notifier = Notifier::new(config.freeze.notifications);
+    let dry_run = config.freeze.dry_run;
+    if dry_run {
+        log::info!("Dry run: logging freeze plans without freezing anything");
+    }
+    if let Some(max) = max_freeze {
+        log::info!("Max freeze duration: {}s", max.as_secs());
+    }
+    if !freeze_delay.is_zero() {
+        log::info!("Freeze delay after game start: {}s", freeze_delay.as_secs());
+    }
+
+    if let Some(path) = &config.trace_path {
+        log::info!("Decision trace: {}", path.display());
+    }
+
+    let persistence = state_persistence();
+    let engine = FreezeEngine::from_config(&config).or_else(|e| {
+        let path = config.rules_path.take().unwrap_or_default();
+        log::error!(
+            "Failed to load rules from {}: {} (using built-in rules)",
+            path.display(),
+            e
+        );
+        FreezeEngine::from_config(&config)
+    });
+    let mut engine = match engine {
+        Ok(engine) => engine,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
     };
+    engine.set_audit(AuditLogger::with_default_path());
 
-    let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+    if let Err(e) = engine.validate_mode() {
+        log::error!("{}", e);
+        return;
+    }
+
+    let mut monitor = Monitor {
+        persistence: persistence.as_ref(),
+        persistent_state: PersistentState::new(),
+        notifier,
+        max_freeze,
+        dry_run,
+        freeze_delay,
+        game_seen_at: None,
+    };
 
     loop {
         thread::sleep(Duration::from_secs(interval_secs));
@@ -106,92 +327,1025 @@ fn monitor_loop(
             continue;
         }
 
-        // Check for gaming processes
-        let gaming_running = engine
-            .find_gaming_processes()
-            .map(|procs| !procs.is_empty())
-            .unwrap_or(false);
-
-        if gaming_running && !state_guard.game_detected {
-            // Game started - freeze processes
-            println!("[SmartFreeze] 🎮 Game detected! Freezing background processes...");
-            state_guard.game_detected = true;
-
-            if let Ok(safe) = engine.find_safe_to_freeze() {
-                let mut persistent_state = PersistentState::new();
-                let mut frozen_count = 0;
-                let mut total_memory = 0u64;
-
-                for process in safe {
-                    match engine.freeze_process(process.pid) {
-                        Ok(_) => {
-                            state_guard.add_frozen(process.pid);
-                            persistent_state.add(
-                                process.pid,
-                                process.name.clone(),
-                                process.full_path.clone(),
-                            );
-                            total_memory += process.memory_mb;
-                            frozen_count += 1;
-                            println!(
-                                "[SmartFreeze]   💀 Terminated {} (PID {}, {} MB) - RAM freed!",
-                                process.name, process.pid, process.memory_mb
-                            );
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "[SmartFreeze]   ✗ Failed to terminate {} (PID {}): {}",
-                                process.name, process.pid, e
-                            );
-                        }
+        monitor.tick(&mut engine, &mut state_guard, Instant::now());
+    }
+}
+
+/// Monitor-loop state that lives across ticks
+struct Monitor<'a> {
+    persistence: &'a dyn StatePersistence,
+    /// What is currently persisted for crash recovery
+    persistent_state: PersistentState,
+    notifier: Notifier,
+    max_freeze: Option<Duration>,
+    /// Log the freeze plan instead of freezing (and never touch the state file)
+    dry_run: bool,
+    /// Grace period between seeing a game and freezing
+    freeze_delay: Duration,
+    /// When a game was first seen, while its freeze is still pending
+    game_seen_at: Option<Instant>,
+}
+
+impl Monitor<'_> {
+    /// One pass: freeze when a game starts or the user goes idle, top up while
+    /// either lasts, resume once no game runs and the user is back
+    fn tick<E, C, Cat>(
+        &mut self,
+        engine: &mut FreezeEngine<E, C, Cat>,
+        state: &mut DaemonState,
+        now: Instant,
+    ) where
+        E: ProcessEnumerator,
+        C: ProcessController,
+        Cat: ProcessCategorizer,
+    {
+        // Pins made from the tray since the last check
+        engine.set_pinned(
+            state.pinned.clone(),
+            state.pinned_names.iter().cloned().collect(),
+        );
+
+        // Resumes that timed out but have finished since are no longer frozen
+        let late = engine.take_late_resumes();
+        if !late.is_empty() {
+            for pid in late {
+                log::info!("  ✓ Resumed PID {} (finished after timing out)", pid);
+                state.remove_frozen(pid);
+                self.persistent_state.remove(pid);
+            }
+            self.save_state();
+        }
+
+        // Check for gaming processes (or a fullscreen app, if enabled). The
+        // session follows the games existing, not the foreground: alt-tabbing to
+        // a browser keeps it going, and the browser is spared only because the
+        // foreground is never frozen. A non-game fullscreen app only counts while
+        // it stays fullscreen. A failed check keeps the last set instead of
+        // looking like every game exited.
+        match engine.gaming_session_pids() {
+            Ok(current) => {
+                let (started, exited) = state.update_gaming_pids(current);
+                if state.game_detected {
+                    log_game_changes(&started, &exited, state.gaming_pids.len());
+                }
+            }
+            Err(e) => log::warn!("Failed to check for running games: {}", e),
+        }
+        engine.set_session_pids(state.gaming_pids.clone());
+        // From the snapshot the game check just took, so no extra enumeration
+        state.pin_candidates = engine.pin_candidates(PIN_MENU_SIZE);
+        // Frozen processes stay frozen until the last game exits
+        let gaming_running = !state.gaming_pids.is_empty();
+        // Idle time is a second trigger sharing the same freeze session
+        let was_idle = std::mem::replace(&mut state.user_idle, engine.is_user_idle());
+        let idle = state.user_idle;
+
+        if !state.game_detected && !idle && !self.grace_period_over(gaming_running, now) {
+            return;
+        }
+        let triggered = gaming_running || idle;
+
+        if triggered && !state.game_detected {
+            // Game started or user stepped away - freeze processes
+            if gaming_running {
+                log::info!("🎮 Game detected! Freezing background processes...");
+            } else {
+                log::info!("💤 User idle. Freezing background processes...");
+            }
+            state.game_detected = true;
+            // Idle may have cut a pending freeze delay short
+            self.game_seen_at = None;
+
+            if self.dry_run {
+                match engine.session_candidates() {
+                    Ok(candidates) => log_dry_run_plan(&candidates),
+                    Err(e) => log::error!("Failed to enumerate safe processes: {}", e),
+                }
+                return;
+            }
+
+            match engine.freeze_session() {
+                Ok(report) => {
+                    record_report(engine, state, &mut self.persistent_state, &report);
+                    state.record_freeze_event(report.frozen_count(), report.freed_mb());
+
+                    // Save to disk for crash recovery
+                    self.save_state();
+
+                    log::info!(
+                        "✓ Froze {} processes (~{} MB)",
+                        report.frozen_count(),
+                        report.freed_mb()
+                    );
+                    if report.frozen_count() > 0 {
+                        self.notifier.show(notify_freeze_summary(
+                            report.frozen_count(),
+                            report.freed_mb(),
+                        ));
                     }
                 }
+                Err(e) => log::error!("Failed to enumerate safe processes: {}", e),
+            }
+        } else if triggered && state.game_detected {
+            // The plan was logged when the game started; nothing is frozen to manage
+            if self.dry_run {
+                return;
+            }
 
-                // Save to disk for crash recovery
-                if let Err(e) = persistence.save(&persistent_state) {
-                    eprintln!("[SmartFreeze] Warning: Failed to save state: {}", e);
+            // Still gaming - release processes frozen for too long
+            if let Some(max) = self.max_freeze {
+                if resume_expired(engine, state, &mut self.persistent_state, max) {
+                    self.save_state();
                 }
+            }
+
+            // Pinned from the tray after they were frozen
+            if resume_pinned(engine, state, &mut self.persistent_state) {
+                self.save_state();
+            }
+
+            // Catch background apps launched since the last pass
+            let exclusions = state.refreeze_exclusions();
+            if let Ok(new) = engine.find_new_safe_to_freeze(&exclusions, state.frozen_memory_mb) {
+                if !new.is_empty() {
+                    let report = engine.freeze_candidates(new);
+                    record_report(engine, state, &mut self.persistent_state, &report);
+                    state.record_frozen(report.frozen_count(), report.freed_mb());
 
-                println!(
-                    "[SmartFreeze] ✓ Terminated {} processes, freed ~{} MB RAM!",
-                    frozen_count, total_memory
-                );
+                    if report.frozen_count() > 0 {
+                        self.save_state();
+                        log::info!(
+                            "✓ Froze {} newly started processes (~{} MB)",
+                            report.frozen_count(),
+                            report.freed_mb()
+                        );
+                    }
+                }
+            }
+        } else if !triggered && state.game_detected {
+            state.game_detected = false;
+            let ended = if was_idle {
+                "⌨️ User is back"
             } else {
-                eprintln!("[SmartFreeze] Failed to enumerate safe processes");
-            }
-        } else if !gaming_running && state_guard.game_detected {
-            // Game exited - restart all terminated processes
-            println!("[SmartFreeze] 🎮 Game closed. Restarting terminated processes...");
-            state_guard.game_detected = false;
-
-            // Load from persistence to get exe paths
-            if let Ok(Some(saved_state)) = persistence.load() {
-                let mut restarted_count = 0;
-                let restart_controller = WindowsProcessController::new();
-
-                for frozen in saved_state.get_valid_processes() {
-                    match restart_controller.restart_process(&frozen.exe_path) {
-                        Ok(new_pid) => {
-                            println!(
-                                "[SmartFreeze]   ✓ Restarted {} (new PID: {})",
-                                frozen.name, new_pid
-                            );
-                            restarted_count += 1;
-                        }
-                        Err(e) => {
-                            eprintln!("[SmartFreeze]   ✗ Failed to restart {}: {}", frozen.name, e);
-                        }
+                "🎮 Game closed"
+            };
+            if self.dry_run {
+                log::info!("[DRY] {}; nothing was frozen", ended);
+                return;
+            }
+
+            // Session over - resume all frozen processes
+            log::info!("{}. Resuming frozen processes...", ended);
+
+            let pids: Vec<u32> = state.frozen_pids.iter().copied().collect();
+            let saved = self.persistent_state.name_lookup();
+            let mut resumed_count = 0;
+            let mut still_resuming = Vec::new();
+
+            for &pid in &pids {
+                let exe_path = saved.get(&pid).map_or("", |(_, path)| path.as_str());
+                match engine.resume_or_relaunch(pid, exe_path) {
+                    Ok(ResumeOutcome::Resumed(_)) => resumed_count += 1,
+                    Ok(ResumeOutcome::Relaunched(new_pid)) => {
+                        log::info!(
+                            "  🔁 Relaunched {} as PID {} (killed while frozen)",
+                            exe_path,
+                            new_pid
+                        );
+                        resumed_count += 1;
+                    }
+                    Err(e @ SmartFreezeError::TimedOut { .. }) => {
+                        log::warn!("  ⏳ {}; tracked until it finishes", e);
+                        still_resuming.push(pid);
+                    }
+                    Err(e) => {
+                        log::error!("  ✗ Failed to resume PID {}: {}", pid, e);
                     }
                 }
+            }
 
-                println!("[SmartFreeze] ✓ Restarted {} processes", restarted_count);
+            log::info!("✓ Resumed {} processes", resumed_count);
+            if resumed_count > 0 {
+                self.notifier.show(notify_resume_summary(resumed_count));
             }
+            state.record_resume_event();
 
-            // Clear in-memory and disk state
-            state_guard.clear_frozen();
-            if let Err(e) = persistence.save(&PersistentState::new()) {
-                eprintln!("[SmartFreeze] Warning: Failed to clear state: {}", e);
+            // Clear in-memory and disk state, except resumes still running late
+            for pid in pids {
+                if !still_resuming.contains(&pid) {
+                    state.remove_frozen(pid);
+                    self.persistent_state.remove(pid);
+                }
+            }
+            state.released_pids.clear();
+            if let Err(e) = self.persistence.save(&self.persistent_state) {
+                log::warn!("Failed to clear state: {}", e);
+            }
+        }
+    }
+
+    /// Whether a newly seen game has now run for the whole freeze delay
+    ///
+    /// A game that exits before then cancels the pending freeze.
+    fn grace_period_over(&mut self, gaming_running: bool, now: Instant) -> bool {
+        if !gaming_running {
+            if self.game_seen_at.take().is_some() {
+                log::info!("🎮 Game exited during the freeze delay; not freezing");
             }
+            return true;
+        }
+        if self.freeze_delay.is_zero() {
+            return true;
+        }
+
+        let delay = self.freeze_delay;
+        let seen_at = *self.game_seen_at.get_or_insert_with(|| {
+            log::info!(
+                "🎮 Game detected; freezing in {}s if it keeps running",
+                delay.as_secs()
+            );
+            now
+        });
+        if now.saturating_duration_since(seen_at) < delay {
+            return false;
+        }
+        self.game_seen_at = None;
+        true
+    }
+
+    fn save_state(&self) {
+        if let Err(e) = self.persistence.save(&self.persistent_state) {
+            log::warn!("Failed to save state: {}", e);
         }
     }
 }
+
+/// Log games starting or exiting while a freeze session is already active
+fn log_game_changes(started: &[u32], exited: &[u32], running: usize) {
+    for pid in started {
+        log::info!("🎮 Another game started (PID {}); {} running", pid, running);
+    }
+    if running > 0 {
+        for pid in exited {
+            log::info!(
+                "🎮 Game exited (PID {}); keeping processes frozen while {} still running",
+                pid,
+                running
+            );
+        }
+    }
+}
+
+/// Log what a freeze pass would do, without touching any process
+fn log_dry_run_plan(processes: &[ProcessInfo]) {
+    for process in processes {
+        log::info!(
+            "[DRY] would freeze {} (PID {}, {} MB)",
+            process.name,
+            process.pid,
+            process.memory_mb
+        );
+    }
+    log::info!(
+        "[DRY] would freeze {} processes (~{} MB)",
+        processes.len(),
+        processes.iter().map(|p| p.memory_mb).sum::<u64>()
+    );
+}
+
+/// Resume processes frozen for longer than `max`, keeping them unfrozen for the session
+///
+/// Returns whether any tracked process was released.
+fn resume_expired<E, C, Cat>(
+    engine: &FreezeEngine<E, C, Cat>,
+    state: &mut DaemonState,
+    persistent_state: &mut PersistentState,
+    max: Duration,
+) -> bool
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    let expired = state.expired_pids(max, Instant::now());
+    let reason = format!("frozen longer than {}s", max.as_secs());
+    release_processes(engine, state, persistent_state, &expired, &reason)
+}
+
+/// Resume frozen processes that have since been pinned
+///
+/// Returns whether any tracked process was released.
+fn resume_pinned<E, C, Cat>(
+    engine: &FreezeEngine<E, C, Cat>,
+    state: &mut DaemonState,
+    persistent_state: &mut PersistentState,
+) -> bool
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    let names = persistent_state.name_lookup();
+    let mut pinned: Vec<u32> = state
+        .frozen_pids
+        .iter()
+        .copied()
+        .filter(|pid| {
+            let name = names.get(pid).map_or("", |(name, _)| name.as_str());
+            engine.is_pinned(*pid, name)
+        })
+        .collect();
+    pinned.sort_unstable();
+    release_processes(engine, state, persistent_state, &pinned, "pinned")
+}
+
+/// Resume `pids` and keep them unfrozen for the rest of the session
+///
+/// Returns whether any tracked process was released.
+fn release_processes<E, C, Cat>(
+    engine: &FreezeEngine<E, C, Cat>,
+    state: &mut DaemonState,
+    persistent_state: &mut PersistentState,
+    pids: &[u32],
+    reason: &str,
+) -> bool
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    for &pid in pids {
+        match engine.resume_process(pid) {
+            Ok(_) | Err(SmartFreezeError::ProcessNotFound(_)) => {
+                log::info!("  ⏱  Resumed PID {} ({})", pid, reason);
+            }
+            Err(e) => {
+                log::error!("  ✗ Failed to resume PID {}: {}", pid, e);
+                continue;
+            }
+        }
+        state.remove_frozen(pid);
+        state.released_pids.insert(pid);
+        persistent_state.remove(pid);
+    }
+
+    !pids.is_empty()
+}
+
+/// Track a freeze pass's successes in memory and in `persistent_state`, logging each outcome
+fn record_report<E, C, Cat>(
+    engine: &FreezeEngine<E, C, Cat>,
+    state: &mut DaemonState,
+    persistent_state: &mut PersistentState,
+    report: &SessionReport,
+) where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    for process in &report.frozen {
+        state.add_frozen_process(process.pid, process.memory_mb);
+        persistent_state.push(
+            FrozenProcess::new(process.pid, process.name.clone(), process.full_path.clone())
+                .with_record(engine.freeze_record(process.pid))
+                .with_mode(engine.config().mode),
+        );
+        log::info!(
+            "  ❄️  Froze {} (PID {}, {} MB)",
+            process.name,
+            process.pid,
+            process.memory_mb
+        );
+    }
+    for process in &report.skipped {
+        log::info!(
+            "  Skipped {} (PID {}): already frozen elsewhere",
+            process.name,
+            process.pid
+        );
+    }
+    for (process, error) in &report.failed {
+        match error {
+            SmartFreezeError::AccessDenied { .. } => log::warn!(
+                "  ✗ Access denied freezing {} (PID {}); run the daemon as administrator to include elevated processes",
+                process.name,
+                process.pid
+            ),
+            e => log::error!(
+                "  ✗ Failed to freeze {} (PID {}): {}",
+                process.name,
+                process.pid,
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categorization::DefaultCategorizer;
+    use crate::freeze_engine::FreezeConfig;
+    use crate::process::ProcessCategory;
+    use crate::Result;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Enumerator whose process list the test can change between ticks
+    struct SharedEnumerator {
+        processes: Arc<Mutex<Vec<ProcessInfo>>>,
+        idle_secs: Arc<Mutex<Option<u64>>>,
+        /// Foreground PID and whether it is fullscreen
+        foreground: Arc<Mutex<Option<(u32, bool)>>>,
+    }
+
+    impl ProcessEnumerator for SharedEnumerator {
+        fn enumerate(&mut self) -> Result<Vec<ProcessInfo>> {
+            let foreground = self.get_foreground_pid();
+            let mut processes = self.processes.lock().unwrap().clone();
+            for process in &mut processes {
+                process.is_foreground = foreground == Some(process.pid);
+            }
+            Ok(processes)
+        }
+
+        fn get_foreground_pid(&self) -> Option<u32> {
+            self.foreground.lock().unwrap().map(|(pid, _)| pid)
+        }
+
+        fn is_foreground_fullscreen(&self) -> bool {
+            self.foreground
+                .lock()
+                .unwrap()
+                .is_some_and(|(_, fullscreen)| fullscreen)
+        }
+
+        fn idle_seconds(&self) -> Option<u64> {
+            *self.idle_secs.lock().unwrap()
+        }
+    }
+
+    /// Controller that counts every call it receives
+    struct CountingController {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl ProcessController for CountingController {
+        fn freeze(&self, _pid: u32) -> Result<usize> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(1)
+        }
+
+        fn resume(&self, _pid: u32) -> Result<usize> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(1)
+        }
+    }
+
+    /// Controller whose resumes of `stuck` PIDs time out, finishing once
+    /// the test moves them to `late`
+    struct LateController {
+        stuck: Vec<u32>,
+        late: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl ProcessController for LateController {
+        fn freeze(&self, _pid: u32) -> Result<usize> {
+            Ok(1)
+        }
+
+        fn resume(&self, pid: u32) -> Result<usize> {
+            if self.stuck.contains(&pid) {
+                return Err(SmartFreezeError::TimedOut {
+                    pid,
+                    operation: "Resume",
+                    timeout_ms: 500,
+                });
+            }
+            Ok(1)
+        }
+
+        fn take_late_resumes(&self) -> Vec<u32> {
+            std::mem::take(&mut *self.late.lock().unwrap())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingPersistence {
+        saves: AtomicUsize,
+    }
+
+    impl StatePersistence for CountingPersistence {
+        fn save(&self, _state: &PersistentState) -> Result<()> {
+            self.saves.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn load(&self) -> Result<Option<PersistentState>> {
+            Ok(None)
+        }
+
+        fn delete(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn process(pid: u32, name: &str, memory_mb: u64, category: ProcessCategory) -> ProcessInfo {
+        let mut process = ProcessInfo::new(
+            pid,
+            name.to_string(),
+            String::new(),
+            memory_mb,
+            false,
+            category,
+        );
+        process.age_secs = Some(3600);
+        process
+    }
+
+    #[test]
+    fn test_tray_failure_falls_back_to_headless() {
+        let failed: std::result::Result<(), Box<dyn std::error::Error>> =
+            Err("no shell to host the icon".into());
+        assert!(matches!(daemon_mode(failed), DaemonMode::Headless));
+
+        let built: std::result::Result<u8, Box<dyn std::error::Error>> = Ok(7);
+        assert!(matches!(daemon_mode(built), DaemonMode::Tray(7)));
+    }
+
+    #[test]
+    fn test_dry_run_never_freezes_or_saves() {
+        let persistence = CountingPersistence::default();
+        let mut monitor = Monitor {
+            persistence: &persistence,
+            persistent_state: PersistentState::new(),
+            notifier: Notifier::new(false),
+            max_freeze: Some(Duration::ZERO),
+            dry_run: true,
+            freeze_delay: Duration::ZERO,
+            game_seen_at: None,
+        };
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "game.exe", 4000, ProcessCategory::Gaming),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+        ]));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut engine = FreezeEngine::new(
+            SharedEnumerator {
+                processes: processes.clone(),
+                idle_secs: Arc::default(),
+                foreground: Arc::default(),
+            },
+            CountingController {
+                calls: calls.clone(),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                dry_run: true,
+                ..Default::default()
+            },
+        );
+        let mut state = DaemonState::new();
+
+        // Game starts, keeps running, then exits
+        let now = Instant::now();
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        monitor.tick(&mut engine, &mut state, now);
+        processes.lock().unwrap().remove(0);
+        monitor.tick(&mut engine, &mut state, now);
+
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(persistence.saves.load(Ordering::SeqCst), 0);
+    }
+
+    /// Monitor with a freeze delay, plus an engine over a game and one freezable app
+    fn delayed_setup(
+        persistence: &CountingPersistence,
+        delay_secs: u64,
+    ) -> (
+        Monitor<'_>,
+        FreezeEngine<SharedEnumerator, CountingController, DefaultCategorizer>,
+        Arc<Mutex<Vec<ProcessInfo>>>,
+        Arc<AtomicUsize>,
+    ) {
+        let monitor = Monitor {
+            persistence,
+            persistent_state: PersistentState::new(),
+            notifier: Notifier::new(false),
+            max_freeze: None,
+            dry_run: false,
+            freeze_delay: Duration::from_secs(delay_secs),
+            game_seen_at: None,
+        };
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "game.exe", 4000, ProcessCategory::Gaming),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+        ]));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let engine = FreezeEngine::new(
+            SharedEnumerator {
+                processes: processes.clone(),
+                idle_secs: Arc::default(),
+                foreground: Arc::default(),
+            },
+            CountingController {
+                calls: calls.clone(),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        (monitor, engine, processes, calls)
+    }
+
+    #[test]
+    fn test_resume_waits_for_last_game_to_exit() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, processes, calls) = delayed_setup(&persistence, 0);
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // First game starts: chrome is frozen
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.frozen_pids.contains(&2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Second game starts, then the first one exits
+        processes
+            .lock()
+            .unwrap()
+            .push(process(3, "othergame.exe", 3000, ProcessCategory::Gaming));
+        monitor.tick(&mut engine, &mut state, now);
+        processes.lock().unwrap().retain(|p| p.pid != 1);
+        monitor.tick(&mut engine, &mut state, now);
+
+        assert!(state.game_detected);
+        assert_eq!(state.gaming_pids, HashSet::from([3]));
+        assert!(state.frozen_pids.contains(&2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Only the last exit resumes
+        processes.lock().unwrap().retain(|p| p.pid != 3);
+        monitor.tick(&mut engine, &mut state, now);
+
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Monitor plus an engine over `processes` whose foreground the test controls
+    fn foreground_setup(
+        persistence: &CountingPersistence,
+        processes: &Arc<Mutex<Vec<ProcessInfo>>>,
+        config: FreezeConfig,
+    ) -> (
+        Monitor<'_>,
+        FreezeEngine<SharedEnumerator, CountingController, DefaultCategorizer>,
+        Arc<Mutex<Option<(u32, bool)>>>,
+    ) {
+        let (monitor, _, _, _) = delayed_setup(persistence, 0);
+        let foreground = Arc::new(Mutex::new(None));
+        let engine = FreezeEngine::new(
+            SharedEnumerator {
+                processes: processes.clone(),
+                idle_secs: Arc::default(),
+                foreground: foreground.clone(),
+            },
+            CountingController {
+                calls: Arc::default(),
+            },
+            DefaultCategorizer::new(),
+            config,
+        );
+        (monitor, engine, foreground)
+    }
+
+    #[test]
+    fn test_browser_in_foreground_keeps_session_and_runs() {
+        let persistence = CountingPersistence::default();
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "game.exe", 4000, ProcessCategory::Gaming),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+            process(3, "Spotify.exe", 400, ProcessCategory::Productivity),
+        ]));
+        let (mut monitor, mut engine, foreground) =
+            foreground_setup(&persistence, &processes, FreezeConfig::default());
+        *foreground.lock().unwrap() = Some((2, false));
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // The game runs behind the browser: session on, only the browser spared
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert_eq!(state.frozen_pids, HashSet::from([3]));
+
+        // Later passes neither end the session nor freeze the browser
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert!(!state.frozen_pids.contains(&2));
+    }
+
+    #[test]
+    fn test_fullscreen_session_survives_alt_tab() {
+        let persistence = CountingPersistence::default();
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "indie.exe", 3000, ProcessCategory::Gaming),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+        ]));
+        let config = FreezeConfig {
+            trigger_on_fullscreen: true,
+            ..Default::default()
+        };
+        let (mut monitor, mut engine, foreground) =
+            foreground_setup(&persistence, &processes, config);
+        *foreground.lock().unwrap() = Some((1, true));
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // Fullscreen game starts the session
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert_eq!(state.frozen_pids, HashSet::from([2]));
+
+        // Alt-tab to a newly opened browser window
+        processes.lock().unwrap().push(process(
+            3,
+            "firefox.exe",
+            900,
+            ProcessCategory::Productivity,
+        ));
+        *foreground.lock().unwrap() = Some((3, false));
+        monitor.tick(&mut engine, &mut state, now);
+
+        assert!(state.game_detected);
+        assert_eq!(state.gaming_pids, HashSet::from([1]));
+        assert!(!state.frozen_pids.contains(&1));
+        assert!(!state.frozen_pids.contains(&3));
+
+        // The game exits: session over
+        processes.lock().unwrap().retain(|p| p.pid != 1);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+    }
+
+    #[test]
+    fn test_fullscreen_browser_ends_session_once_windowed() {
+        let persistence = CountingPersistence::default();
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "firefox.exe", 900, ProcessCategory::Productivity),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+        ]));
+        let config = FreezeConfig {
+            trigger_on_fullscreen: true,
+            ..Default::default()
+        };
+        let (mut monitor, mut engine, foreground) =
+            foreground_setup(&persistence, &processes, config);
+        *foreground.lock().unwrap() = Some((1, true));
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // F11 in the browser starts a session
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert_eq!(state.frozen_pids, HashSet::from([2]));
+
+        // Leaving fullscreen ends it, though the browser keeps running
+        *foreground.lock().unwrap() = Some((1, false));
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+    }
+
+    #[test]
+    fn test_timed_out_resume_stays_tracked_until_it_finishes() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, _, _, _) = delayed_setup(&persistence, 0);
+        let processes = Arc::new(Mutex::new(vec![
+            process(1, "game.exe", 4000, ProcessCategory::Gaming),
+            process(2, "chrome.exe", 800, ProcessCategory::Productivity),
+            process(3, "Spotify.exe", 400, ProcessCategory::Productivity),
+        ]));
+        let late = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = FreezeEngine::new(
+            SharedEnumerator {
+                processes: processes.clone(),
+                idle_secs: Arc::default(),
+                foreground: Arc::default(),
+            },
+            LateController {
+                stuck: vec![2],
+                late: late.clone(),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        monitor.tick(&mut engine, &mut state, now);
+        assert_eq!(state.frozen_pids, HashSet::from([2, 3]));
+
+        // The game exits; chrome's resume is stuck past the timeout
+        processes.lock().unwrap().retain(|p| p.pid != 1);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert_eq!(state.frozen_pids, HashSet::from([2]));
+        assert_eq!(
+            monitor
+                .persistent_state
+                .name_lookup()
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&2]
+        );
+
+        // It finishes: both views drop it together
+        late.lock().unwrap().push(2);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.frozen_pids.is_empty());
+        assert!(monitor.persistent_state.is_empty());
+    }
+
+    /// Monitor plus an engine with a 60s idle trigger over one freezable app
+    fn idle_setup(
+        persistence: &CountingPersistence,
+    ) -> (
+        Monitor<'_>,
+        FreezeEngine<SharedEnumerator, CountingController, DefaultCategorizer>,
+        Arc<Mutex<Vec<ProcessInfo>>>,
+        Arc<Mutex<Option<u64>>>,
+        Arc<AtomicUsize>,
+    ) {
+        let (monitor, _, _, _) = delayed_setup(persistence, 0);
+        let processes = Arc::new(Mutex::new(vec![process(
+            2,
+            "chrome.exe",
+            800,
+            ProcessCategory::Productivity,
+        )]));
+        let idle_secs = Arc::new(Mutex::new(Some(0)));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let engine = FreezeEngine::new(
+            SharedEnumerator {
+                processes: processes.clone(),
+                idle_secs: idle_secs.clone(),
+                foreground: Arc::default(),
+            },
+            CountingController {
+                calls: calls.clone(),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                idle_trigger_secs: Some(60),
+                ..Default::default()
+            },
+        );
+        (monitor, engine, processes, idle_secs, calls)
+    }
+
+    #[test]
+    fn test_idle_freezes_and_input_resumes() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, _processes, idle_secs, calls) = idle_setup(&persistence);
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        *idle_secs.lock().unwrap() = Some(59);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        *idle_secs.lock().unwrap() = Some(60);
+        monitor.tick(&mut engine, &mut state, now);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert!(state.frozen_pids.contains(&2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        *idle_secs.lock().unwrap() = Some(0);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_idle_and_game_share_one_session() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, processes, idle_secs, calls) = idle_setup(&persistence);
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // Idle freezes first; a game starting later doesn't freeze again
+        *idle_secs.lock().unwrap() = Some(600);
+        monitor.tick(&mut engine, &mut state, now);
+        processes
+            .lock()
+            .unwrap()
+            .push(process(1, "game.exe", 4000, ProcessCategory::Gaming));
+        monitor.tick(&mut engine, &mut state, now);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Input while the game runs keeps everything frozen
+        *idle_secs.lock().unwrap() = Some(0);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        assert!(state.frozen_pids.contains(&2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Going idle again mid-game is not a new session either
+        *idle_secs.lock().unwrap() = Some(600);
+        monitor.tick(&mut engine, &mut state, now);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Only with the game gone and the user back is everything resumed
+        processes.lock().unwrap().retain(|p| p.pid != 1);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.game_detected);
+        *idle_secs.lock().unwrap() = Some(0);
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_pinned_processes_stay_running() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, processes, calls) = delayed_setup(&persistence, 0);
+        processes.lock().unwrap().push(process(
+            3,
+            "Spotify.exe",
+            400,
+            ProcessCategory::Productivity,
+        ));
+        let mut state = DaemonState::new();
+        let now = Instant::now();
+
+        // Pinned before the game starts: never frozen
+        state.toggle_pinned(3, "Spotify.exe");
+        monitor.tick(&mut engine, &mut state, now);
+        assert_eq!(state.frozen_pids, HashSet::from([2]));
+        assert_eq!(
+            state
+                .pin_candidates
+                .iter()
+                .map(|p| p.pid)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Pinned mid-session: resumed on the next check and not refrozen
+        state.toggle_pinned(2, "chrome.exe");
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        state.toggle_pinned(2, "chrome.exe");
+        monitor.tick(&mut engine, &mut state, now);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_game_exiting_during_delay_cancels_freeze() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, processes, calls) = delayed_setup(&persistence, 10);
+        let mut state = DaemonState::new();
+        let start = Instant::now();
+
+        monitor.tick(&mut engine, &mut state, start);
+        monitor.tick(&mut engine, &mut state, start + Duration::from_secs(5));
+        processes.lock().unwrap().remove(0);
+        monitor.tick(&mut engine, &mut state, start + Duration::from_secs(8));
+        // Past the original deadline, but the game is gone
+        monitor.tick(&mut engine, &mut state, start + Duration::from_secs(20));
+
+        assert!(!state.game_detected);
+        assert!(state.frozen_pids.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_freeze_waits_for_delay() {
+        let persistence = CountingPersistence::default();
+        let (mut monitor, mut engine, _processes, calls) = delayed_setup(&persistence, 10);
+        let mut state = DaemonState::new();
+        let start = Instant::now();
+
+        monitor.tick(&mut engine, &mut state, start);
+        monitor.tick(&mut engine, &mut state, start + Duration::from_secs(9));
+        assert!(!state.game_detected);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        monitor.tick(&mut engine, &mut state, start + Duration::from_secs(10));
+        assert!(state.game_detected);
+        assert!(state.frozen_pids.contains(&2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
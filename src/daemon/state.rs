@@ -1,35 +1,204 @@
 //! Daemon state management
 
-use std::collections::HashSet;
+use crate::process::ProcessInfo;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 /// Daemon runtime state
 #[derive(Debug)]
 pub struct DaemonState {
     /// PIDs of currently frozen processes
     pub frozen_pids: HashSet<u32>,
-    /// Whether a game is currently running
+    /// Memory held by the currently frozen processes
+    pub frozen_memory_mb: u64,
+    /// When each frozen process was frozen
+    pub frozen_at: HashMap<u32, Instant>,
+    /// Memory held by each frozen process
+    frozen_sizes: HashMap<u32, u64>,
+    /// Processes resumed by `max_freeze_secs` this session (not refrozen until it ends)
+    pub released_pids: HashSet<u32>,
+    /// Whether a freeze session is active (started by a game or by idle time)
     pub game_detected: bool,
+    /// PIDs of the games (or fullscreen app) seen running on the last check
+    pub gaming_pids: HashSet<u32>,
+    /// Whether the user was idle past `idle_trigger_secs` on the last check
+    pub user_idle: bool,
     /// Whether auto-freeze is enabled
     pub enabled: bool,
+    /// PIDs pinned from the tray; never frozen
+    pub pinned: HashSet<u32>,
+    /// Lowercased names pinned from the tray, so a relaunched app stays pinned
+    pub pinned_names: HashSet<String>,
+    /// Largest pinnable processes from the last check, listed in the tray
+    pub pin_candidates: Vec<ProcessInfo>,
+    /// Number of times a game start triggered a freeze pass
+    pub total_freeze_events: u64,
+    /// Processes frozen across all freeze passes
+    pub total_processes_frozen: u64,
+    /// Memory held by frozen processes, summed across all passes
+    pub total_memory_freed_mb: u64,
+    /// Number of times frozen processes were resumed
+    pub total_resume_events: u64,
 }
 
 impl DaemonState {
     pub fn new() -> Self {
         Self {
             frozen_pids: HashSet::new(),
+            frozen_memory_mb: 0,
+            frozen_at: HashMap::new(),
+            frozen_sizes: HashMap::new(),
+            released_pids: HashSet::new(),
             game_detected: false,
+            gaming_pids: HashSet::new(),
+            user_idle: false,
             enabled: true,
+            pinned: HashSet::new(),
+            pinned_names: HashSet::new(),
+            pin_candidates: Vec::new(),
+            total_freeze_events: 0,
+            total_processes_frozen: 0,
+            total_memory_freed_mb: 0,
+            total_resume_events: 0,
+        }
+    }
+
+    /// Start with a saved enabled preference
+    pub fn with_enabled(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::new()
         }
     }
 
     pub fn add_frozen(&mut self, pid: u32) {
-        self.frozen_pids.insert(pid);
+        self.add_frozen_process(pid, 0);
+    }
+
+    /// Track a frozen process together with the memory it holds
+    pub fn add_frozen_process(&mut self, pid: u32, memory_mb: u64) {
+        self.add_frozen_process_at(pid, memory_mb, Instant::now());
+    }
+
+    /// Track a frozen process, recording when it was frozen
+    pub fn add_frozen_process_at(&mut self, pid: u32, memory_mb: u64, frozen_at: Instant) {
+        if self.frozen_pids.insert(pid) {
+            self.frozen_memory_mb += memory_mb;
+            self.frozen_at.insert(pid, frozen_at);
+            self.frozen_sizes.insert(pid, memory_mb);
+        }
+    }
+
+    /// Whether this daemon froze `pid` and hasn't resumed it yet
+    pub fn is_frozen(&self, pid: u32) -> bool {
+        self.frozen_pids.contains(&pid)
+    }
+
+    /// Stop tracking one frozen process
+    pub fn remove_frozen(&mut self, pid: u32) {
+        if self.frozen_pids.remove(&pid) {
+            self.frozen_at.remove(&pid);
+            let memory_mb = self.frozen_sizes.remove(&pid).unwrap_or(0);
+            self.frozen_memory_mb = self.frozen_memory_mb.saturating_sub(memory_mb);
+        }
+    }
+
+    /// Frozen PIDs that have been frozen for longer than `max`, by PID
+    pub fn expired_pids(&self, max: Duration, now: Instant) -> Vec<u32> {
+        let mut expired: Vec<u32> = self
+            .frozen_at
+            .iter()
+            .filter(|(_, &at)| now.saturating_duration_since(at) > max)
+            .map(|(&pid, _)| pid)
+            .collect();
+        expired.sort_unstable();
+        expired
+    }
+
+    /// PIDs the refreeze pass must skip: frozen ones and ones released by the cap
+    pub fn refreeze_exclusions(&self) -> HashSet<u32> {
+        self.frozen_pids
+            .union(&self.released_pids)
+            .copied()
+            .collect()
     }
 
     pub fn clear_frozen(&mut self) -> Vec<u32> {
+        self.frozen_memory_mb = 0;
+        self.frozen_at.clear();
+        self.frozen_sizes.clear();
+        self.released_pids.clear();
         self.frozen_pids.drain().collect()
     }
 
+    /// Label for the tray's info item
+    pub fn frozen_label(&self) -> String {
+        match self.frozen_pids.len() {
+            0 => "Frozen: none".to_string(),
+            1 => format!("Frozen: 1 process ({} MB)", self.frozen_memory_mb),
+            n => format!("Frozen: {} processes ({} MB)", n, self.frozen_memory_mb),
+        }
+    }
+
+    /// Replace the set of running games, returning the PIDs that started and
+    /// exited since the last check (each sorted)
+    pub fn update_gaming_pids(&mut self, current: HashSet<u32>) -> (Vec<u32>, Vec<u32>) {
+        let mut started: Vec<u32> = current.difference(&self.gaming_pids).copied().collect();
+        let mut exited: Vec<u32> = self.gaming_pids.difference(&current).copied().collect();
+        started.sort_unstable();
+        exited.sort_unstable();
+        self.gaming_pids = current;
+        (started, exited)
+    }
+
+    /// Record a completed freeze pass
+    pub fn record_freeze_event(&mut self, processes: usize, memory_mb: u64) {
+        self.total_freeze_events += 1;
+        self.record_frozen(processes, memory_mb);
+    }
+
+    /// Count processes frozen mid-session without starting a new freeze event
+    pub fn record_frozen(&mut self, processes: usize, memory_mb: u64) {
+        self.total_processes_frozen += processes as u64;
+        self.total_memory_freed_mb += memory_mb;
+    }
+
+    /// Record a completed resume pass
+    pub fn record_resume_event(&mut self) {
+        self.total_resume_events += 1;
+    }
+
+    /// Session statistics, one item per line
+    pub fn stats_summary(&self) -> String {
+        format!(
+            "Freeze events: {}\nProcesses frozen: {}\nMemory freed: {} MB\nResume events: {}",
+            self.total_freeze_events,
+            self.total_processes_frozen,
+            self.total_memory_freed_mb,
+            self.total_resume_events
+        )
+    }
+
+    /// Whether a process is pinned by PID or by name
+    pub fn is_pinned(&self, pid: u32, name: &str) -> bool {
+        self.pinned.contains(&pid) || self.pinned_names.contains(&name.to_lowercase())
+    }
+
+    /// Pin a process by PID and name, or unpin it if it already is;
+    /// returns whether it is now pinned
+    pub fn toggle_pinned(&mut self, pid: u32, name: &str) -> bool {
+        let name = name.to_lowercase();
+        if self.is_pinned(pid, &name) {
+            self.pinned.remove(&pid);
+            self.pinned_names.remove(&name);
+            false
+        } else {
+            self.pinned.insert(pid);
+            self.pinned_names.insert(name);
+            true
+        }
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
@@ -57,6 +226,31 @@ mod tests {
         assert!(state.frozen_pids.is_empty());
     }
 
+    #[test]
+    fn test_update_gaming_pids_reports_changes() {
+        let mut state = DaemonState::new();
+
+        assert_eq!(
+            state.update_gaming_pids(HashSet::from([10, 20])),
+            (vec![10, 20], vec![])
+        );
+        assert_eq!(
+            state.update_gaming_pids(HashSet::from([20, 30])),
+            (vec![30], vec![10])
+        );
+        assert_eq!(
+            state.update_gaming_pids(HashSet::new()),
+            (vec![], vec![20, 30])
+        );
+        assert!(state.gaming_pids.is_empty());
+    }
+
+    #[test]
+    fn test_with_enabled() {
+        assert!(!DaemonState::with_enabled(false).is_enabled());
+        assert!(DaemonState::with_enabled(true).is_enabled());
+    }
+
     #[test]
     fn test_add_frozen() {
         let mut state = DaemonState::new();
@@ -65,6 +259,17 @@ mod tests {
         assert!(state.frozen_pids.contains(&1234));
     }
 
+    #[test]
+    fn test_is_frozen() {
+        let mut state = DaemonState::new();
+        state.add_frozen_process(1234, 300);
+        assert!(state.is_frozen(1234));
+        assert!(!state.is_frozen(5678));
+
+        state.remove_frozen(1234);
+        assert!(!state.is_frozen(1234));
+    }
+
     #[test]
     fn test_clear_frozen() {
         let mut state = DaemonState::new();
@@ -76,6 +281,63 @@ mod tests {
         assert!(state.frozen_pids.is_empty());
     }
 
+    #[test]
+    fn test_expired_pids() {
+        let start = Instant::now();
+        let mut state = DaemonState::new();
+        state.add_frozen_process_at(1, 100, start);
+        state.add_frozen_process_at(2, 200, start + Duration::from_secs(50));
+
+        let cap = Duration::from_secs(60);
+        assert!(state
+            .expired_pids(cap, start + Duration::from_secs(60))
+            .is_empty());
+        assert_eq!(
+            state.expired_pids(cap, start + Duration::from_secs(61)),
+            vec![1]
+        );
+        assert_eq!(
+            state.expired_pids(cap, start + Duration::from_secs(200)),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_remove_frozen() {
+        let mut state = DaemonState::new();
+        state.add_frozen_process(1, 100);
+        state.add_frozen_process(2, 200);
+
+        state.remove_frozen(1);
+        state.remove_frozen(1);
+        state.released_pids.insert(1);
+
+        assert_eq!(state.frozen_memory_mb, 200);
+        assert!(!state.frozen_at.contains_key(&1));
+        assert_eq!(state.refreeze_exclusions(), HashSet::from([1, 2]));
+
+        state.clear_frozen();
+        assert!(state.refreeze_exclusions().is_empty());
+    }
+
+    #[test]
+    fn test_toggle_pinned() {
+        let mut state = DaemonState::new();
+        assert!(!state.is_pinned(10, "Spotify.exe"));
+
+        assert!(state.toggle_pinned(10, "Spotify.exe"));
+        assert!(state.is_pinned(10, "Spotify.exe"));
+        // A relaunch gets a new PID but keeps the name
+        assert!(state.is_pinned(11, "spotify.exe"));
+        assert!(!state.is_pinned(12, "chrome.exe"));
+
+        assert!(!state.toggle_pinned(11, "SPOTIFY.EXE"));
+        assert!(!state.is_pinned(11, "spotify.exe"));
+        assert!(state.is_pinned(10, "Spotify.exe"));
+        assert!(!state.toggle_pinned(10, "Spotify.exe"));
+        assert!(state.pinned.is_empty() && state.pinned_names.is_empty());
+    }
+
     #[test]
     fn test_toggle_enabled() {
         let mut state = DaemonState::new();
@@ -87,4 +349,38 @@ mod tests {
         state.toggle_enabled();
         assert!(state.is_enabled());
     }
+
+    #[test]
+    fn test_frozen_label() {
+        let mut state = DaemonState::new();
+        assert_eq!(state.frozen_label(), "Frozen: none");
+
+        state.add_frozen_process(1, 1200);
+        assert_eq!(state.frozen_label(), "Frozen: 1 process (1200 MB)");
+
+        state.add_frozen_process(2, 643);
+        state.add_frozen_process(2, 643);
+        assert_eq!(state.frozen_label(), "Frozen: 2 processes (1843 MB)");
+
+        state.clear_frozen();
+        assert_eq!(state.frozen_label(), "Frozen: none");
+    }
+
+    #[test]
+    fn test_stats_counters() {
+        let mut state = DaemonState::new();
+        state.record_freeze_event(3, 900);
+        state.record_freeze_event(2, 400);
+        state.record_frozen(1, 100);
+        state.record_resume_event();
+
+        assert_eq!(state.total_freeze_events, 2);
+        assert_eq!(state.total_processes_frozen, 6);
+        assert_eq!(state.total_memory_freed_mb, 1400);
+        assert_eq!(state.total_resume_events, 1);
+
+        let summary = state.stats_summary();
+        assert_eq!(summary.lines().count(), 4);
+        assert!(summary.contains("Memory freed: 1400 MB"));
+    }
 }
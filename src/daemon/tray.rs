@@ -1,152 +1,434 @@
 //! System tray UI
 
+use super::service::{resume_on_shutdown, toggle_auto_freeze};
 use super::state::DaemonState;
-use crate::windows::WindowsRegistry;
+use crate::windows::{StartupOptions, WindowsRegistry};
 use std::sync::{Arc, Mutex};
-use tray_icon::menu::{Menu, MenuEvent, MenuItem};
-use tray_icon::{Icon, TrayIconBuilder};
+use std::time::Duration;
+use tray_icon::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, Submenu};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 use winit::event_loop::{ControlFlow, EventLoop};
 
-pub fn run_system_tray(state: Arc<Mutex<DaemonState>>) -> Result<(), Box<dyn std::error::Error>> {
-    let event_loop = EventLoop::new()?;
-
-    // Create menu items
-    let tray_menu = Menu::new();
-    let enable_item = MenuItem::new("Enable Auto-Freeze", true, None);
-    let startup_item = MenuItem::new("Run on Windows Startup", true, None);
-    let quit_item = MenuItem::new("Quit", true, None);
-
-    tray_menu.append(&enable_item)?;
-    tray_menu.append(&startup_item)?;
-    tray_menu.append(&quit_item)?;
-
-    // Create tray icon (simple blue square)
-    let icon_rgba = create_icon_data();
-    let icon = Icon::from_rgba(icon_rgba, 32, 32)?;
-
-    let _tray_icon = TrayIconBuilder::new()
-        .with_menu(Box::new(tray_menu))
-        .with_icon(icon)
-        .with_tooltip("SmartFreeze - Auto Process Freezer")
-        .build()?;
-
-    println!("[SmartFreeze] ✓ System tray icon created");
-
-    // Event loop
-    let menu_channel = MenuEvent::receiver();
-    let registry = WindowsRegistry::new();
-
-    event_loop.run(move |_event, elwt| {
-        elwt.set_control_flow(ControlFlow::Wait);
-
-        if let Ok(event) = menu_channel.try_recv() {
-            if event.id == enable_item.id() {
-                // Toggle auto-freeze
-                let mut state_guard = state.lock().unwrap();
-                state_guard.toggle_enabled();
-                let enabled = state_guard.is_enabled();
-                drop(state_guard);
-
-                println!(
-                    "[SmartFreeze] Auto-freeze: {}",
-                    if enabled { "ENABLED" } else { "DISABLED" }
-                );
-
-                // Update menu text
-                enable_item.set_text(if enabled {
-                    "Disable Auto-Freeze"
-                } else {
-                    "Enable Auto-Freeze"
-                });
-            } else if event.id == startup_item.id() {
-                // Toggle Windows startup
-                if registry.is_installed() {
-                    match registry.uninstall_startup() {
-                        Ok(()) => {
-                            println!("[SmartFreeze] ✓ Removed from Windows startup");
-                            startup_item.set_text("Run on Windows Startup");
-                        }
-                        Err(e) => {
-                            eprintln!("[SmartFreeze] ✗ Failed to remove from startup: {}", e);
+/// Identifies a tray menu entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuSlot {
+    Info,
+    Enable,
+    Pin,
+    Startup,
+    Stats,
+    Quit,
+}
+
+/// Something menu items can be appended to (allows mocking)
+trait MenuAppender<T: ?Sized> {
+    fn try_append(&self, item: &T) -> Result<(), String>;
+}
+
+impl MenuAppender<dyn IsMenuItem> for Menu {
+    fn try_append(&self, item: &dyn IsMenuItem) -> Result<(), String> {
+        self.append(item).map_err(|e| e.to_string())
+    }
+}
+
+/// Result of building the tray menu item by item
+#[derive(Debug, Default)]
+struct MenuAssembly {
+    appended: Vec<MenuSlot>,
+    errors: Vec<(MenuSlot, String)>,
+}
+
+impl MenuAssembly {
+    fn has(&self, slot: MenuSlot) -> bool {
+        self.appended.contains(&slot)
+    }
+}
+
+/// Append every item, collecting failures instead of aborting on the first
+fn assemble_menu<M, T>(menu: &M, items: &[(MenuSlot, &T)]) -> MenuAssembly
+where
+    M: MenuAppender<T>,
+    T: ?Sized,
+{
+    let mut assembly = MenuAssembly::default();
+
+    for &(slot, item) in items {
+        match menu.try_append(item) {
+            Ok(()) => assembly.appended.push(slot),
+            Err(e) => assembly.errors.push((slot, e)),
+        }
+    }
+
+    assembly
+}
+
+/// Label of the enable/disable menu item
+fn enable_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Disable Auto-Freeze"
+    } else {
+        "Enable Auto-Freeze"
+    }
+}
+
+/// A process offered in the pin submenu
+#[derive(Debug, Clone, PartialEq)]
+struct PinRow {
+    pid: u32,
+    name: String,
+    label: String,
+    pinned: bool,
+}
+
+/// The pin submenu's rows for the monitor's latest candidates
+fn pin_rows(state: &DaemonState) -> Vec<PinRow> {
+    state
+        .pin_candidates
+        .iter()
+        .map(|p| PinRow {
+            pid: p.pid,
+            name: p.name.clone(),
+            label: format!("{} (PID {}, {} MB)", p.name, p.pid, p.memory_mb),
+            pinned: state.is_pinned(p.pid, &p.name),
+        })
+        .collect()
+}
+
+/// A check item in the pin submenu and the row it was built from
+struct PinEntry {
+    item: CheckMenuItem,
+    row: PinRow,
+}
+
+/// Replace the pin submenu's items with one check item per row
+fn rebuild_pin_menu(menu: &Submenu, entries: &mut Vec<PinEntry>, rows: &[PinRow]) {
+    for entry in entries.drain(..) {
+        let _ = menu.remove(&entry.item);
+    }
+    for row in rows {
+        let item = CheckMenuItem::new(&row.label, true, row.pinned, None);
+        match menu.append(&item) {
+            Ok(()) => entries.push(PinEntry {
+                item,
+                row: row.clone(),
+            }),
+            Err(e) => log::warn!("Failed to list {} in the pin menu: {}", row.name, e),
+        }
+    }
+    menu.set_enabled(!entries.is_empty());
+}
+
+/// Tray icon and menu, built but not yet running
+pub struct SystemTray {
+    event_loop: EventLoop<()>,
+    tray_icon: TrayIcon,
+    info_item: MenuItem,
+    enable_item: MenuItem,
+    pin_menu: Submenu,
+    startup_item: MenuItem,
+    stats_item: MenuItem,
+    quit_item: MenuItem,
+    state: Arc<Mutex<DaemonState>>,
+    startup_options: StartupOptions,
+}
+
+impl SystemTray {
+    /// Create the event loop, menu and icon
+    ///
+    /// Fails on headless systems (e.g. Server Core) without a shell to host
+    /// the icon; the caller then runs the daemon without a tray.
+    pub fn new(
+        state: Arc<Mutex<DaemonState>>,
+        startup_options: StartupOptions,
+        icon_color: [u8; 3],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let event_loop = EventLoop::new()?;
+
+        // Create menu items
+        let tray_menu = Menu::new();
+        let info_item = MenuItem::new(state.lock().unwrap().frozen_label(), false, None);
+        let enable_item =
+            MenuItem::new(enable_label(state.lock().unwrap().is_enabled()), true, None);
+        // Filled in once the monitor has listed the running processes
+        let pin_menu = Submenu::new("Keep Running (Pin)", false);
+        let startup_item = MenuItem::new("Run on Windows Startup", true, None);
+        let stats_item = MenuItem::new("Show Stats", true, None);
+        let quit_item = MenuItem::new("Quit", true, None);
+
+        let items: [(MenuSlot, &dyn IsMenuItem); 6] = [
+            (MenuSlot::Info, &info_item),
+            (MenuSlot::Enable, &enable_item),
+            (MenuSlot::Pin, &pin_menu),
+            (MenuSlot::Startup, &startup_item),
+            (MenuSlot::Stats, &stats_item),
+            (MenuSlot::Quit, &quit_item),
+        ];
+        let assembly = assemble_menu(&tray_menu, &items);
+
+        for (slot, error) in &assembly.errors {
+            eprintln!(
+                "[SmartFreeze] ⚠ Failed to add {:?} menu item: {}",
+                slot, error
+            );
+        }
+
+        // Without a Quit item the tray can't be used to stop the daemon
+        if !assembly.has(MenuSlot::Quit) {
+            return Err("tray menu has no Quit item".into());
+        }
+
+        // Create tray icon (a square in the configured color)
+        let icon_rgba = create_icon_data(icon_color);
+        let icon = Icon::from_rgba(icon_rgba, ICON_SIZE, ICON_SIZE)?;
+
+        let tray_icon = TrayIconBuilder::new()
+            .with_menu(Box::new(tray_menu))
+            .with_icon(icon)
+            .with_tooltip("SmartFreeze - Auto Process Freezer")
+            .build()?;
+
+        println!("[SmartFreeze] ✓ System tray icon created");
+
+        Ok(Self {
+            event_loop,
+            tray_icon,
+            info_item,
+            enable_item,
+            pin_menu,
+            startup_item,
+            stats_item,
+            quit_item,
+            state,
+            startup_options,
+        })
+    }
+
+    /// Run the tray's event loop on the calling (main) thread until Quit
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let Self {
+            event_loop,
+            tray_icon,
+            info_item,
+            enable_item,
+            pin_menu,
+            startup_item,
+            stats_item,
+            quit_item,
+            state,
+            startup_options,
+        } = self;
+        let mut frozen_label = state.lock().unwrap().frozen_label();
+        let mut shown_enabled = state.lock().unwrap().is_enabled();
+        let mut pin_entries: Vec<PinEntry> = Vec::new();
+        let mut shown_rows: Vec<PinRow> = Vec::new();
+
+        // Event loop
+        let menu_channel = MenuEvent::receiver();
+        let registry = WindowsRegistry::new();
+
+        event_loop.run(move |_event, elwt| {
+            // Wake periodically so the frozen count follows the monitor loop
+            elwt.set_control_flow(ControlFlow::wait_duration(Duration::from_secs(1)));
+
+            let label = state.lock().unwrap().frozen_label();
+            if label != frozen_label {
+                info_item.set_text(&label);
+                frozen_label = label;
+            }
+
+            // The hotkey toggles from its own thread
+            let enabled = state.lock().unwrap().is_enabled();
+            if enabled != shown_enabled {
+                enable_item.set_text(enable_label(enabled));
+                shown_enabled = enabled;
+            }
+
+            // The monitor refreshes the candidates on every check
+            let rows = pin_rows(&state.lock().unwrap());
+            if rows != shown_rows {
+                rebuild_pin_menu(&pin_menu, &mut pin_entries, &rows);
+                shown_rows = rows;
+            }
+
+            if let Ok(event) = menu_channel.try_recv() {
+                if event.id == enable_item.id() {
+                    shown_enabled = toggle_auto_freeze(&state);
+                    enable_item.set_text(enable_label(shown_enabled));
+                } else if event.id == startup_item.id() {
+                    // Toggle Windows startup
+                    if registry.is_installed() {
+                        match registry.uninstall_startup() {
+                            Ok(()) => {
+                                println!("[SmartFreeze] ✓ Removed from Windows startup");
+                                startup_item.set_text("Run on Windows Startup");
+                            }
+                            Err(e) => {
+                                eprintln!("[SmartFreeze] ✗ Failed to remove from startup: {}", e);
+                            }
                         }
-                    }
-                } else {
-                    match std::env::current_exe() {
-                        Ok(exe_path) => {
-                            if let Some(path_str) = exe_path.to_str() {
-                                match registry.install_startup(path_str) {
-                                    Ok(()) => {
-                                        println!("[SmartFreeze] ✓ Added to Windows startup");
-                                        startup_item.set_text("Remove from Windows Startup");
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "[SmartFreeze] ✗ Failed to add to startup: {}",
-                                            e
-                                        );
+                    } else {
+                        match std::env::current_exe() {
+                            Ok(exe_path) => {
+                                if let Some(path_str) = exe_path.to_str() {
+                                    match registry.install_startup(path_str, &startup_options) {
+                                        Ok(()) => {
+                                            println!("[SmartFreeze] ✓ Added to Windows startup");
+                                            startup_item.set_text("Remove from Windows Startup");
+                                        }
+                                        Err(e) => {
+                                            eprintln!(
+                                                "[SmartFreeze] ✗ Failed to add to startup: {}",
+                                                e
+                                            );
+                                        }
                                     }
                                 }
                             }
-                        }
-                        Err(e) => {
-                            eprintln!("[SmartFreeze] ✗ Failed to get exe path: {}", e);
-                        }
-                    }
-                }
-            } else if event.id == quit_item.id() {
-                // Quit daemon - restart all terminated processes
-                println!("[SmartFreeze] Shutting down...");
-
-                // Load from persistence to get exe paths
-                let persistence = crate::persistence::FileStatePersistence::with_default_path();
-                use crate::persistence::StatePersistence;
-
-                if let Ok(Some(saved_state)) = persistence.load() {
-                    let valid = saved_state.get_valid_processes();
-                    if !valid.is_empty() {
-                        println!(
-                            "[SmartFreeze] Restarting {} terminated processes...",
-                            valid.len()
-                        );
-                        let controller = crate::windows::WindowsProcessController::new();
-
-                        for frozen in valid {
-                            match controller.restart_process(&frozen.exe_path) {
-                                Ok(new_pid) => println!(
-                                    "[SmartFreeze]   ✓ Restarted {} (new PID: {})",
-                                    frozen.name, new_pid
-                                ),
-                                Err(e) => eprintln!(
-                                    "[SmartFreeze]   ✗ Failed to restart {}: {}",
-                                    frozen.name, e
-                                ),
+                            Err(e) => {
+                                eprintln!("[SmartFreeze] ✗ Failed to get exe path: {}", e);
                             }
                         }
                     }
-                }
+                } else if event.id == stats_item.id() {
+                    let summary = state.lock().unwrap().stats_summary();
+                    println!("[SmartFreeze] Session stats:\n{}", summary);
+                    let _ = tray_icon.set_tooltip(Some(format!("SmartFreeze\n{}", summary)));
+                } else if event.id == quit_item.id() {
+                    // Quit daemon - resume all frozen processes
+                    println!("[SmartFreeze] Shutting down...");
 
-                // Clear persistent state
-                let _ = persistence.save(&crate::persistence::PersistentState::new());
+                    resume_on_shutdown(&state);
 
-                println!("[SmartFreeze] Goodbye!");
-                elwt.exit();
+                    println!("[SmartFreeze] Goodbye!");
+                    elwt.exit();
+                } else if let Some(entry) = pin_entries.iter().find(|e| event.id == e.item.id()) {
+                    let row = &entry.row;
+                    let pinned = state.lock().unwrap().toggle_pinned(row.pid, &row.name);
+                    entry.item.set_checked(pinned);
+                    log::info!(
+                        "{} {} (PID {})",
+                        if pinned { "📌 Pinned" } else { "Unpinned" },
+                        row.name,
+                        row.pid
+                    );
+                }
             }
-        }
-    })?;
+        })?;
 
-    Ok(())
+        Ok(())
+    }
 }
 
-fn create_icon_data() -> Vec<u8> {
-    // Create a simple 32x32 blue square icon
-    let mut rgba = Vec::with_capacity(32 * 32 * 4);
-    for _ in 0..32 {
-        for _ in 0..32 {
-            rgba.push(64); // R
-            rgba.push(128); // G
-            rgba.push(255); // B
-            rgba.push(255); // A
+/// Width and height of the tray icon in pixels
+const ICON_SIZE: u32 = 32;
+
+/// Opaque `ICON_SIZE` square of `[r, g, b]` as RGBA bytes
+fn create_icon_data([r, g, b]: [u8; 3]) -> Vec<u8> {
+    [r, g, b, 255].repeat((ICON_SIZE * ICON_SIZE) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct MockMenu {
+        fail_on: Vec<&'static str>,
+        appended: RefCell<Vec<&'static str>>,
+    }
+
+    impl MenuAppender<&'static str> for MockMenu {
+        fn try_append(&self, item: &&'static str) -> Result<(), String> {
+            if self.fail_on.contains(item) {
+                return Err(format!("cannot append {}", item));
+            }
+            self.appended.borrow_mut().push(item);
+            Ok(())
         }
     }
-    rgba
+
+    #[test]
+    fn test_assemble_menu_partial() {
+        let menu = MockMenu {
+            fail_on: vec!["startup"],
+            appended: RefCell::new(Vec::new()),
+        };
+
+        let assembly = assemble_menu(
+            &menu,
+            &[
+                (MenuSlot::Enable, &"enable"),
+                (MenuSlot::Startup, &"startup"),
+                (MenuSlot::Quit, &"quit"),
+            ],
+        );
+
+        assert_eq!(assembly.appended, vec![MenuSlot::Enable, MenuSlot::Quit]);
+        assert_eq!(assembly.errors.len(), 1);
+        assert_eq!(assembly.errors[0].0, MenuSlot::Startup);
+        assert!(assembly.has(MenuSlot::Quit));
+        assert_eq!(*menu.appended.borrow(), vec!["enable", "quit"]);
+    }
+
+    #[test]
+    fn test_pin_rows_follow_candidates() {
+        use crate::process::{ProcessCategory, ProcessInfo};
+
+        let mut state = DaemonState::new();
+        assert!(pin_rows(&state).is_empty());
+
+        state.pin_candidates = vec![
+            ProcessInfo::new(
+                3310,
+                "chrome.exe".to_string(),
+                String::new(),
+                1850,
+                false,
+                ProcessCategory::Productivity,
+            ),
+            ProcessInfo::new(
+                3520,
+                "Spotify.exe".to_string(),
+                String::new(),
+                310,
+                false,
+                ProcessCategory::Productivity,
+            ),
+        ];
+        state.toggle_pinned(3520, "Spotify.exe");
+
+        let rows = pin_rows(&state);
+        assert_eq!(rows[0].label, "chrome.exe (PID 3310, 1850 MB)");
+        assert!(!rows[0].pinned);
+        assert_eq!(rows[1].name, "Spotify.exe");
+        assert!(rows[1].pinned);
+    }
+
+    #[test]
+    fn test_icon_data_uses_configured_color() {
+        let rgba = create_icon_data([220, 20, 60]);
+
+        assert_eq!(rgba.len(), 32 * 32 * 4);
+        assert!(rgba.chunks(4).all(|pixel| pixel == [220, 20, 60, 255]));
+        assert_eq!(
+            &create_icon_data(crate::persistence::DEFAULT_ICON_COLOR)[..4],
+            &[64, 128, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_assemble_menu_missing_quit() {
+        let menu = MockMenu {
+            fail_on: vec!["quit"],
+            appended: RefCell::new(Vec::new()),
+        };
+
+        let assembly = assemble_menu(
+            &menu,
+            &[(MenuSlot::Enable, &"enable"), (MenuSlot::Quit, &"quit")],
+        );
+
+        assert!(assembly.has(MenuSlot::Enable));
+        assert!(!assembly.has(MenuSlot::Quit));
+    }
 }
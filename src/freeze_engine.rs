@@ -1,37 +1,509 @@
 //! Core freeze engine logic
 
+use crate::audit::{AuditAction, AuditEntry, AuditLogger};
 use crate::categorization::ProcessCategorizer;
 use crate::process::{ProcessCategory, ProcessInfo};
-use crate::Result;
+use crate::trace::{DecisionTrace, TraceDecision, TraceEntry};
+use crate::{Result, SmartFreezeError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Strategy used to take a process out of the way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FreezeMode {
+    /// Suspend every thread of the process
+    Suspend,
+    /// Keep the process running with EcoQoS and idle priority
+    Throttle,
+    /// Lower priority and pin to one core; suspend too with `escalate_soft_freeze`
+    Soft,
+}
+
+impl FreezeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FreezeMode::Suspend => "suspend",
+            FreezeMode::Throttle => "throttle",
+            FreezeMode::Soft => "soft",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        match self {
+            FreezeMode::Suspend => 1 << 0,
+            FreezeMode::Throttle => 1 << 1,
+            FreezeMode::Soft => 1 << 2,
+        }
+    }
+}
+
+/// Set of freeze modes a controller is able to perform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FreezeCapabilities(u8);
+
+impl FreezeCapabilities {
+    /// No capabilities at all
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    /// Capabilities containing exactly the given modes
+    pub fn from_modes(modes: &[FreezeMode]) -> Self {
+        modes
+            .iter()
+            .fold(Self::none(), |caps, &mode| caps.with(mode))
+    }
+
+    /// Add a mode to this set
+    pub fn with(self, mode: FreezeMode) -> Self {
+        Self(self.0 | mode.bit())
+    }
+
+    /// Check whether a mode is part of this set
+    pub fn supports(&self, mode: FreezeMode) -> bool {
+        self.0 & mode.bit() != 0
+    }
+}
+
+/// Why a process is kept running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProtectReason {
+    Foreground,
+    Critical,
+    Gaming,
+    Communication,
+    BelowThreshold,
+    BelowCpuThreshold,
+    TooYoung,
+    NeverFreeze,
+    /// Pinned from the daemon's tray for this session
+    Pinned,
+    /// Belongs to another user's session
+    OtherSession,
+    /// Safe to freeze, but beyond `max_processes`
+    OverProcessCap,
+    /// Safe to freeze, but would push the total past `max_total_freeze_mb`
+    OverMemoryCap,
+    /// SmartFreeze itself or the console hosting it
+    SelfProcess,
+    /// Has a visible window (with `keep_visible_windows`)
+    VisibleWindow,
+    /// Couldn't be opened during enumeration
+    Inaccessible,
+}
+
+impl ProtectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtectReason::Foreground => "Foreground",
+            ProtectReason::Critical => "Critical",
+            ProtectReason::Gaming => "Gaming",
+            ProtectReason::Communication => "Communication",
+            ProtectReason::BelowThreshold => "Below threshold",
+            ProtectReason::BelowCpuThreshold => "Below CPU threshold",
+            ProtectReason::TooYoung => "Too young",
+            ProtectReason::NeverFreeze => "Never freeze",
+            ProtectReason::Pinned => "Pinned",
+            ProtectReason::OtherSession => "Other session",
+            ProtectReason::OverProcessCap => "Over cap",
+            ProtectReason::OverMemoryCap => "Over memory cap",
+            ProtectReason::VisibleWindow => "Visible window",
+            ProtectReason::SelfProcess => "SmartFreeze",
+            ProtectReason::Inaccessible => "Inaccessible",
+        }
+    }
+}
+
+/// Structured dry-run result from `FreezeEngine::plan`
+#[derive(Debug, Clone, Serialize)]
+pub struct FreezePlan {
+    /// Processes that would be frozen, largest first
+    pub would_freeze: Vec<ProcessInfo>,
+    /// Every other process with the reason it is kept running, largest first
+    pub protected: Vec<(ProcessInfo, ProtectReason)>,
+    pub total_memory_to_free_mb: u64,
+}
+
+/// Why one process got its category and whether it would be frozen (`--explain`)
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub process: ProcessInfo,
+    /// Every rule matching the name or path and why, highest precedence first
+    pub matches: Vec<(ProcessCategory, String)>,
+    /// Category implied by the publisher, if it has one
+    pub publisher_category: Option<ProcessCategory>,
+    /// Name of the parent process, if the last enumeration saw it
+    pub parent_name: Option<String>,
+    /// Memory threshold that applies to the process's category
+    pub threshold_mb: u64,
+    /// Why it is kept running; `None` means it would be frozen
+    pub protect_reason: Option<ProtectReason>,
+}
+
+/// Result of `FreezeEngine::freeze_process_detailed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreezeOutcome {
+    pub pid: u32,
+    pub threads_affected: usize,
+    /// Time spent in the controller call
+    pub elapsed: Duration,
+    /// Working set from the last enumeration, if the PID was in it
+    pub memory_mb_before: Option<u64>,
+}
+
+/// Result of a daemon freeze pass (`FreezeEngine::freeze_session`)
+#[derive(Debug, Default)]
+pub struct SessionReport {
+    /// Processes frozen, in selection order
+    pub frozen: Vec<ProcessInfo>,
+    /// Memory held by the frozen processes in MB
+    pub total_memory_mb: u64,
+    /// Already frozen by another SmartFreeze instance or tool
+    pub skipped: Vec<ProcessInfo>,
+    /// Processes that couldn't be frozen, with the error
+    pub failed: Vec<(ProcessInfo, SmartFreezeError)>,
+    /// RAM measured as released by trimming, with `trim_after_freeze`
+    pub reclaimed_mb: Option<u64>,
+}
+
+impl SessionReport {
+    pub fn frozen_count(&self) -> usize {
+        self.frozen.len()
+    }
+
+    /// Measured reclaim when trimmed, else the working sets at freeze time
+    pub fn freed_mb(&self) -> u64 {
+        self.reclaimed_mb.unwrap_or(self.total_memory_mb)
+    }
+}
+
+/// Working set of a process sampled around a trim
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkingSetTrim {
+    pub before_mb: u64,
+    pub after_mb: u64,
+}
+
+impl WorkingSetTrim {
+    /// RAM actually released by the trim (never negative)
+    pub fn reclaimed_mb(&self) -> u64 {
+        self.before_mb.saturating_sub(self.after_mb)
+    }
+}
+
+/// What a controller changed to freeze one process, so another run can undo it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreezeRecord {
+    /// (thread ID, suspend count before it was suspended)
+    #[serde(default)]
+    pub threads: Vec<(u32, u32)>,
+    /// Priority class and affinity mask before they were lowered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<(u32, usize)>,
+}
+
+/// How `FreezeEngine::resume_or_relaunch` brought a process back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeOutcome {
+    /// Resumed in place, with the number of threads resumed
+    Resumed(usize),
+    /// The process was gone; a new instance was started with this PID
+    Relaunched(u32),
+}
 
 /// Configuration for the freeze engine
 #[derive(Debug, Clone)]
 pub struct FreezeConfig {
     /// Minimum memory threshold in MB
     pub min_memory_mb: u64,
+    /// Per-category memory thresholds in MB, overriding `min_memory_mb`
+    pub category_thresholds: HashMap<ProcessCategory, u64>,
+    /// Treat a fullscreen foreground app as a gaming session
+    pub trigger_on_fullscreen: bool,
     /// Whether to keep communication apps running
     pub keep_communication: bool,
+    /// With `keep_communication`, still freeze the helper processes spawned by
+    /// communication apps; only the top-level app keeps running
+    pub freeze_comm_children: bool,
+    /// Keep processes with a visible, non-minimized window running
+    pub keep_visible_windows: bool,
+    /// Requested freeze mode (`None` uses the controller's native behavior)
+    pub mode: Option<FreezeMode>,
+    /// In soft mode, also suspend the process after lowering its priority
+    pub escalate_soft_freeze: bool,
+    /// Don't freeze processes younger than this (they may still be initializing)
+    pub min_process_age_secs: u64,
+    /// Only freeze processes using at least this much CPU (percent of one core)
+    pub min_cpu_percent: Option<f64>,
+    /// Cap on how many processes are selected (largest memory users first)
+    pub max_processes: Option<usize>,
+    /// Cap on the combined memory of the selected processes in MB; selection
+    /// stops at the first (largest first) process that would exceed it
+    pub max_total_freeze_mb: Option<u64>,
+    /// Process names that are never frozen (case-insensitive)
+    pub never_freeze: Vec<String>,
+    /// Process names frozen regardless of thresholds or category (case-insensitive)
+    pub always_freeze: Vec<String>,
+    /// Free system RAM to aim for; only as many processes as needed are frozen
+    pub target_free_mb: Option<u64>,
+    /// Daemon resumes a process once it has been frozen this long, even mid-game
+    pub max_freeze_secs: Option<u64>,
+    /// Daemon waits this long after a game starts, re-checking it, before freezing
+    pub freeze_delay_secs: u64,
+    /// Daemon also freezes once the user has been idle this long, resuming on input
+    pub idle_trigger_secs: Option<u64>,
+    /// Only freeze processes in our own session (not other RDP/switched users)
+    pub current_session_only: bool,
+    /// Daemon shows a desktop notification after freezing or resuming
+    pub notifications: bool,
+    /// Daemon logs what it would freeze instead of freezing
+    pub dry_run: bool,
+    /// Start a process again from its saved path if it was killed while frozen
+    pub relaunch_killed: bool,
+    /// Empty each frozen process's working set and measure the RAM released
+    pub trim_after_freeze: bool,
+    /// Our own PID; it and its parent (the hosting console) are never frozen
+    pub self_pid: Option<u32>,
 }
 
 impl Default for FreezeConfig {
     fn default() -> Self {
         Self {
             min_memory_mb: 100,
+            category_thresholds: HashMap::new(),
+            trigger_on_fullscreen: false,
             keep_communication: false,
+            freeze_comm_children: false,
+            keep_visible_windows: false,
+            mode: None,
+            escalate_soft_freeze: false,
+            min_process_age_secs: 10,
+            min_cpu_percent: None,
+            max_processes: None,
+            max_total_freeze_mb: None,
+            never_freeze: Vec::new(),
+            always_freeze: Vec::new(),
+            target_free_mb: None,
+            max_freeze_secs: None,
+            freeze_delay_secs: 0,
+            idle_trigger_secs: None,
+            current_session_only: true,
+            notifications: false,
+            dry_run: false,
+            relaunch_killed: false,
+            trim_after_freeze: false,
+            self_pid: None,
         }
     }
 }
 
+impl FreezeConfig {
+    /// Memory threshold for a category (`min_memory_mb` unless overridden)
+    pub fn threshold_for(&self, category: ProcessCategory) -> u64 {
+        self.category_thresholds
+            .get(&category)
+            .copied()
+            .unwrap_or(self.min_memory_mb)
+    }
+}
+
+/// `BELOW_NORMAL_PRIORITY_CLASS`, applied by a soft freeze
+pub const SOFT_FREEZE_PRIORITY_CLASS: u32 = 0x0000_4000;
+/// Affinity mask applied by a soft freeze: the first logical core only
+pub const SOFT_FREEZE_AFFINITY_MASK: usize = 1;
+
+/// Most threads a batch freeze runs at once
+pub const MAX_FREEZE_WORKERS: usize = 8;
+
+/// Attempts per process before a transient freeze failure is reported
+pub const FREEZE_ATTEMPTS: u32 = 3;
+/// Initial delay between freeze attempts (doubles each retry)
+pub const FREEZE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Run `action` on every PID using up to `MAX_FREEZE_WORKERS` threads
+///
+/// Results come back in the order of `pids`, however the work interleaved.
+fn for_each_parallel<F>(pids: &[u32], action: F) -> Vec<(u32, Result<usize>)>
+where
+    F: Fn(u32) -> Result<usize> + Sync,
+{
+    let workers = pids.len().min(MAX_FREEZE_WORKERS);
+    if workers <= 1 {
+        return pids.iter().map(|&pid| (pid, action(pid))).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<usize>>> = pids.iter().map(|_| None).collect();
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(&pid) = pids.get(index) else {
+                            break;
+                        };
+                        done.push((index, action(pid)));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let done = handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (index, result) in done {
+                results[index] = Some(result);
+            }
+        }
+    });
+
+    pids.iter()
+        .zip(results)
+        .map(|(&pid, result)| (pid, result.expect("every PID is claimed by a worker")))
+        .collect()
+}
+
+/// Case-insensitive match of a process name against a configured list
+fn name_listed(list: &[String], name: &str) -> bool {
+    list.iter().any(|entry| entry.eq_ignore_ascii_case(name))
+}
+
+/// Take candidates (largest first) while their combined memory stays within `max_mb`
+pub fn select_within_memory_cap(mut candidates: Vec<ProcessInfo>, max_mb: u64) -> Vec<ProcessInfo> {
+    candidates.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+
+    let mut selected_mb = 0;
+    candidates
+        .into_iter()
+        .take_while(|p| {
+            selected_mb += p.memory_mb;
+            selected_mb <= max_mb
+        })
+        .collect()
+}
+
+/// Pick the biggest candidates until their memory covers `needed_mb`
+pub fn select_until_target(mut candidates: Vec<ProcessInfo>, needed_mb: u64) -> Vec<ProcessInfo> {
+    candidates.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+
+    let mut selected_mb = 0;
+    candidates
+        .into_iter()
+        .take_while(|p| {
+            let take = selected_mb < needed_mb;
+            selected_mb += p.memory_mb;
+            take
+        })
+        .collect()
+}
+
 /// Trait for process enumeration (allows mocking)
 pub trait ProcessEnumerator: Send + Sync {
     fn enumerate(&mut self) -> Result<Vec<ProcessInfo>>;
     fn get_foreground_pid(&self) -> Option<u32>;
+
+    /// Whether the foreground window covers the whole primary screen
+    fn is_foreground_fullscreen(&self) -> bool {
+        false
+    }
+
+    /// Total and available physical memory in MB (`None` if unknown)
+    fn system_memory_mb(&self) -> Option<(u64, u64)> {
+        None
+    }
+
+    /// Session ID of the current process (`None` disables session filtering)
+    fn current_session_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Seconds since the user last touched keyboard or mouse (`None` if unknown)
+    fn idle_seconds(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// Trait for process control (allows mocking)
+///
+/// Controllers may be stateful: `freeze` returns the number of threads it
+/// suspended and `resume` the number it actually resumed, which only covers
+/// suspensions made by this controller.
 pub trait ProcessController: Send + Sync {
+    /// Freeze a process, returning the number of threads suspended
+    ///
+    /// Freezing is not reentrant: a process that is already frozen fails with
+    /// `AlreadyFrozen` instead of being suspended deeper, so a single `resume`
+    /// always wakes it.
     fn freeze(&self, pid: u32) -> Result<usize>;
     fn resume(&self, pid: u32) -> Result<usize>;
+
+    /// Freeze modes this controller can perform (thread suspension by default)
+    fn capabilities(&self) -> FreezeCapabilities {
+        FreezeCapabilities::from_modes(&[FreezeMode::Suspend])
+    }
+
+    /// Check whether this controller can perform the given freeze mode
+    fn supports_mode(&self, mode: FreezeMode) -> bool {
+        self.capabilities().supports(mode)
+    }
+
+    /// Enable or disable efficiency mode (EcoQoS + idle priority) for a process
+    fn set_efficiency_mode(&self, _pid: u32, _enabled: bool) -> Result<()> {
+        Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle))
+    }
+
+    /// Set a priority class and CPU affinity mask, remembering the originals
+    ///
+    /// Repeated calls keep the values recorded by the first one.
+    fn set_priority_and_affinity(
+        &self,
+        _pid: u32,
+        _priority_class: u32,
+        _affinity_mask: usize,
+    ) -> Result<()> {
+        Err(SmartFreezeError::UnsupportedMode(FreezeMode::Soft))
+    }
+
+    /// Put back the priority and affinity saved by `set_priority_and_affinity`
+    fn restore_priority_and_affinity(&self, _pid: u32) -> Result<()> {
+        Err(SmartFreezeError::UnsupportedMode(FreezeMode::Soft))
+    }
+
+    /// Flush a process's working set, returning its size before and after
+    fn trim_working_set(&self, _pid: u32) -> Result<WorkingSetTrim> {
+        Err(SmartFreezeError::UnsupportedOperation("trim working set"))
+    }
+
+    /// Start a fresh instance of an executable, returning its PID
+    fn restart_process(&self, _exe_path: &str) -> Result<u32> {
+        Err(SmartFreezeError::UnsupportedOperation("restart process"))
+    }
+
+    /// What this controller changed to freeze `pid`, for the state file
+    fn freeze_record(&self, _pid: u32) -> FreezeRecord {
+        FreezeRecord::default()
+    }
+
+    /// Take over a freeze recorded by another run, so resuming undoes exactly that
+    fn adopt_freeze(&self, _pid: u32, _record: &FreezeRecord) {}
+
+    /// Executable path of a running process (`None` if unknown or gone)
+    fn exe_path(&self, _pid: u32) -> Option<String> {
+        None
+    }
+
+    /// PIDs whose `resume` timed out but has since succeeded, drained by the call
+    fn take_late_resumes(&self) -> Vec<u32> {
+        Vec::new()
+    }
 }
 
 /// Main freeze engine coordinating process management
@@ -43,9 +515,18 @@ where
 {
     enumerator: E,
     controller: C,
-    #[allow(dead_code)]
     categorizer: Cat,
     config: FreezeConfig,
+    trace: Option<Box<dyn DecisionTrace>>,
+    audit: Option<AuditLogger>,
+    /// Processes seen by the most recent enumeration
+    last_snapshot: Vec<ProcessInfo>,
+    /// PIDs pinned by the user; never frozen
+    pinned_pids: HashSet<u32>,
+    /// Process names pinned by the user (case-insensitive); never frozen
+    pinned_names: Vec<String>,
+    /// Processes making up the running gaming session; never frozen
+    session_pids: HashSet<u32>,
 }
 
 impl<E, C, Cat> FreezeEngine<E, C, Cat>
@@ -60,12 +541,68 @@ where
             controller,
             categorizer,
             config,
+            trace: None,
+            audit: None,
+            last_snapshot: Vec::new(),
+            pinned_pids: HashSet::new(),
+            pinned_names: Vec::new(),
+            session_pids: HashSet::new(),
+        }
+    }
+
+    /// Record every successful freeze and resume to `audit`
+    pub fn set_audit(&mut self, audit: AuditLogger) {
+        self.audit = Some(audit);
+    }
+
+    /// Append a successful freeze or resume to the audit log, if one is set
+    fn record_audit(&self, pid: u32, action: AuditAction, result: &Result<usize>) {
+        let (Some(audit), Ok(threads)) = (&self.audit, result) else {
+            return;
+        };
+        let name = self
+            .last_snapshot
+            .iter()
+            .find(|p| p.pid == pid)
+            .map_or("", |p| p.name.as_str());
+        // Auditing is best effort; it must never turn a freeze into a failure
+        if let Err(e) = audit.record(&AuditEntry::new(pid, name, action, *threads)) {
+            log::warn!("Failed to write audit log: {}", e);
         }
     }
 
+    /// Enumerate processes and remember the result for tree lookups
+    fn refresh(&mut self) -> Result<Vec<ProcessInfo>> {
+        let processes = self.enumerator.enumerate()?;
+        self.last_snapshot = processes.clone();
+        Ok(processes)
+    }
+
+    /// Replace the pinned PIDs and names, which are kept running like `never_freeze`
+    pub fn set_pinned(&mut self, pids: HashSet<u32>, names: Vec<String>) {
+        self.pinned_pids = pids;
+        self.pinned_names = names;
+    }
+
+    /// Whether `process` is pinned by PID or by name
+    pub fn is_pinned(&self, pid: u32, name: &str) -> bool {
+        self.pinned_pids.contains(&pid) || name_listed(&self.pinned_names, name)
+    }
+
+    /// Replace the processes making up the gaming session, which stay protected
+    /// even after they lose the foreground
+    pub fn set_session_pids(&mut self, pids: HashSet<u32>) {
+        self.session_pids = pids;
+    }
+
+    /// Record every selection decision to the given trace sink
+    pub fn set_trace(&mut self, trace: Box<dyn DecisionTrace>) {
+        self.trace = Some(trace);
+    }
+
     /// Get all running processes
     pub fn enumerate_processes(&mut self) -> Result<Vec<ProcessInfo>> {
-        self.enumerator.enumerate()
+        self.refresh()
     }
 
     /// Get foreground process ID
@@ -75,195 +612,2461 @@ where
 
     /// Find processes that are safe to freeze
     pub fn find_safe_to_freeze(&mut self) -> Result<Vec<ProcessInfo>> {
-        let processes = self.enumerator.enumerate()?;
-
-        Ok(processes
-            .into_iter()
-            .filter(|p| {
-                p.memory_mb >= self.config.min_memory_mb
-                    && p.is_safe_to_freeze(self.config.keep_communication)
-            })
-            .collect())
+        self.select_safe(&HashSet::new(), 0)
     }
 
-    /// Find all gaming processes
-    pub fn find_gaming_processes(&mut self) -> Result<Vec<ProcessInfo>> {
-        let processes = self.enumerator.enumerate()?;
+    /// Safe-to-freeze processes outside `already_frozen`, with the memory cap
+    /// reduced by the `frozen_mb` those already hold
+    fn select_safe(
+        &mut self,
+        already_frozen: &HashSet<u32>,
+        frozen_mb: u64,
+    ) -> Result<Vec<ProcessInfo>> {
+        let processes = self.refresh()?;
 
-        Ok(processes
-            .into_iter()
-            .filter(|p| p.category == ProcessCategory::Gaming)
-            .collect())
+        // Biggest memory users first, PID as a deterministic tiebreak
+        let mut safe: Vec<ProcessInfo> = processes
+            .iter()
+            .filter(|p| self.exclusion_reason(p).is_none())
+            .cloned()
+            .collect();
+        safe.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+        if let Some(max) = self.config.max_processes {
+            safe.truncate(max);
+        }
+        safe.retain(|p| !already_frozen.contains(&p.pid));
+        if let Some(max_mb) = self.config.max_total_freeze_mb {
+            safe = select_within_memory_cap(safe, max_mb.saturating_sub(frozen_mb));
+        }
+
+        if let Some(trace) = &self.trace {
+            let decisions = processes
+                .iter()
+                .map(|p| {
+                    let selected = safe.iter().any(|s| s.pid == p.pid);
+                    let reason = match self.exclusion_reason(p) {
+                        Some(reason) => reason,
+                        None if selected => "selected".to_string(),
+                        None if already_frozen.contains(&p.pid) => "already frozen".to_string(),
+                        None => self.describe_reason(self.cap_reason(safe.len()), p),
+                    };
+                    TraceDecision {
+                        pid: p.pid,
+                        name: p.name.clone(),
+                        selected,
+                        reason,
+                    }
+                })
+                .collect();
+            let entry = TraceEntry::new(
+                self.config.min_memory_mb,
+                self.config.keep_communication,
+                processes,
+                decisions,
+            );
+
+            // Tracing is best-effort and must never block a freeze decision
+            let _ = trace.record(&entry);
+        }
+
+        Ok(safe)
     }
 
-    /// Freeze a specific process
-    pub fn freeze_process(&self, pid: u32) -> Result<usize> {
-        self.controller.freeze(pid)
+    /// Safe-to-freeze processes that aren't already in `already_frozen`
+    ///
+    /// Used while a game is running to catch apps started after the first pass.
+    /// `frozen_mb` is what the session already holds, so `max_total_freeze_mb`
+    /// caps the running total rather than each pass.
+    pub fn find_new_safe_to_freeze(
+        &mut self,
+        already_frozen: &HashSet<u32>,
+        frozen_mb: u64,
+    ) -> Result<Vec<ProcessInfo>> {
+        self.select_safe(already_frozen, frozen_mb)
     }
 
-    /// Resume a specific process
-    pub fn resume_process(&self, pid: u32) -> Result<usize> {
-        self.controller.resume(pid)
+    /// Evaluate every running process, marking which ones would be frozen
+    ///
+    /// Sorted like `find_safe_to_freeze` (memory descending, then PID).
+    pub fn evaluate_processes(&mut self) -> Result<Vec<ProcessInfo>> {
+        let safe = self.find_safe_to_freeze()?;
+
+        let mut processes: Vec<ProcessInfo> = self
+            .last_snapshot
+            .iter()
+            .cloned()
+            .map(|mut p| {
+                p.freezable = Some(safe.iter().any(|s| s.pid == p.pid));
+                p
+            })
+            .collect();
+        processes.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+
+        Ok(processes)
     }
 
-    /// Freeze multiple processes, returning PIDs of successfully frozen processes
-    pub fn freeze_multiple(&self, pids: &[u32]) -> Vec<(u32, Result<usize>)> {
-        pids.iter()
-            .map(|&pid| (pid, self.freeze_process(pid)))
-            .collect()
+    /// Trim the working set of every safe-to-freeze process
+    ///
+    /// Processes keep running and are not tracked as frozen. Per-process
+    /// failures (e.g. access denied) are skipped; returns total MB trimmed.
+    pub fn trim_safe_to_freeze(&mut self) -> Result<u64> {
+        let candidates = self.find_safe_to_freeze()?;
+        Ok(self.trim_processes(&candidates))
     }
 
-    /// Resume multiple processes
-    pub fn resume_multiple(&self, pids: &[u32]) -> Vec<(u32, Result<usize>)> {
-        pids.iter()
-            .map(|&pid| (pid, self.resume_process(pid)))
-            .collect()
+    /// Trim each process's working set, returning the total MB released
+    ///
+    /// Processes that can't be trimmed (e.g. access denied) count as 0.
+    fn trim_processes(&self, processes: &[ProcessInfo]) -> u64 {
+        processes
+            .iter()
+            .filter_map(|p| match self.controller.trim_working_set(p.pid) {
+                Ok(trim) => Some(trim.reclaimed_mb()),
+                Err(e) => {
+                    log::debug!("Couldn't trim {} (PID {}): {}", p.name, p.pid, e);
+                    None
+                }
+            })
+            .sum()
     }
 
-    /// Get current configuration
-    pub fn config(&self) -> &FreezeConfig {
-        &self.config
+    /// How much memory must be freed to reach `target_free_mb`
+    ///
+    /// `None` without a target or when system memory can't be queried.
+    pub fn memory_deficit_mb(&self) -> Option<u64> {
+        let target = self.config.target_free_mb?;
+        let (_total, available) = self.enumerator.system_memory_mb()?;
+        Some(target.saturating_sub(available))
     }
 
-    /// Update configuration
-    pub fn set_config(&mut self, config: FreezeConfig) {
-        self.config = config;
+    /// What a freeze pass would freeze: the safe-to-freeze list, cut down to
+    /// the missing free memory when `target_free_mb` is set
+    pub fn session_candidates(&mut self) -> Result<Vec<ProcessInfo>> {
+        let safe = self.find_safe_to_freeze()?;
+        Ok(match self.memory_deficit_mb() {
+            Some(needed_mb) => select_until_target(safe, needed_mb),
+            None => safe,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::categorization::DefaultCategorizer;
+    /// Freeze the session candidates (the daemon's pass when a game starts)
+    pub fn freeze_session(&mut self) -> Result<SessionReport> {
+        let candidates = self.session_candidates()?;
+        Ok(self.freeze_candidates(candidates))
+    }
+
+    /// Freeze `processes` concurrently, retrying transient failures
+    pub fn freeze_candidates(&self, processes: Vec<ProcessInfo>) -> SessionReport {
+        let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+        let results = self.freeze_multiple_retry(&pids, FREEZE_ATTEMPTS, FREEZE_RETRY_BACKOFF);
+
+        let mut report = SessionReport::default();
+        for (process, (_, result)) in processes.into_iter().zip(results) {
+            match result {
+                Ok(_) => {
+                    report.total_memory_mb += process.memory_mb;
+                    report.frozen.push(process);
+                }
+                Err(SmartFreezeError::AlreadyFrozen { .. }) => report.skipped.push(process),
+                Err(e) => report.failed.push((process, e)),
+            }
+        }
 
-    struct MockEnumerator {
-        processes: Vec<ProcessInfo>,
-        foreground_pid: Option<u32>,
+        // Suspending alone doesn't release RAM; trimming pages it out
+        if self.config.trim_after_freeze {
+            report.reclaimed_mb = Some(self.trim_processes(&report.frozen));
+        }
+        report
     }
 
-    impl MockEnumerator {
-        fn new(processes: Vec<ProcessInfo>, foreground_pid: Option<u32>) -> Self {
-            Self {
-                processes,
-                foreground_pid,
+    /// Freeze the biggest safe-to-freeze processes until `target_free_mb` is met
+    ///
+    /// Failed freezes don't count toward the target, so the next candidate is
+    /// tried instead. Returns the processes actually frozen.
+    pub fn freeze_until_target(&mut self) -> Result<Vec<ProcessInfo>> {
+        let Some(needed_mb) = self.memory_deficit_mb() else {
+            return Ok(Vec::new());
+        };
+        // Already sorted biggest first
+        let candidates = self.find_safe_to_freeze()?;
+
+        let mut freed_mb = 0;
+        let mut frozen = Vec::new();
+        for process in candidates {
+            if freed_mb >= needed_mb {
+                break;
+            }
+            if self.freeze_process(process.pid).is_ok() {
+                freed_mb += process.memory_mb;
+                frozen.push(process);
             }
         }
+
+        Ok(frozen)
     }
 
-    impl ProcessEnumerator for MockEnumerator {
-        fn enumerate(&mut self) -> Result<Vec<ProcessInfo>> {
-            Ok(self.processes.clone())
+    /// Why a process would not be frozen (`None` if it is safe to freeze)
+    ///
+    /// `never_freeze` wins over everything; `always_freeze` bypasses the
+    /// thresholds and category rules but never Critical or foreground protection.
+    fn protect_reason(&self, process: &ProcessInfo) -> Option<ProtectReason> {
+        // Freezing ourselves or our console would hang the tool
+        if self.is_self_or_host(process.pid) {
+            return Some(ProtectReason::SelfProcess);
         }
 
-        fn get_foreground_pid(&self) -> Option<u32> {
-            self.foreground_pid
+        // Nothing is known about it, and it couldn't be suspended anyway
+        if process.category == ProcessCategory::Inaccessible {
+            return Some(ProtectReason::Inaccessible);
         }
-    }
 
-    struct MockController {
-        frozen_pids: std::sync::Arc<std::sync::Mutex<Vec<u32>>>,
-    }
+        if name_listed(&self.config.never_freeze, &process.name) {
+            return Some(ProtectReason::NeverFreeze);
+        }
 
-    impl MockController {
-        fn new() -> Self {
-            Self {
-                frozen_pids: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
+        if self.is_pinned(process.pid, &process.name) {
+            return Some(ProtectReason::Pinned);
+        }
+
+        // A fullscreen game the user alt-tabbed out of is neither foreground nor Gaming
+        if self.session_pids.contains(&process.pid) {
+            return Some(ProtectReason::Gaming);
+        }
+
+        // Other users' apps are never ours to freeze, even always_freeze ones
+        if self.config.current_session_only {
+            if let Some(session_id) = self.enumerator.current_session_id() {
+                if process.session_id != session_id {
+                    return Some(ProtectReason::OtherSession);
+                }
             }
         }
 
-        fn get_frozen_pids(&self) -> Vec<u32> {
-            self.frozen_pids.lock().unwrap().clone()
+        // Freezing mid-initialization can deadlock a process, even an always_freeze one.
+        // An unknown age (start time unreadable) can't be judged, so it doesn't hold it back.
+        if process
+            .age_secs
+            .is_some_and(|age| age < self.config.min_process_age_secs)
+        {
+            return Some(ProtectReason::TooYoung);
         }
-    }
 
-    impl ProcessController for MockController {
-        fn freeze(&self, pid: u32) -> Result<usize> {
-            self.frozen_pids.lock().unwrap().push(pid);
-            Ok(1)
+        if name_listed(&self.config.always_freeze, &process.name) {
+            if process.is_foreground {
+                return Some(ProtectReason::Foreground);
+            }
+            if process.category == ProcessCategory::Critical {
+                return Some(ProtectReason::Critical);
+            }
+            return None;
         }
 
-        fn resume(&self, pid: u32) -> Result<usize> {
-            let mut pids = self.frozen_pids.lock().unwrap();
-            pids.retain(|&p| p != pid);
-            Ok(1)
+        if process.memory_mb < self.config.threshold_for(process.category) {
+            return Some(ProtectReason::BelowThreshold);
         }
-    }
 
-    fn create_test_process(
-        pid: u32,
-        name: &str,
-        memory_mb: u64,
-        is_foreground: bool,
-        category: ProcessCategory,
-    ) -> ProcessInfo {
-        ProcessInfo::new(
-            pid,
-            name.to_string(),
-            format!("C:\\Test\\{}", name),
-            memory_mb,
-            is_foreground,
-            category,
-        )
+        if let Some(min_cpu) = self.config.min_cpu_percent {
+            if process.cpu_percent < min_cpu {
+                return Some(ProtectReason::BelowCpuThreshold);
+            }
+        }
+
+        if !process.is_safe_to_freeze(self.config.keep_communication)
+            && !self.is_freezable_comm_child(process)
+        {
+            return Some(if process.is_foreground {
+                ProtectReason::Foreground
+            } else {
+                match process.category {
+                    ProcessCategory::Critical => ProtectReason::Critical,
+                    ProcessCategory::Gaming => ProtectReason::Gaming,
+                    _ => ProtectReason::Communication,
+                }
+            });
+        }
+
+        if self.config.keep_visible_windows && process.has_visible_window {
+            return Some(ProtectReason::VisibleWindow);
+        }
+
+        None
     }
 
-    #[test]
-    fn test_find_safe_to_freeze_filters_by_memory() {
-        let processes = vec![
-            create_test_process(1, "low_mem.exe", 50, false, ProcessCategory::Productivity),
-            create_test_process(2, "high_mem.exe", 200, false, ProcessCategory::Productivity),
-        ];
+    /// A communication helper whose ancestor is also a communication app
+    ///
+    /// Only applies with `freeze_comm_children`; the top-level app has no
+    /// communication ancestor and stays protected.
+    fn is_freezable_comm_child(&self, process: &ProcessInfo) -> bool {
+        if !self.config.freeze_comm_children
+            || process.is_foreground
+            || process.category != ProcessCategory::Communication
+        {
+            return false;
+        }
 
-        let enumerator = MockEnumerator::new(processes, None);
-        let controller = MockController::new();
-        let categorizer = DefaultCategorizer::new();
-        let config = FreezeConfig {
-            min_memory_mb: 100,
-            keep_communication: false,
+        let mut visited = HashSet::from([process.pid]);
+        let mut parent_pid = process.parent_pid;
+        while parent_pid != 0 && visited.insert(parent_pid) {
+            let Some(parent) = self.last_snapshot.iter().find(|p| p.pid == parent_pid) else {
+                return false;
+            };
+            if parent.category == ProcessCategory::Communication {
+                return true;
+            }
+            parent_pid = parent.parent_pid;
+        }
+        false
+    }
+
+    /// Whether `pid` is `self_pid` or its parent in the latest snapshot
+    pub fn is_self_or_host(&self, pid: u32) -> bool {
+        let Some(self_pid) = self.config.self_pid else {
+            return false;
         };
 
-        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
-        let safe = engine.find_safe_to_freeze().unwrap();
+        pid == self_pid
+            || (pid != 0
+                && self
+                    .last_snapshot
+                    .iter()
+                    .any(|p| p.pid == self_pid && p.parent_pid == pid))
+    }
 
-        assert_eq!(safe.len(), 1);
-        assert_eq!(safe[0].pid, 2);
+    /// Human-readable exclusion reason, with the values that triggered it
+    fn exclusion_reason(&self, process: &ProcessInfo) -> Option<String> {
+        self.protect_reason(process)
+            .map(|reason| self.describe_reason(reason, process))
     }
 
-    #[test]
-    fn test_find_safe_to_freeze_excludes_foreground() {
-        let processes = vec![
-            create_test_process(
-                1,
-                "foreground.exe",
-                200,
-                true,
-                ProcessCategory::Productivity,
+    fn describe_reason(&self, reason: ProtectReason, process: &ProcessInfo) -> String {
+        match reason {
+            ProtectReason::NeverFreeze => "listed in never_freeze".to_string(),
+            ProtectReason::Pinned => "pinned".to_string(),
+            ProtectReason::OtherSession => format!("other session ({})", process.session_id),
+            ProtectReason::TooYoung => format!(
+                "too young ({}s < {}s)",
+                process.age_secs.unwrap_or_default(),
+                self.config.min_process_age_secs
             ),
-            create_test_process(
+            ProtectReason::BelowThreshold => format!(
+                "below threshold ({} MB < {} MB)",
+                process.memory_mb,
+                self.config.threshold_for(process.category)
+            ),
+            ProtectReason::BelowCpuThreshold => format!(
+                "below CPU threshold ({:.1}% < {:.1}%)",
+                process.cpu_percent,
+                self.config.min_cpu_percent.unwrap_or_default()
+            ),
+            ProtectReason::Foreground => "foreground process".to_string(),
+            ProtectReason::Critical | ProtectReason::Gaming | ProtectReason::Communication => {
+                format!("protected category ({})", process.category.as_str())
+            }
+            ProtectReason::OverProcessCap => format!(
+                "over process cap ({})",
+                self.config.max_processes.unwrap_or_default()
+            ),
+            ProtectReason::OverMemoryCap => format!(
+                "over total memory cap ({} MB)",
+                self.config.max_total_freeze_mb.unwrap_or_default()
+            ),
+            ProtectReason::SelfProcess => "SmartFreeze itself or its console".to_string(),
+            ProtectReason::VisibleWindow => "has a visible window".to_string(),
+            ProtectReason::Inaccessible => "couldn't be opened".to_string(),
+        }
+    }
+
+    /// Which cap left out eligible processes, given how many were selected
+    fn cap_reason(&self, selected: usize) -> ProtectReason {
+        if self.config.max_processes == Some(selected) {
+            ProtectReason::OverProcessCap
+        } else {
+            ProtectReason::OverMemoryCap
+        }
+    }
+
+    /// The largest processes that are safe to freeze or only kept by a pin,
+    /// for choosing what to pin
+    ///
+    /// Built from the last snapshot rather than a fresh enumeration (not traced).
+    pub fn pin_candidates(&self, limit: usize) -> Vec<ProcessInfo> {
+        let mut candidates: Vec<ProcessInfo> = self
+            .last_snapshot
+            .iter()
+            .filter(|p| matches!(self.protect_reason(p), None | Some(ProtectReason::Pinned)))
+            .cloned()
+            .collect();
+        candidates.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// Dry-run preview: what would be frozen and why everything else is kept
+    pub fn plan(&mut self) -> Result<FreezePlan> {
+        let would_freeze = self.find_safe_to_freeze()?;
+
+        let mut protected: Vec<(ProcessInfo, ProtectReason)> = self
+            .last_snapshot
+            .iter()
+            .filter(|p| !would_freeze.iter().any(|w| w.pid == p.pid))
+            .map(|p| {
+                let reason = self
+                    .protect_reason(p)
+                    .unwrap_or_else(|| self.cap_reason(would_freeze.len()));
+                (p.clone(), reason)
+            })
+            .collect();
+        protected.sort_by(|(a, _), (b, _)| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+
+        Ok(FreezePlan {
+            total_memory_to_free_mb: would_freeze.iter().map(|p| p.memory_mb).sum(),
+            would_freeze,
+            protected,
+        })
+    }
+
+    /// Find all gaming processes
+    pub fn find_gaming_processes(&mut self) -> Result<Vec<ProcessInfo>> {
+        let processes = self.refresh()?;
+
+        Ok(processes
+            .into_iter()
+            .filter(|p| p.category == ProcessCategory::Gaming)
+            .collect())
+    }
+
+    /// Find processes whose name contains `pattern` (case-insensitive), by PID
+    pub fn find_by_name(&mut self, pattern: &str) -> Result<Vec<ProcessInfo>> {
+        let pattern = pattern.to_lowercase();
+        let mut matches: Vec<ProcessInfo> = self
+            .refresh()?
+            .into_iter()
+            .filter(|p| p.name.to_lowercase().contains(&pattern))
+            .collect();
+        matches.sort_by_key(|p| p.pid);
+        Ok(matches)
+    }
+
+    /// Explain the processes whose PID is `target`, or whose name contains it
+    pub fn explain(&mut self, target: &str) -> Result<Vec<Explanation>> {
+        let processes = self.refresh()?;
+        let pattern = target.to_lowercase();
+        let mut found: Vec<&ProcessInfo> = match target.parse::<u32>() {
+            Ok(pid) => processes.iter().filter(|p| p.pid == pid).collect(),
+            Err(_) => processes
+                .iter()
+                .filter(|p| p.name.to_lowercase().contains(&pattern))
+                .collect(),
+        };
+        found.sort_by_key(|p| p.pid);
+
+        Ok(found
+            .into_iter()
+            .map(|process| Explanation {
+                matches: self
+                    .categorizer
+                    .categorize_all_matches(&process.name, &process.full_path),
+                publisher_category: process
+                    .publisher
+                    .as_deref()
+                    .and_then(|publisher| self.categorizer.categorize_publisher(publisher)),
+                parent_name: processes
+                    .iter()
+                    .find(|p| p.pid == process.parent_pid && p.pid != process.pid)
+                    .map(|p| p.name.clone()),
+                threshold_mb: self.config.threshold_for(process.category),
+                protect_reason: self.protect_reason(process),
+                process: process.clone(),
+            })
+            .collect())
+    }
+
+    /// Whether a gaming session is active: a Gaming process is running, or
+    /// (with `trigger_on_fullscreen`) the foreground app is fullscreen
+    pub fn is_gaming_session(&mut self) -> Result<bool> {
+        if self.config.trigger_on_fullscreen && self.enumerator.is_foreground_fullscreen() {
+            return Ok(true);
+        }
+
+        Ok(!self.find_gaming_processes()?.is_empty())
+    }
+
+    /// PIDs making up the gaming session: running Gaming processes, plus the
+    /// fullscreen foreground app with `trigger_on_fullscreen`
+    ///
+    /// Games count for as long as they run, in focus or not, so alt-tabbing
+    /// to a browser keeps the session going. A fullscreen app that isn't a
+    /// game only counts while it is still the fullscreen foreground, so a
+    /// browser once put in F11 doesn't keep everything frozen.
+    pub fn gaming_session_pids(&mut self) -> Result<HashSet<u32>> {
+        let mut pids: HashSet<u32> = self
+            .find_gaming_processes()?
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        if self.config.trigger_on_fullscreen && self.enumerator.is_foreground_fullscreen() {
+            pids.extend(self.enumerator.get_foreground_pid());
+        }
+        Ok(pids)
+    }
+
+    /// Whether the user has been idle for at least `idle_trigger_secs`
+    pub fn is_user_idle(&self) -> bool {
+        match (
+            self.config.idle_trigger_secs,
+            self.enumerator.idle_seconds(),
+        ) {
+            (Some(trigger), Some(idle)) => idle >= trigger,
+            _ => false,
+        }
+    }
+
+    /// Check that the configured freeze mode is supported by the controller
+    pub fn validate_mode(&self) -> Result<()> {
+        match self.config.mode {
+            Some(mode) if !self.controller.supports_mode(mode) => {
+                Err(SmartFreezeError::UnsupportedMode(mode))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Freeze a specific process using the configured mode
+    ///
+    /// Throttling affects no threads directly and reports a count of 0.
+    pub fn freeze_process(&self, pid: u32) -> Result<usize> {
+        self.validate_mode()?;
+        let result = match self.config.mode {
+            Some(FreezeMode::Throttle) => self.throttle_process(pid).map(|()| 0),
+            Some(FreezeMode::Soft) => self.soft_freeze_process(pid),
+            _ => self.controller.freeze(pid),
+        };
+        self.record_audit(pid, AuditAction::Freeze, &result);
+        result
+    }
+
+    /// Freeze a process and report thread count, timing and prior memory use
+    pub fn freeze_process_detailed(&self, pid: u32) -> Result<FreezeOutcome> {
+        let memory_mb_before = self
+            .last_snapshot
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.memory_mb);
+
+        let started = Instant::now();
+        let threads_affected = self.freeze_process(pid)?;
+
+        Ok(FreezeOutcome {
+            pid,
+            threads_affected,
+            elapsed: started.elapsed(),
+            memory_mb_before,
+        })
+    }
+
+    /// Freeze a PID named by the user, guarding against fat-fingered targets
+    ///
+    /// Re-enumerates first and refuses critical and foreground processes,
+    /// SmartFreeze and its console, and PIDs the enumeration can't see (System,
+    /// protected processes). `force` skips these checks.
+    pub fn freeze_guarded(&mut self, pid: u32, force: bool) -> Result<usize> {
+        if !force {
+            self.refresh()?;
+            self.check_manual_target(pid)?;
+        }
+        self.freeze_process(pid)
+    }
+
+    fn check_manual_target(&self, pid: u32) -> Result<()> {
+        if self.is_self_or_host(pid) {
+            return Err(SmartFreezeError::ProtectedProcess {
+                pid,
+                reason: "SmartFreeze itself or its console".to_string(),
+            });
+        }
+        if !self.last_snapshot.iter().any(|p| p.pid == pid) {
+            return Err(SmartFreezeError::ProtectedProcess {
+                pid,
+                reason: "not visible to enumeration (system or protected process)".to_string(),
+            });
+        }
+        self.check_not_protected(pid)
+    }
+
+    /// Freeze with retries for transient failures
+    ///
+    /// A thread exiting between snapshot and suspend surfaces as `FreezeFailed`,
+    /// which is retried after `backoff`, doubling each time. Other errors
+    /// (notably `ProcessNotFound`) are returned immediately.
+    pub fn freeze_process_retry(
+        &self,
+        pid: u32,
+        attempts: u32,
+        backoff: Duration,
+    ) -> Result<usize> {
+        let mut delay = backoff;
+        let mut attempt = 1;
+
+        loop {
+            match self.freeze_process(pid) {
+                Err(SmartFreezeError::FreezeFailed { .. }) if attempt < attempts => {
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Resume a specific process using the configured mode
+    pub fn resume_process(&self, pid: u32) -> Result<usize> {
+        let result = match self.config.mode {
+            Some(FreezeMode::Throttle) => self.unthrottle_process(pid).map(|()| 0),
+            Some(FreezeMode::Soft) => self.soft_resume_process(pid),
+            _ => self.controller.resume(pid),
+        };
+        self.record_audit(pid, AuditAction::Resume, &result);
+        result
+    }
+
+    /// Resume a process, starting it again from `exe_path` if it was killed while frozen
+    ///
+    /// Relaunching needs `relaunch_killed` and a known path; otherwise the
+    /// `ProcessNotFound` error is returned as is.
+    pub fn resume_or_relaunch(&self, pid: u32, exe_path: &str) -> Result<ResumeOutcome> {
+        match self.resume_process(pid) {
+            Ok(threads) => Ok(ResumeOutcome::Resumed(threads)),
+            Err(SmartFreezeError::ProcessNotFound(_))
+                if self.config.relaunch_killed && !exe_path.is_empty() =>
+            {
+                let new_pid = self.controller.restart_process(exe_path)?;
+                self.record_relaunch(new_pid, exe_path);
+                Ok(ResumeOutcome::Relaunched(new_pid))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Append a relaunch to the audit log, named after the executable
+    fn record_relaunch(&self, pid: u32, exe_path: &str) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let name = exe_path.rsplit(['\\', '/']).next().unwrap_or(exe_path);
+        if let Err(e) = audit.record(&AuditEntry::new(pid, name, AuditAction::Relaunch, 0)) {
+            log::warn!("Failed to write audit log: {}", e);
+        }
+    }
+
+    /// Put a process into efficiency mode instead of suspending it
+    pub fn throttle_process(&self, pid: u32) -> Result<()> {
+        if !self.controller.supports_mode(FreezeMode::Throttle) {
+            return Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle));
+        }
+        self.check_not_protected(pid)?;
+        self.controller.set_efficiency_mode(pid, true)
+    }
+
+    /// Take a process out of efficiency mode
+    pub fn unthrottle_process(&self, pid: u32) -> Result<()> {
+        if !self.controller.supports_mode(FreezeMode::Throttle) {
+            return Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle));
+        }
+        self.controller.set_efficiency_mode(pid, false)
+    }
+
+    /// Lower priority and restrict affinity, then suspend if `escalate_soft_freeze`
+    ///
+    /// Reports the suspended thread count, or 0 without escalation. A failed
+    /// escalation restores the priority so nothing is left half-frozen.
+    fn soft_freeze_process(&self, pid: u32) -> Result<usize> {
+        self.check_not_protected(pid)?;
+        self.controller.set_priority_and_affinity(
+            pid,
+            SOFT_FREEZE_PRIORITY_CLASS,
+            SOFT_FREEZE_AFFINITY_MASK,
+        )?;
+
+        if !self.config.escalate_soft_freeze {
+            return Ok(0);
+        }
+        self.controller.freeze(pid).inspect_err(|_| {
+            let _ = self.controller.restore_priority_and_affinity(pid);
+        })
+    }
+
+    /// Undo a soft freeze: resume threads (if escalated), then restore priority
+    fn soft_resume_process(&self, pid: u32) -> Result<usize> {
+        let resumed = if self.config.escalate_soft_freeze {
+            self.controller.resume(pid)
+        } else {
+            Ok(0)
+        };
+        let restored = self.controller.restore_priority_and_affinity(pid);
+        let threads = resumed?;
+        restored.map(|()| threads)
+    }
+
+    /// Refuse to act on processes the last enumeration saw as critical or foreground
+    fn check_not_protected(&self, pid: u32) -> Result<()> {
+        let Some(process) = self.last_snapshot.iter().find(|p| p.pid == pid) else {
+            return Ok(());
+        };
+
+        if process.category == ProcessCategory::Critical {
+            return Err(SmartFreezeError::ProtectedProcess {
+                pid,
+                reason: "critical system process".to_string(),
+            });
+        }
+
+        if process.is_foreground {
+            return Err(SmartFreezeError::ProtectedProcess {
+                pid,
+                reason: "foreground process".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Freeze multiple processes concurrently, returning results in `pids` order
+    pub fn freeze_multiple(&self, pids: &[u32]) -> Vec<(u32, Result<usize>)> {
+        for_each_parallel(pids, |pid| self.freeze_process(pid))
+    }
+
+    /// `freeze_multiple` for PIDs picked by the user, refusing the same targets
+    /// as `freeze_guarded` (results still in `pids` order)
+    pub fn freeze_multiple_guarded(&mut self, pids: &[u32]) -> Result<Vec<(u32, Result<usize>)>> {
+        self.refresh()?;
+
+        let mut refused = HashMap::new();
+        let allowed: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|&pid| match self.check_manual_target(pid) {
+                Ok(()) => true,
+                Err(e) => {
+                    refused.insert(pid, e);
+                    false
+                }
+            })
+            .collect();
+
+        let mut frozen = self.freeze_multiple(&allowed).into_iter();
+        Ok(pids
+            .iter()
+            .map(|&pid| match refused.remove(&pid) {
+                Some(e) => (pid, Err(e)),
+                None => frozen.next().expect("one result per allowed PID"),
+            })
+            .collect())
+    }
+
+    /// `freeze_multiple` with `freeze_process_retry` for each PID
+    pub fn freeze_multiple_retry(
+        &self,
+        pids: &[u32],
+        attempts: u32,
+        backoff: Duration,
+    ) -> Vec<(u32, Result<usize>)> {
+        for_each_parallel(pids, |pid| {
+            self.freeze_process_retry(pid, attempts, backoff)
+        })
+    }
+
+    /// Descendants of a process according to the most recent enumeration
+    ///
+    /// Critical and foreground processes are skipped along with their
+    /// subtrees. Cycles in the parent map are ignored.
+    pub fn find_descendants(&self, root_pid: u32) -> Vec<u32> {
+        let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+        for process in &self.last_snapshot {
+            if process.pid != 0 && process.pid != process.parent_pid {
+                children
+                    .entry(process.parent_pid)
+                    .or_default()
+                    .push(process);
+            }
+        }
+
+        let mut visited = HashSet::from([root_pid]);
+        let mut queue = VecDeque::from([root_pid]);
+        let mut descendants = Vec::new();
+
+        while let Some(pid) = queue.pop_front() {
+            for child in children.get(&pid).into_iter().flatten() {
+                if !visited.insert(child.pid) {
+                    continue;
+                }
+                if child.is_foreground || child.category == ProcessCategory::Critical {
+                    continue;
+                }
+                descendants.push(child.pid);
+                queue.push_back(child.pid);
+            }
+        }
+
+        descendants
+    }
+
+    /// Freeze a process and all of its descendants
+    pub fn freeze_process_tree(&self, root_pid: u32) -> Vec<(u32, Result<usize>)> {
+        let mut pids = vec![root_pid];
+        pids.extend(self.find_descendants(root_pid));
+        self.freeze_multiple(&pids)
+    }
+
+    /// Resume multiple processes
+    pub fn resume_multiple(&self, pids: &[u32]) -> Vec<(u32, Result<usize>)> {
+        pids.iter()
+            .map(|&pid| (pid, self.resume_process(pid)))
+            .collect()
+    }
+
+    /// Resume the frozen PIDs that categorize as `category`
+    ///
+    /// Categories aren't stored per frozen PID, so each one is re-categorized
+    /// from the name and path it was frozen under (see
+    /// `PersistentState::name_lookup`). PIDs missing from `name_lookup` stay frozen.
+    pub fn resume_by_category(
+        &self,
+        frozen_pids: &[u32],
+        category: ProcessCategory,
+        name_lookup: &HashMap<u32, (String, String)>,
+    ) -> Vec<(u32, Result<usize>)> {
+        let matching: Vec<u32> = frozen_pids
+            .iter()
+            .copied()
+            .filter(|pid| {
+                name_lookup.get(pid).is_some_and(|(name, path)| {
+                    self.categorizer.categorize(*pid, name, path) == category
+                })
+            })
+            .collect();
+        self.resume_multiple(&matching)
+    }
+
+    /// PIDs whose resume timed out but has since succeeded
+    pub fn take_late_resumes(&self) -> Vec<u32> {
+        self.controller.take_late_resumes()
+    }
+
+    /// What the controller changed to freeze `pid`, for the state file
+    pub fn freeze_record(&self, pid: u32) -> FreezeRecord {
+        self.controller.freeze_record(pid)
+    }
+
+    /// Take over a freeze saved by another run, so resuming undoes exactly that
+    pub fn adopt_freeze(&self, pid: u32, record: &FreezeRecord) {
+        self.controller.adopt_freeze(pid, record);
+    }
+
+    /// Executable path of a running process (`None` if unknown or gone)
+    pub fn exe_path(&self, pid: u32) -> Option<String> {
+        self.controller.exe_path(pid)
+    }
+
+    /// Get current configuration
+    pub fn config(&self) -> &FreezeConfig {
+        &self.config
+    }
+
+    /// Update configuration
+    pub fn set_config(&mut self, config: FreezeConfig) {
+        self.config = config;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categorization::DefaultCategorizer;
+    use crate::cli::Args;
+    use crate::config::Config;
+    use crate::testing::MockProcessEnumerator as MockEnumerator;
+    use clap::Parser;
+
+    struct MockController {
+        frozen_pids: std::sync::Arc<std::sync::Mutex<Vec<u32>>>,
+        throttled_pids: std::sync::Arc<std::sync::Mutex<Vec<u32>>>,
+        /// PID -> (priority class, affinity mask) currently applied by a soft freeze
+        soft_frozen: std::sync::Mutex<HashMap<u32, (u32, usize)>>,
+        supports_throttle: bool,
+        /// Thread count reported by `freeze`
+        threads: usize,
+        /// Simulated time spent suspending
+        freeze_delay: Duration,
+    }
+
+    impl MockController {
+        fn new() -> Self {
+            Self {
+                frozen_pids: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
+                throttled_pids: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
+                soft_frozen: std::sync::Mutex::new(HashMap::new()),
+                supports_throttle: false,
+                threads: 1,
+                freeze_delay: Duration::ZERO,
+            }
+        }
+
+        fn with_throttle() -> Self {
+            Self {
+                supports_throttle: true,
+                ..Self::new()
+            }
+        }
+
+        fn get_frozen_pids(&self) -> Vec<u32> {
+            self.frozen_pids.lock().unwrap().clone()
+        }
+    }
+
+    impl FreezeEngine<MockEnumerator, MockController, DefaultCategorizer> {
+        /// Engine over no processes, configured the way the real one would be
+        fn from_config(config: &Config) -> Self {
+            Self::new(
+                MockEnumerator::new(Vec::new(), None),
+                MockController::new(),
+                DefaultCategorizer::new(),
+                config.freeze.clone(),
+            )
+        }
+    }
+
+    impl ProcessController for MockController {
+        fn freeze(&self, pid: u32) -> Result<usize> {
+            std::thread::sleep(self.freeze_delay);
+            self.frozen_pids.lock().unwrap().push(pid);
+            Ok(self.threads)
+        }
+
+        fn resume(&self, pid: u32) -> Result<usize> {
+            let mut pids = self.frozen_pids.lock().unwrap();
+            let before = pids.len();
+            pids.retain(|&p| p != pid);
+            Ok(before - pids.len())
+        }
+
+        fn capabilities(&self) -> FreezeCapabilities {
+            let caps = FreezeCapabilities::from_modes(&[FreezeMode::Suspend, FreezeMode::Soft]);
+            if self.supports_throttle {
+                caps.with(FreezeMode::Throttle)
+            } else {
+                caps
+            }
+        }
+
+        fn trim_working_set(&self, pid: u32) -> Result<WorkingSetTrim> {
+            if pid == 99 {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+            Ok(WorkingSetTrim {
+                before_mb: u64::from(pid) * 10,
+                after_mb: 0,
+            })
+        }
+
+        fn set_priority_and_affinity(
+            &self,
+            pid: u32,
+            priority_class: u32,
+            affinity_mask: usize,
+        ) -> Result<()> {
+            self.soft_frozen
+                .lock()
+                .unwrap()
+                .insert(pid, (priority_class, affinity_mask));
+            Ok(())
+        }
+
+        fn restore_priority_and_affinity(&self, pid: u32) -> Result<()> {
+            self.soft_frozen.lock().unwrap().remove(&pid);
+            Ok(())
+        }
+
+        fn set_efficiency_mode(&self, pid: u32, enabled: bool) -> Result<()> {
+            let mut pids = self.throttled_pids.lock().unwrap();
+            if enabled {
+                pids.push(pid);
+            } else {
+                pids.retain(|&p| p != pid);
+            }
+            Ok(())
+        }
+    }
+
+    fn create_test_process(
+        pid: u32,
+        name: &str,
+        memory_mb: u64,
+        is_foreground: bool,
+        category: ProcessCategory,
+    ) -> ProcessInfo {
+        let mut process = ProcessInfo::new(
+            pid,
+            name.to_string(),
+            format!("C:\\Test\\{}", name),
+            memory_mb,
+            is_foreground,
+            category,
+        );
+        // Long-running unless a test says otherwise
+        process.age_secs = Some(3600);
+        process
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_filters_by_memory() {
+        let processes = vec![
+            create_test_process(1, "low_mem.exe", 50, false, ProcessCategory::Productivity),
+            create_test_process(2, "high_mem.exe", 200, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            min_memory_mb: 100,
+            trigger_on_fullscreen: false,
+            keep_communication: false,
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        assert_eq!(safe.len(), 1);
+        assert_eq!(safe[0].pid, 2);
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_excludes_foreground() {
+        let processes = vec![
+            create_test_process(
+                1,
+                "foreground.exe",
+                200,
+                true,
+                ProcessCategory::Productivity,
+            ),
+            create_test_process(
                 2,
                 "background.exe",
                 200,
                 false,
-                ProcessCategory::Productivity,
+                ProcessCategory::Productivity,
+            ),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, Some(1));
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        assert_eq!(safe.len(), 1);
+        assert_eq!(safe[0].pid, 2);
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_excludes_critical() {
+        let processes = vec![
+            create_test_process(1, "explorer.exe", 200, false, ProcessCategory::Critical),
+            create_test_process(2, "chrome.exe", 200, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        assert_eq!(safe.len(), 1);
+        assert_eq!(safe[0].pid, 2);
+    }
+
+    #[test]
+    fn test_keep_communication_flag() {
+        let processes = vec![
+            create_test_process(1, "discord.exe", 200, false, ProcessCategory::Communication),
+            create_test_process(2, "chrome.exe", 200, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes.clone(), None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            min_memory_mb: 100,
+            trigger_on_fullscreen: false,
+            keep_communication: false,
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.len(), 2); // Both freezable
+
+        // Now with keep_communication enabled
+        let enumerator2 = MockEnumerator::new(processes, None);
+        let controller2 = MockController::new();
+        let categorizer2 = DefaultCategorizer::new();
+        let config2 = FreezeConfig {
+            min_memory_mb: 100,
+            trigger_on_fullscreen: false,
+            keep_communication: true,
+            ..Default::default()
+        };
+
+        let mut engine2 = FreezeEngine::new(enumerator2, controller2, categorizer2, config2);
+        let safe2 = engine2.find_safe_to_freeze().unwrap();
+        assert_eq!(safe2.len(), 1); // Only chrome, discord protected
+        assert_eq!(safe2[0].pid, 2);
+    }
+
+    #[test]
+    fn test_freeze_comm_children_keeps_top_level_app() {
+        let mut processes = vec![
+            create_test_process(1, "explorer.exe", 200, false, ProcessCategory::Critical),
+            create_test_process(
+                10,
+                "discord.exe",
+                300,
+                false,
+                ProcessCategory::Communication,
+            ),
+            create_test_process(
+                11,
+                "discord.exe",
+                250,
+                false,
+                ProcessCategory::Communication,
+            ),
+            create_test_process(
+                12,
+                "discord.exe",
+                200,
+                false,
+                ProcessCategory::Communication,
+            ),
+            // Grandchild through a helper of another category
+            create_test_process(13, "crashpad.exe", 150, false, ProcessCategory::Unknown),
+            create_test_process(
+                14,
+                "discord.exe",
+                120,
+                false,
+                ProcessCategory::Communication,
+            ),
+            create_test_process(20, "teams.exe", 400, false, ProcessCategory::Communication),
+        ];
+        for (pid, parent) in [(10, 1), (11, 10), (12, 10), (13, 11), (14, 13), (20, 1)] {
+            processes
+                .iter_mut()
+                .find(|p| p.pid == pid)
+                .unwrap()
+                .parent_pid = parent;
+        }
+
+        let config = FreezeConfig {
+            keep_communication: true,
+            freeze_comm_children: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes.clone(), None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+        let mut pids: Vec<u32> = engine
+            .find_safe_to_freeze()
+            .unwrap()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        pids.sort_unstable();
+        assert_eq!(pids, vec![11, 12, 13, 14]);
+
+        // Off by default: every communication process is kept
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                keep_communication: true,
+                ..Default::default()
+            },
+        );
+        let pids: Vec<u32> = engine
+            .find_safe_to_freeze()
+            .unwrap()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![13]);
+    }
+
+    #[test]
+    fn test_inaccessible_processes_are_never_selected() {
+        let processes = vec![
+            create_test_process(4, "<PID 4>", 0, false, ProcessCategory::Inaccessible),
+            create_test_process(8, "<PID 8>", 500, false, ProcessCategory::Inaccessible),
+            create_test_process(12, "chrome.exe", 200, false, ProcessCategory::Productivity),
+        ];
+        let config = FreezeConfig {
+            min_memory_mb: 0,
+            always_freeze: vec!["<PID 8>".to_string()],
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        let plan = engine.plan().unwrap();
+        let selected: Vec<u32> = plan.would_freeze.iter().map(|p| p.pid).collect();
+        assert_eq!(selected, vec![12]);
+        assert!(plan
+            .protected
+            .iter()
+            .all(|(_, reason)| *reason == ProtectReason::Inaccessible));
+    }
+
+    #[test]
+    fn test_keep_visible_windows_flag() {
+        let mut editor =
+            create_test_process(1, "notepad.exe", 200, false, ProcessCategory::Productivity);
+        editor.has_visible_window = true;
+        let processes = vec![
+            editor,
+            create_test_process(2, "updater.exe", 200, false, ProcessCategory::Productivity),
+        ];
+
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes.clone(), None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        assert_eq!(engine.find_safe_to_freeze().unwrap().len(), 2);
+
+        let config = FreezeConfig {
+            keep_visible_windows: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+        let plan = engine.plan().unwrap();
+        assert_eq!(plan.would_freeze.len(), 1);
+        assert_eq!(plan.would_freeze[0].pid, 2);
+        assert_eq!(plan.protected[0].1, ProtectReason::VisibleWindow);
+    }
+
+    #[test]
+    fn test_self_and_host_console_are_never_frozen() {
+        let mut own =
+            create_test_process(40, "smart-freeze.exe", 900, false, ProcessCategory::Unknown);
+        own.parent_pid = 30;
+        let processes = vec![
+            create_test_process(
+                30,
+                "WindowsTerminal.exe",
+                500,
+                false,
+                ProcessCategory::Productivity,
+            ),
+            own,
+            create_test_process(50, "chrome.exe", 800, false, ProcessCategory::Productivity),
+        ];
+
+        let config = FreezeConfig {
+            self_pid: Some(40),
+            always_freeze: vec!["smart-freeze.exe".to_string()],
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![50]);
+        assert!(engine.is_self_or_host(40));
+        assert!(engine.is_self_or_host(30));
+        assert!(!engine.is_self_or_host(50));
+    }
+
+    #[test]
+    fn test_category_thresholds_override_global() {
+        let processes = vec![
+            create_test_process(
+                1,
+                "updater.exe",
+                120,
+                false,
+                ProcessCategory::BackgroundService,
+            ),
+            create_test_process(2, "editor.exe", 120, false, ProcessCategory::Productivity),
+            create_test_process(3, "unknown.exe", 120, false, ProcessCategory::Unknown),
+        ];
+
+        let config = FreezeConfig {
+            min_memory_mb: 150,
+            category_thresholds: HashMap::from([
+                (ProcessCategory::BackgroundService, 50),
+                (ProcessCategory::Productivity, 500),
+            ]),
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes.clone(), None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        // Below the global 150 MB but above the background threshold
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1]);
+
+        // Above the global 100 MB but below the productivity threshold
+        let config = FreezeConfig {
+            min_memory_mb: 100,
+            category_thresholds: HashMap::from([(ProcessCategory::Productivity, 500)]),
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(
+            engine.exclusion_reason(&engine.last_snapshot[1]).unwrap(),
+            "below threshold (120 MB < 500 MB)"
+        );
+    }
+
+    #[test]
+    fn test_never_and_always_freeze_lists() {
+        let processes = vec![
+            create_test_process(1, "vpn.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(2, "Tiny.exe", 10, false, ProcessCategory::Productivity),
+            create_test_process(3, "discord.exe", 300, false, ProcessCategory::Communication),
+            create_test_process(4, "both.exe", 300, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            keep_communication: true,
+            never_freeze: vec!["VPN.EXE".to_string(), "both.exe".to_string()],
+            always_freeze: vec![
+                "tiny.exe".to_string(),
+                "discord.exe".to_string(),
+                "both.exe".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        let pids: Vec<u32> = safe.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_session_outlives_the_foreground() {
+        let processes = vec![
+            create_test_process(1, "indie.exe", 3000, false, ProcessCategory::Gaming),
+            create_test_process(2, "chrome.exe", 900, true, ProcessCategory::Productivity),
+            create_test_process(3, "Spotify.exe", 400, false, ProcessCategory::Productivity),
+        ];
+        let mut enumerator = MockEnumerator::new(processes.clone(), Some(2));
+        enumerator.sequence = vec![
+            processes.clone(),
+            processes.clone(),
+            processes[1..].to_vec(),
+        ];
+
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        // Alt-tabbed to the browser: the game still runs, so still the session
+        let session = engine.gaming_session_pids().unwrap();
+        assert_eq!(session, HashSet::from([1]));
+
+        // Neither the game nor the foreground browser is frozen
+        engine.set_session_pids(session);
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![3]);
+
+        // Exited: the session ends
+        assert!(engine.gaming_session_pids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fullscreen_app_counts_only_while_fullscreen() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 900, true, ProcessCategory::Productivity),
+            create_test_process(2, "Spotify.exe", 400, false, ProcessCategory::Productivity),
+        ];
+        let mut enumerator = MockEnumerator::new(processes, Some(1));
+        enumerator.fullscreen = true;
+
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                trigger_on_fullscreen: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(engine.gaming_session_pids().unwrap(), HashSet::from([1]));
+
+        // Out of F11 (or alt-tabbed away): a browser doesn't hold the session open
+        engine.enumerator.fullscreen = false;
+        assert!(engine.gaming_session_pids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pinned_processes_are_not_frozen() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 900, false, ProcessCategory::Productivity),
+            create_test_process(2, "Spotify.exe", 400, false, ProcessCategory::Productivity),
+            create_test_process(3, "code.exe", 300, false, ProcessCategory::Productivity),
+            create_test_process(4, "tiny.exe", 10, false, ProcessCategory::Productivity),
+        ];
+
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                always_freeze: vec!["code.exe".to_string()],
+                ..Default::default()
+            },
+        );
+        // By PID, and by name so a relaunch stays pinned; pins beat always_freeze
+        engine.set_pinned(HashSet::from([1]), vec!["CODE.EXE".to_string()]);
+
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(engine.freeze_session().unwrap().frozen_count(), 1);
+
+        let plan = engine.plan().unwrap();
+        assert!(plan
+            .protected
+            .iter()
+            .any(|(p, reason)| p.pid == 1 && *reason == ProtectReason::Pinned));
+
+        // Pinned processes stay listed so they can be unpinned; others still don't
+        let candidates = engine.pin_candidates(10);
+        assert_eq!(
+            candidates.iter().map(|p| p.pid).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(engine.pin_candidates(1)[0].pid, 1);
+
+        engine.set_pinned(HashSet::new(), Vec::new());
+        assert_eq!(engine.find_safe_to_freeze().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_always_freeze_cannot_override_protection() {
+        let processes = vec![
+            create_test_process(1, "explorer.exe", 200, false, ProcessCategory::Critical),
+            create_test_process(2, "editor.exe", 200, true, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, Some(2));
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            always_freeze: vec!["explorer.exe".to_string(), "editor.exe".to_string()],
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        assert!(safe.is_empty());
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_sorted_by_memory() {
+        let processes = vec![
+            create_test_process(4, "small.exe", 150, false, ProcessCategory::Productivity),
+            create_test_process(3, "big.exe", 900, false, ProcessCategory::Productivity),
+            create_test_process(2, "tie_b.exe", 400, false, ProcessCategory::Productivity),
+            create_test_process(1, "tie_a.exe", 400, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        let pids: Vec<u32> = safe.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![3, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_evaluate_processes_marks_freezable() {
+        let processes = vec![
+            create_test_process(1, "explorer.exe", 300, false, ProcessCategory::Critical),
+            create_test_process(2, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(3, "small.exe", 20, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let evaluated = engine.evaluate_processes().unwrap();
+
+        let marks: Vec<(u32, Option<bool>)> =
+            evaluated.iter().map(|p| (p.pid, p.freezable)).collect();
+        assert_eq!(
+            marks,
+            vec![(2, Some(true)), (1, Some(false)), (3, Some(false))]
+        );
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_max_processes() {
+        let processes = vec![
+            create_test_process(1, "a.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "b.exe", 600, false, ProcessCategory::Productivity),
+            create_test_process(3, "c.exe", 400, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            max_processes: Some(2),
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        let pids: Vec<u32> = safe.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_max_total_freeze_mb_stops_at_cap() {
+        let processes = vec![
+            create_test_process(1, "a.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "b.exe", 600, false, ProcessCategory::Productivity),
+            create_test_process(3, "c.exe", 400, false, ProcessCategory::Productivity),
+            create_test_process(4, "d.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let engine_with = |max_processes, max_total_freeze_mb| {
+            FreezeEngine::new(
+                MockEnumerator::new(processes.clone(), None),
+                MockController::new(),
+                DefaultCategorizer::new(),
+                FreezeConfig {
+                    max_processes,
+                    max_total_freeze_mb,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // Largest first: 600 + 400 fits, adding 300 would not
+        let mut engine = engine_with(None, Some(1000));
+        let report = engine.freeze_session().unwrap();
+        let pids: Vec<u32> = report.frozen.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![2, 3]);
+        assert_eq!(report.total_memory_mb, 1000);
+        assert_eq!(engine.controller.get_frozen_pids().len(), 2);
+
+        let plan = engine.plan().unwrap();
+        assert!(plan
+            .protected
+            .iter()
+            .all(|(_, reason)| *reason == ProtectReason::OverMemoryCap));
+
+        // Whichever cap is tighter wins
+        let mut engine = engine_with(Some(1), Some(1000));
+        let plan = engine.plan().unwrap();
+        assert_eq!(plan.would_freeze.len(), 1);
+        assert!(plan
+            .protected
+            .iter()
+            .all(|(_, reason)| *reason == ProtectReason::OverProcessCap));
+
+        let mut engine = engine_with(Some(3), Some(700));
+        let safe = engine.find_safe_to_freeze().unwrap();
+        let pids: Vec<u32> = safe.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![2]);
+    }
+
+    #[test]
+    fn test_find_safe_to_freeze_min_cpu() {
+        let mut idle =
+            create_test_process(1, "idle.exe", 200, false, ProcessCategory::Productivity);
+        idle.cpu_percent = 0.5;
+        let mut busy =
+            create_test_process(2, "busy.exe", 200, false, ProcessCategory::Productivity);
+        busy.cpu_percent = 12.0;
+
+        let enumerator = MockEnumerator::new(vec![idle, busy], None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            min_cpu_percent: Some(5.0),
+            ..Default::default()
+        };
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let safe = engine.find_safe_to_freeze().unwrap();
+
+        assert_eq!(safe.len(), 1);
+        assert_eq!(safe[0].pid, 2);
+    }
+
+    #[test]
+    fn test_freeze_multiple() {
+        let processes = vec![];
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+
+        let results = engine.freeze_multiple(&[1, 2, 3]);
+        assert_eq!(results.len(), 3);
+
+        for (_pid, result) in results {
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap(), 1);
+        }
+
+        // Frozen in parallel, so in no particular order
+        let frozen: HashSet<u32> = engine.controller.get_frozen_pids().into_iter().collect();
+        assert_eq!(frozen, HashSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_resume_multiple_only_counts_frozen() {
+        let enumerator = MockEnumerator::new(vec![], None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.freeze_multiple(&[1, 2]);
+
+        let results = engine.resume_multiple(&[1, 3]);
+        assert_eq!(results[0].1.as_ref().unwrap(), &1);
+        assert_eq!(results[1].1.as_ref().unwrap(), &0);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![2]);
+    }
+
+    #[test]
+    fn test_freeze_guarded_refuses_protected_targets() {
+        let processes = vec![
+            create_test_process(4, "System", 1, false, ProcessCategory::Critical),
+            create_test_process(10, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(20, "editor.exe", 300, true, ProcessCategory::Productivity),
+            create_child_process(30, 40, "smart-freeze.exe", false, ProcessCategory::Unknown),
+        ];
+        let enumerator = MockEnumerator::new(processes, Some(20));
+        let config = FreezeConfig {
+            self_pid: Some(30),
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        for pid in [4, 20, 30, 40, 999] {
+            assert!(
+                matches!(
+                    engine.freeze_guarded(pid, false),
+                    Err(SmartFreezeError::ProtectedProcess { .. })
+                ),
+                "PID {} should be refused",
+                pid
+            );
+        }
+        assert!(engine.controller.get_frozen_pids().is_empty());
+
+        assert_eq!(engine.freeze_guarded(10, false).unwrap(), 1);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![10]);
+    }
+
+    #[test]
+    fn test_freeze_multiple_guarded_refuses_protected_targets() {
+        let processes = vec![
+            create_test_process(4, "csrss.exe", 5, false, ProcessCategory::Critical),
+            create_test_process(10, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(20, "editor.exe", 300, true, ProcessCategory::Productivity),
+            create_child_process(30, 40, "smart-freeze.exe", false, ProcessCategory::Unknown),
+            create_test_process(50, "Spotify.exe", 400, false, ProcessCategory::Productivity),
+        ];
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, Some(20)),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                self_pid: Some(30),
+                ..Default::default()
+            },
+        );
+
+        let results = engine
+            .freeze_multiple_guarded(&[4, 10, 20, 30, 40, 50])
+            .unwrap();
+
+        let pids: Vec<u32> = results.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(pids, vec![4, 10, 20, 30, 40, 50]);
+        for (pid, result) in &results {
+            match pid {
+                10 | 50 => assert_eq!(result.as_ref().unwrap(), &1),
+                _ => assert!(matches!(
+                    result,
+                    Err(SmartFreezeError::ProtectedProcess { .. })
+                )),
+            }
+        }
+        let mut frozen = engine.controller.get_frozen_pids();
+        frozen.sort_unstable();
+        assert_eq!(frozen, vec![10, 50]);
+    }
+
+    #[test]
+    fn test_freeze_guarded_force_overrides() {
+        let processes = vec![create_test_process(
+            4,
+            "System",
+            1,
+            false,
+            ProcessCategory::Critical,
+        )];
+        let enumerator = MockEnumerator::new(processes, None);
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        assert!(engine.freeze_guarded(4, false).is_err());
+        assert_eq!(engine.freeze_guarded(4, true).unwrap(), 1);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![4]);
+    }
+
+    #[test]
+    fn test_freeze_process_detailed() {
+        let processes = vec![create_test_process(
+            42,
+            "chrome.exe",
+            800,
+            false,
+            ProcessCategory::Productivity,
+        )];
+        let controller = MockController {
+            threads: 12,
+            freeze_delay: Duration::from_millis(5),
+            ..MockController::new()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            controller,
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        engine.enumerate_processes().unwrap();
+
+        let outcome = engine.freeze_process_detailed(42).unwrap();
+        assert_eq!(outcome.pid, 42);
+        assert_eq!(outcome.threads_affected, 12);
+        assert!(outcome.elapsed >= Duration::from_millis(5));
+        assert_eq!(outcome.memory_mb_before, Some(800));
+
+        // Not in the last enumeration: no memory figure, still frozen
+        let outcome = engine.freeze_process_detailed(7).unwrap();
+        assert_eq!(outcome.memory_mb_before, None);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![42, 7]);
+    }
+
+    /// Controller with scripted failures per PID
+    struct ScriptedController {
+        frozen: std::sync::Mutex<Vec<u32>>,
+    }
+
+    impl ProcessController for ScriptedController {
+        fn freeze(&self, pid: u32) -> Result<usize> {
+            match pid {
+                2 => Err(SmartFreezeError::AccessDenied { pid }),
+                3 => Err(SmartFreezeError::AlreadyFrozen { pid }),
+                _ => {
+                    self.frozen.lock().unwrap().push(pid);
+                    Ok(1)
+                }
+            }
+        }
+
+        fn resume(&self, _pid: u32) -> Result<usize> {
+            Ok(1)
+        }
+
+        /// Pages out 40% of a 100 MB-per-PID working set; PID 4 can't be trimmed
+        fn trim_working_set(&self, pid: u32) -> Result<WorkingSetTrim> {
+            if pid == 4 {
+                return Err(SmartFreezeError::AccessDenied { pid });
+            }
+            Ok(WorkingSetTrim {
+                before_mb: u64::from(pid) * 100,
+                after_mb: u64::from(pid) * 60,
+            })
+        }
+    }
+
+    #[test]
+    fn test_working_set_trim_delta() {
+        let trim = WorkingSetTrim {
+            before_mb: 500,
+            after_mb: 120,
+        };
+        assert_eq!(trim.reclaimed_mb(), 380);
+
+        // The process grew again between the samples
+        let grew = WorkingSetTrim {
+            before_mb: 100,
+            after_mb: 130,
+        };
+        assert_eq!(grew.reclaimed_mb(), 0);
+    }
+
+    #[test]
+    fn test_freeze_session_report() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(2, "admin.exe", 400, false, ProcessCategory::Unknown),
+            create_test_process(3, "frozen.exe", 300, false, ProcessCategory::Unknown),
+            create_test_process(
+                4,
+                "onedrive.exe",
+                200,
+                false,
+                ProcessCategory::BackgroundService,
+            ),
+            create_test_process(5, "game.exe", 900, false, ProcessCategory::Gaming),
+        ];
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            ScriptedController {
+                frozen: std::sync::Mutex::new(Vec::new()),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let report = engine.freeze_session().unwrap();
+
+        let pids = |list: &[ProcessInfo]| list.iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(report.frozen_count(), 2);
+        assert_eq!(pids(&report.frozen), vec![1, 4]);
+        assert_eq!(report.total_memory_mb, 700);
+        assert_eq!(pids(&report.skipped), vec![3]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0.pid, 2);
+        assert!(matches!(
+            report.failed[0].1,
+            SmartFreezeError::AccessDenied { pid: 2 }
+        ));
+    }
+
+    #[test]
+    fn test_freeze_session_measures_reclaimed_memory() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(2, "admin.exe", 400, false, ProcessCategory::Unknown),
+            create_test_process(
+                4,
+                "onedrive.exe",
+                200,
+                false,
+                ProcessCategory::BackgroundService,
+            ),
+            create_test_process(6, "slack.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let scripted = || ScriptedController {
+            frozen: std::sync::Mutex::new(Vec::new()),
+        };
+
+        let mut untrimmed = FreezeEngine::new(
+            MockEnumerator::new(processes.clone(), None),
+            scripted(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        let report = untrimmed.freeze_session().unwrap();
+        assert_eq!(report.reclaimed_mb, None);
+        assert_eq!(report.freed_mb(), 1000);
+
+        let config = FreezeConfig {
+            trim_after_freeze: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            scripted(),
+            DefaultCategorizer::new(),
+            config,
+        );
+        let report = engine.freeze_session().unwrap();
+
+        // Frozen: 1, 4 and 6; PID 4 couldn't be trimmed, PID 2 wasn't frozen
+        assert_eq!(report.frozen_count(), 3);
+        assert_eq!(report.total_memory_mb, 1000);
+        assert_eq!(report.reclaimed_mb, Some(40 + 240));
+        assert_eq!(report.freed_mb(), 280);
+    }
+
+    #[test]
+    fn test_freeze_session_respects_memory_target() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(2, "slack.exe", 400, false, ProcessCategory::Productivity),
+            create_test_process(3, "code.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let mut enumerator = MockEnumerator::new(processes, None);
+        enumerator.system_memory = Some((16_000, 3_600));
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                target_free_mb: Some(4_000),
+                ..Default::default()
+            },
+        );
+
+        // 400 MB missing: the biggest process covers it
+        let report = engine.freeze_session().unwrap();
+        assert_eq!(report.frozen_count(), 1);
+        assert_eq!(report.frozen[0].pid, 1);
+        assert!(report.failed.is_empty());
+    }
+
+    #[test]
+    fn test_freeze_and_resume_are_audited() {
+        let path = std::env::temp_dir().join(format!(
+            "smartfreeze_engine_audit_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let processes = vec![create_test_process(
+            42,
+            "chrome.exe",
+            800,
+            false,
+            ProcessCategory::Productivity,
+        )];
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        engine.set_audit(AuditLogger::new(path.clone(), 1024 * 1024, 1));
+        engine.enumerate_processes().unwrap();
+
+        engine.freeze_process(42).unwrap();
+        engine.resume_process(42).unwrap();
+        // Nothing was frozen, so the mock resumes 0 threads; still a success
+        engine.resume_process(7).unwrap();
+
+        let entries = AuditLogger::new(path.clone(), 1024 * 1024, 1)
+            .read_recent(10)
+            .unwrap();
+        let summary: Vec<(u32, &str, AuditAction)> = entries
+            .iter()
+            .map(|e| (e.pid, e.name.as_str(), e.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (42, "chrome.exe", AuditAction::Freeze),
+                (42, "chrome.exe", AuditAction::Resume),
+                (7, "", AuditAction::Resume),
+            ]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_explain_by_name_and_pid() {
+        let mut discord = create_test_process(
+            42,
+            "Discord.exe",
+            350,
+            false,
+            ProcessCategory::Communication,
+        );
+        discord.parent_pid = 7;
+        let processes = vec![
+            create_test_process(7, "explorer.exe", 120, false, ProcessCategory::Critical),
+            discord,
+            create_test_process(43, "chrome.exe", 80, false, ProcessCategory::Productivity),
+        ];
+        let config = FreezeConfig {
+            keep_communication: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(processes, None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        let explained = engine.explain("discord").unwrap();
+        assert_eq!(explained.len(), 1);
+        let discord = &explained[0];
+        assert_eq!(discord.process.pid, 42);
+        assert_eq!(discord.parent_name.as_deref(), Some("explorer.exe"));
+        assert_eq!(
+            discord.matches,
+            vec![(
+                ProcessCategory::Communication,
+                "matched name substring 'discord'".to_string()
+            )]
+        );
+        assert_eq!(discord.protect_reason, Some(ProtectReason::Communication));
+
+        let chrome = engine.explain("43").unwrap();
+        assert_eq!(chrome.len(), 1);
+        assert_eq!(chrome[0].threshold_mb, 100);
+        assert_eq!(
+            chrome[0].protect_reason,
+            Some(ProtectReason::BelowThreshold)
+        );
+
+        assert!(engine.explain("notepad").unwrap().is_empty());
+    }
+
+    /// Controller whose processes were all killed while frozen
+    #[derive(Default)]
+    struct KilledController {
+        relaunched: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl ProcessController for KilledController {
+        fn freeze(&self, _pid: u32) -> Result<usize> {
+            Ok(1)
+        }
+
+        fn resume(&self, pid: u32) -> Result<usize> {
+            Err(SmartFreezeError::ProcessNotFound(pid))
+        }
+
+        fn restart_process(&self, exe_path: &str) -> Result<u32> {
+            self.relaunched.lock().unwrap().push(exe_path.to_string());
+            Ok(9000)
+        }
+    }
+
+    #[test]
+    fn test_killed_process_is_relaunched() {
+        let path = std::env::temp_dir().join(format!(
+            "smartfreeze_engine_relaunch_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let config = FreezeConfig {
+            relaunch_killed: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(Vec::new(), None),
+            KilledController::default(),
+            DefaultCategorizer::new(),
+            config,
+        );
+        engine.set_audit(AuditLogger::new(path.clone(), 1024 * 1024, 1));
+
+        let outcome = engine
+            .resume_or_relaunch(42, "C:\\Apps\\slack.exe")
+            .unwrap();
+        assert_eq!(outcome, ResumeOutcome::Relaunched(9000));
+        assert_eq!(
+            *engine.controller.relaunched.lock().unwrap(),
+            vec!["C:\\Apps\\slack.exe".to_string()]
+        );
+
+        // Without a saved path there is nothing to relaunch
+        assert!(matches!(
+            engine.resume_or_relaunch(43, ""),
+            Err(SmartFreezeError::ProcessNotFound(43))
+        ));
+
+        let entries = AuditLogger::new(path.clone(), 1024 * 1024, 1)
+            .read_recent(10)
+            .unwrap();
+        let relaunches: Vec<(u32, &str)> = entries
+            .iter()
+            .filter(|e| e.action == AuditAction::Relaunch)
+            .map(|e| (e.pid, e.name.as_str()))
+            .collect();
+        assert_eq!(relaunches, vec![(9000, "slack.exe")]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_killed_process_is_not_relaunched_by_default() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(Vec::new(), None),
+            KilledController::default(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        assert!(matches!(
+            engine.resume_or_relaunch(42, "C:\\Apps\\slack.exe"),
+            Err(SmartFreezeError::ProcessNotFound(42))
+        ));
+        assert!(engine.controller.relaunched.lock().unwrap().is_empty());
+    }
+
+    /// Controller that tracks how many `freeze` calls overlap
+    #[derive(Default)]
+    struct ConcurrencyController {
+        active: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl ProcessController for ConcurrencyController {
+        fn freeze(&self, pid: u32) -> Result<usize> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(10));
+            self.active.fetch_sub(1, Ordering::SeqCst);
+
+            if pid.is_multiple_of(5) {
+                return Err(SmartFreezeError::AccessDenied { pid });
+            }
+            Ok(pid as usize)
+        }
+
+        fn resume(&self, _pid: u32) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_freeze_multiple_runs_concurrently_in_order() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            ConcurrencyController::default(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        let pids: Vec<u32> = (1..=40).rev().collect();
+
+        let results = engine.freeze_multiple(&pids);
+
+        let peak = engine.controller.peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "expected overlapping freezes, peak was {}", peak);
+        assert!(peak <= MAX_FREEZE_WORKERS);
+
+        assert_eq!(
+            results.iter().map(|(pid, _)| *pid).collect::<Vec<_>>(),
+            pids
+        );
+        for (pid, result) in &results {
+            match result {
+                Ok(threads) => assert_eq!(*threads, *pid as usize),
+                Err(SmartFreezeError::AccessDenied { pid: failed }) => {
+                    assert_eq!(failed, pid);
+                    assert!(pid.is_multiple_of(5));
+                }
+                Err(e) => panic!("unexpected error {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_resume_by_category_is_selective() {
+        let enumerator = MockEnumerator::new(vec![], None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.freeze_multiple(&[1, 2, 3, 4, 5]);
+
+        let name_lookup: HashMap<u32, (String, String)> = [
+            (1, "Discord.exe", "C:\\Discord\\Discord.exe"),
+            (2, "OneDrive.exe", "C:\\OneDrive\\OneDrive.exe"),
+            (3, "slack.exe", "C:\\Slack\\slack.exe"),
+            (4, "chrome.exe", "C:\\Chrome\\chrome.exe"),
+        ]
+        .into_iter()
+        .map(|(pid, name, path)| (pid, (name.to_string(), path.to_string())))
+        .collect();
+
+        let results = engine.resume_by_category(
+            &[1, 2, 3, 4, 5],
+            ProcessCategory::Communication,
+            &name_lookup,
+        );
+        let resumed: Vec<u32> = results.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(resumed, vec![1, 3]);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        // PID 5 has no recorded name, so it is left alone
+        let frozen: HashSet<u32> = engine.controller.get_frozen_pids().into_iter().collect();
+        assert_eq!(frozen, HashSet::from([2, 4, 5]));
+
+        engine.resume_by_category(&[2, 4, 5], ProcessCategory::BackgroundService, &name_lookup);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![4, 5]);
+    }
+
+    fn create_child_process(
+        pid: u32,
+        parent_pid: u32,
+        name: &str,
+        is_foreground: bool,
+        category: ProcessCategory,
+    ) -> ProcessInfo {
+        let mut process = create_test_process(pid, name, 100, is_foreground, category);
+        process.parent_pid = parent_pid;
+        process
+    }
+
+    #[test]
+    fn test_freeze_process_tree() {
+        let processes = vec![
+            create_child_process(10, 1, "launcher.exe", false, ProcessCategory::Unknown),
+            create_child_process(11, 10, "helper.exe", false, ProcessCategory::Unknown),
+            create_child_process(12, 11, "renderer.exe", false, ProcessCategory::Unknown),
+            create_child_process(13, 10, "conhost.exe", false, ProcessCategory::Critical),
+            create_child_process(
+                14,
+                13,
+                "under_critical.exe",
+                false,
+                ProcessCategory::Unknown,
             ),
+            create_child_process(15, 10, "window.exe", true, ProcessCategory::Productivity),
+            create_child_process(20, 1, "unrelated.exe", false, ProcessCategory::Unknown),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.enumerate_processes().unwrap();
+
+        let results = engine.freeze_process_tree(10);
+        let pids: Vec<u32> = results.iter().map(|(pid, _)| *pid).collect();
+
+        assert_eq!(pids, vec![10, 11, 12]);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn test_find_descendants_handles_cycles_and_pid_zero() {
+        let processes = vec![
+            create_child_process(0, 0, "idle", false, ProcessCategory::Unknown),
+            create_child_process(1, 2, "a.exe", false, ProcessCategory::Unknown),
+            create_child_process(2, 1, "b.exe", false, ProcessCategory::Unknown),
+            create_child_process(3, 3, "self_parent.exe", false, ProcessCategory::Unknown),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.enumerate_processes().unwrap();
+
+        assert_eq!(engine.find_descendants(1), vec![2]);
+        assert_eq!(engine.find_descendants(3), Vec::<u32>::new());
+        assert!(!engine.find_descendants(0).contains(&0));
+    }
+
+    #[test]
+    fn test_find_gaming_processes() {
+        let processes = vec![
+            create_test_process(1, "steam.exe", 150, false, ProcessCategory::Gaming),
+            create_test_process(2, "game.exe", 500, false, ProcessCategory::Gaming),
+            create_test_process(3, "chrome.exe", 200, false, ProcessCategory::Productivity),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, None);
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        let gaming = engine.find_gaming_processes().unwrap();
+
+        assert_eq!(gaming.len(), 2);
+        assert!(gaming.iter().any(|p| p.pid == 1));
+        assert!(gaming.iter().any(|p| p.pid == 2));
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let processes = vec![
+            create_test_process(7, "Discord.exe", 300, false, ProcessCategory::Communication),
+            create_test_process(3, "discord.exe", 150, false, ProcessCategory::Communication),
+            create_test_process(5, "chrome.exe", 200, false, ProcessCategory::Productivity),
         ];
 
-        let enumerator = MockEnumerator::new(processes, Some(1));
+        let enumerator = MockEnumerator::new(processes, None);
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let pids: Vec<u32> = engine
+            .find_by_name("DISCORD")
+            .unwrap()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![3, 7]);
+        assert!(engine.find_by_name("spotify").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_default_capabilities_are_suspend_only() {
+        let controller = MockController::new();
+
+        assert!(controller.supports_mode(FreezeMode::Suspend));
+        assert!(!controller.supports_mode(FreezeMode::Throttle));
+    }
+
+    #[test]
+    fn test_capabilities_from_modes() {
+        let caps = FreezeCapabilities::from_modes(&[FreezeMode::Suspend, FreezeMode::Soft]);
+        assert!(caps.supports(FreezeMode::Suspend));
+        assert!(caps.supports(FreezeMode::Soft));
+        assert!(!caps.supports(FreezeMode::Throttle));
+
+        assert!(!FreezeCapabilities::none().supports(FreezeMode::Suspend));
+    }
+
+    #[test]
+    fn test_engine_rejects_unsupported_mode() {
+        let enumerator = MockEnumerator::new(vec![], None);
+        // Without throttle support, the mock controller rejects --mode throttle
+        let controller = MockController::new();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig {
+            mode: Some(FreezeMode::Throttle),
+            ..Default::default()
+        };
+
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+
+        assert!(matches!(
+            engine.validate_mode(),
+            Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle))
+        ));
+        assert!(engine.freeze_process(1).is_err());
+        assert!(engine.controller.get_frozen_pids().is_empty());
+    }
+
+    #[test]
+    fn test_engine_accepts_supported_mode() {
+        let enumerator = MockEnumerator::new(vec![], None);
         let controller = MockController::new();
         let categorizer = DefaultCategorizer::new();
-        let config = FreezeConfig::default();
+        let config = FreezeConfig {
+            mode: Some(FreezeMode::Suspend),
+            ..Default::default()
+        };
 
-        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
-        let safe = engine.find_safe_to_freeze().unwrap();
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
 
-        assert_eq!(safe.len(), 1);
-        assert_eq!(safe[0].pid, 2);
+        assert!(engine.validate_mode().is_ok());
+        assert_eq!(engine.freeze_process(1).unwrap(), 1);
     }
 
     #[test]
-    fn test_find_safe_to_freeze_excludes_critical() {
+    fn test_engine_from_config() {
+        let args = Args::parse_from(["smart-freeze", "--threshold", "500", "--mode", "throttle"]);
+        let engine = FreezeEngine::from_config(&Config::from_args(&args));
+
+        assert_eq!(engine.config().min_memory_mb, 500);
+        assert_eq!(engine.config().self_pid, Some(std::process::id()));
+        assert!(matches!(
+            engine.validate_mode(),
+            Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle))
+        ));
+    }
+
+    struct MockTrace {
+        entries: std::sync::Arc<std::sync::Mutex<Vec<TraceEntry>>>,
+    }
+
+    impl DecisionTrace for MockTrace {
+        fn record(&self, entry: &TraceEntry) -> Result<()> {
+            self.entries.lock().unwrap().push(entry.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_records_each_call() {
         let processes = vec![
-            create_test_process(1, "explorer.exe", 200, false, ProcessCategory::Critical),
-            create_test_process(2, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(1, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "explorer.exe", 200, false, ProcessCategory::Critical),
+            create_test_process(3, "tiny.exe", 10, false, ProcessCategory::Unknown),
         ];
 
         let enumerator = MockEnumerator::new(processes, None);
@@ -271,75 +3074,535 @@ mod tests {
         let categorizer = DefaultCategorizer::new();
         let config = FreezeConfig::default();
 
+        let entries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
         let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.set_trace(Box::new(MockTrace {
+            entries: entries.clone(),
+        }));
+
+        let first = engine.find_safe_to_freeze().unwrap();
+        let second = engine.find_safe_to_freeze().unwrap();
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        for (entry, result) in entries.iter().zip([first, second]) {
+            assert_eq!(entry.input.len(), 3);
+            assert_eq!(entry.decisions.len(), 3);
+            assert_eq!(
+                entry.selected_pids(),
+                result.iter().map(|p| p.pid).collect::<Vec<_>>()
+            );
+        }
+
+        let reasons: Vec<_> = entries[0]
+            .decisions
+            .iter()
+            .map(|d| d.reason.as_str())
+            .collect();
+        assert_eq!(reasons[0], "selected");
+        assert_eq!(reasons[1], "protected category (Critical)");
+        assert_eq!(reasons[2], "below threshold (10 MB < 100 MB)");
+    }
+
+    /// Controller whose first `failures` freeze attempts fail transiently
+    struct FlakyController {
+        failures: std::sync::Mutex<u32>,
+        attempts: std::sync::Mutex<u32>,
+        gone: bool,
+    }
+
+    impl FlakyController {
+        fn new(failures: u32, gone: bool) -> Self {
+            Self {
+                failures: std::sync::Mutex::new(failures),
+                attempts: std::sync::Mutex::new(0),
+                gone,
+            }
+        }
+    }
+
+    impl ProcessController for FlakyController {
+        fn freeze(&self, pid: u32) -> Result<usize> {
+            *self.attempts.lock().unwrap() += 1;
+            if self.gone {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(SmartFreezeError::FreezeFailed {
+                    pid,
+                    reason: "thread exited".to_string(),
+                });
+            }
+            Ok(4)
+        }
+
+        fn resume(&self, _pid: u32) -> Result<usize> {
+            Ok(4)
+        }
+    }
+
+    #[test]
+    fn test_freeze_retry_recovers_from_transient_failure() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            FlakyController::new(1, false),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let result = engine.freeze_process_retry(42, 3, std::time::Duration::from_millis(1));
+
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(*engine.controller.attempts.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_freeze_retry_gives_up() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            FlakyController::new(5, false),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let result = engine.freeze_process_retry(42, 3, std::time::Duration::from_millis(1));
+
+        assert!(matches!(result, Err(SmartFreezeError::FreezeFailed { .. })));
+        assert_eq!(*engine.controller.attempts.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_freeze_retry_skips_missing_process() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            FlakyController::new(0, true),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let result = engine.freeze_process_retry(42, 3, std::time::Duration::from_millis(1));
+
+        assert!(matches!(result, Err(SmartFreezeError::ProcessNotFound(42))));
+        assert_eq!(*engine.controller.attempts.lock().unwrap(), 1);
+    }
+
+    /// Controller tracking per-process suspend depth, as Windows does
+    struct DepthController {
+        depth: std::sync::Mutex<HashMap<u32, u32>>,
+    }
+
+    impl ProcessController for DepthController {
+        fn freeze(&self, pid: u32) -> Result<usize> {
+            let mut depth = self.depth.lock().unwrap();
+            let current = depth.entry(pid).or_insert(0);
+            if *current > 0 {
+                return Err(SmartFreezeError::AlreadyFrozen { pid });
+            }
+            *current += 1;
+            Ok(1)
+        }
+
+        fn resume(&self, pid: u32) -> Result<usize> {
+            let mut depth = self.depth.lock().unwrap();
+            match depth.get_mut(&pid) {
+                Some(current) if *current > 0 => {
+                    *current -= 1;
+                    Ok(1)
+                }
+                _ => Err(SmartFreezeError::ResumeFailed {
+                    pid,
+                    reason: "not frozen".to_string(),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_second_freeze_is_rejected() {
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            DepthController {
+                depth: std::sync::Mutex::new(HashMap::new()),
+            },
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        assert_eq!(engine.freeze_process(42).unwrap(), 1);
+        assert!(matches!(
+            engine.freeze_process_retry(42, 3, std::time::Duration::from_millis(1)),
+            Err(SmartFreezeError::AlreadyFrozen { pid: 42 })
+        ));
+        assert_eq!(engine.controller.depth.lock().unwrap()[&42], 1);
+
+        // One resume fully wakes the process
+        engine.resume_process(42).unwrap();
+        assert_eq!(engine.controller.depth.lock().unwrap()[&42], 0);
+        assert_eq!(engine.freeze_process(42).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_plan_classifies_protected() {
+        let processes = vec![
+            create_test_process(1, "editor.exe", 400, true, ProcessCategory::Productivity),
+            create_test_process(2, "explorer.exe", 300, false, ProcessCategory::Critical),
+            create_test_process(3, "game.exe", 3000, false, ProcessCategory::Gaming),
+            create_test_process(4, "discord.exe", 250, false, ProcessCategory::Communication),
+            create_test_process(5, "tiny.exe", 20, false, ProcessCategory::Productivity),
+            create_test_process(6, "chrome.exe", 800, false, ProcessCategory::Productivity),
+            create_test_process(
+                7,
+                "sync.exe",
+                200,
+                false,
+                ProcessCategory::BackgroundService,
+            ),
+        ];
+
+        let enumerator = MockEnumerator::new(processes, Some(1));
+        let config = FreezeConfig {
+            keep_communication: true,
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        let plan = engine.plan().unwrap();
+
+        let frozen: Vec<u32> = plan.would_freeze.iter().map(|p| p.pid).collect();
+        assert_eq!(frozen, vec![6, 7]);
+        assert_eq!(plan.total_memory_to_free_mb, 1000);
+
+        let protected: Vec<(u32, ProtectReason)> = plan
+            .protected
+            .iter()
+            .map(|(p, reason)| (p.pid, *reason))
+            .collect();
+        assert_eq!(
+            protected,
+            vec![
+                (3, ProtectReason::Gaming),
+                (1, ProtectReason::Foreground),
+                (2, ProtectReason::Critical),
+                (4, ProtectReason::Communication),
+                (5, ProtectReason::BelowThreshold),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_process_age() {
+        let mut fresh =
+            create_test_process(1, "fresh.exe", 500, false, ProcessCategory::Productivity);
+        fresh.age_secs = Some(3);
+        let mut settled =
+            create_test_process(2, "settled.exe", 500, false, ProcessCategory::Productivity);
+        settled.age_secs = Some(10);
+        let mut forced = create_test_process(3, "forced.exe", 500, false, ProcessCategory::Unknown);
+        forced.age_secs = Some(1);
+        // Start time couldn't be read
+        let mut unknown =
+            create_test_process(4, "unknown.exe", 400, false, ProcessCategory::Productivity);
+        unknown.age_secs = None;
+
+        let enumerator = MockEnumerator::new(vec![fresh, settled, forced, unknown], None);
+        let config = FreezeConfig {
+            always_freeze: vec!["forced.exe".to_string()],
+            ..Default::default()
+        };
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
         let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2, 4]);
 
-        assert_eq!(safe.len(), 1);
-        assert_eq!(safe[0].pid, 2);
+        engine.set_config(FreezeConfig {
+            min_process_age_secs: 0,
+            ..Default::default()
+        });
+        assert_eq!(engine.find_safe_to_freeze().unwrap().len(), 4);
     }
 
     #[test]
-    fn test_keep_communication_flag() {
+    fn test_find_new_safe_to_freeze() {
+        let first = vec![
+            create_test_process(1, "game.exe", 4000, true, ProcessCategory::Gaming),
+            create_test_process(2, "chrome.exe", 500, false, ProcessCategory::Productivity),
+        ];
+        let mut second = first.clone();
+        second.push(create_test_process(
+            3,
+            "updater.exe",
+            300,
+            false,
+            ProcessCategory::BackgroundService,
+        ));
+
+        let enumerator = MockEnumerator::with_sequence(vec![first, second]);
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let mut frozen = HashSet::new();
+        let initial = engine.find_new_safe_to_freeze(&frozen, 0).unwrap();
+        assert_eq!(initial.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2]);
+        frozen.insert(2);
+
+        let new = engine.find_new_safe_to_freeze(&frozen, 500).unwrap();
+        assert_eq!(new.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_top_up_respects_running_memory_cap() {
+        let first = vec![
+            create_test_process(1, "a.exe", 600, false, ProcessCategory::Productivity),
+            create_test_process(2, "b.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let mut second = first.clone();
+        second.push(create_test_process(
+            3,
+            "c.exe",
+            100,
+            false,
+            ProcessCategory::Productivity,
+        ));
+        second.push(create_test_process(
+            4,
+            "d.exe",
+            50,
+            false,
+            ProcessCategory::Productivity,
+        ));
+
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::with_sequence(vec![first, second]),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig {
+                max_total_freeze_mb: Some(1000),
+                ..Default::default()
+            },
+        );
+
+        let initial = engine.find_new_safe_to_freeze(&HashSet::new(), 0).unwrap();
+        let frozen: HashSet<u32> = initial.iter().map(|p| p.pid).collect();
+        assert_eq!(frozen, HashSet::from([1, 2]));
+
+        // 900 MB are already frozen: only 100 MB more fits
+        let new = engine.find_new_safe_to_freeze(&frozen, 900).unwrap();
+        assert_eq!(new.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_gaming_session_triggers() {
+        let processes = vec![create_test_process(
+            1,
+            "unlisted_game.exe",
+            2000,
+            true,
+            ProcessCategory::Unknown,
+        )];
+
+        let mut enumerator = MockEnumerator::new(processes, Some(1));
+        enumerator.fullscreen = true;
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        // Fullscreen alone does nothing unless the trigger is enabled
+        assert!(!engine.is_gaming_session().unwrap());
+        assert!(engine.gaming_session_pids().unwrap().is_empty());
+
+        engine.set_config(FreezeConfig {
+            trigger_on_fullscreen: true,
+            ..Default::default()
+        });
+        assert!(engine.is_gaming_session().unwrap());
+        assert_eq!(engine.gaming_session_pids().unwrap(), HashSet::from([1]));
+
+        let game = create_test_process(2, "game.exe", 2000, false, ProcessCategory::Gaming);
+        let mut engine = FreezeEngine::new(
+            MockEnumerator::new(vec![game], None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+        assert!(engine.is_gaming_session().unwrap());
+    }
+
+    #[test]
+    fn test_user_idle_trigger() {
+        let mut enumerator = MockEnumerator::new(Vec::new(), None);
+        enumerator.idle_secs = Some(300);
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        // Idle time alone does nothing without a trigger
+        assert!(!engine.is_user_idle());
+
+        for (trigger, idle) in [(300, true), (301, false)] {
+            engine.set_config(FreezeConfig {
+                idle_trigger_secs: Some(trigger),
+                ..Default::default()
+            });
+            assert_eq!(engine.is_user_idle(), idle, "trigger {}", trigger);
+        }
+
+        engine.enumerator.idle_secs = None;
+        assert!(!engine.is_user_idle());
+    }
+
+    #[test]
+    fn test_throttle_process() {
         let processes = vec![
-            create_test_process(1, "discord.exe", 200, false, ProcessCategory::Communication),
-            create_test_process(2, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(1, "explorer.exe", 200, false, ProcessCategory::Critical),
+            create_test_process(2, "window.exe", 200, true, ProcessCategory::Productivity),
+            create_test_process(3, "chrome.exe", 200, false, ProcessCategory::Productivity),
         ];
 
-        let enumerator = MockEnumerator::new(processes.clone(), None);
-        let controller = MockController::new();
+        let enumerator = MockEnumerator::new(processes, Some(2));
+        let controller = MockController::with_throttle();
+        let categorizer = DefaultCategorizer::new();
+        let config = FreezeConfig::default();
+
+        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+        engine.enumerate_processes().unwrap();
+
+        assert!(matches!(
+            engine.throttle_process(1),
+            Err(SmartFreezeError::ProtectedProcess { pid: 1, .. })
+        ));
+        assert!(matches!(
+            engine.throttle_process(2),
+            Err(SmartFreezeError::ProtectedProcess { pid: 2, .. })
+        ));
+        assert!(engine.throttle_process(3).is_ok());
+        assert_eq!(*engine.controller.throttled_pids.lock().unwrap(), vec![3]);
+
+        engine.unthrottle_process(3).unwrap();
+        assert!(engine.controller.throttled_pids.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_throttle_mode_dispatch() {
+        let enumerator = MockEnumerator::new(vec![], None);
+        let controller = MockController::with_throttle();
         let categorizer = DefaultCategorizer::new();
         let config = FreezeConfig {
-            min_memory_mb: 100,
-            keep_communication: false,
+            mode: Some(FreezeMode::Throttle),
+            ..Default::default()
         };
 
-        let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
-        let safe = engine.find_safe_to_freeze().unwrap();
-        assert_eq!(safe.len(), 2); // Both freezable
+        let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
 
-        // Now with keep_communication enabled
-        let enumerator2 = MockEnumerator::new(processes, None);
-        let controller2 = MockController::new();
-        let categorizer2 = DefaultCategorizer::new();
-        let config2 = FreezeConfig {
-            min_memory_mb: 100,
-            keep_communication: true,
+        assert_eq!(engine.freeze_process(7).unwrap(), 0);
+        assert_eq!(*engine.controller.throttled_pids.lock().unwrap(), vec![7]);
+        assert!(engine.controller.get_frozen_pids().is_empty());
+
+        engine.resume_process(7).unwrap();
+        assert!(engine.controller.throttled_pids.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_soft_mode_lowers_priority_without_suspending() {
+        let config = FreezeConfig {
+            mode: Some(FreezeMode::Soft),
+            ..Default::default()
         };
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
 
-        let mut engine2 = FreezeEngine::new(enumerator2, controller2, categorizer2, config2);
-        let safe2 = engine2.find_safe_to_freeze().unwrap();
-        assert_eq!(safe2.len(), 1); // Only chrome, discord protected
-        assert_eq!(safe2[0].pid, 2);
+        assert_eq!(engine.freeze_process(7).unwrap(), 0);
+        assert_eq!(
+            engine.controller.soft_frozen.lock().unwrap()[&7],
+            (SOFT_FREEZE_PRIORITY_CLASS, SOFT_FREEZE_AFFINITY_MASK)
+        );
+        assert!(engine.controller.get_frozen_pids().is_empty());
+
+        assert_eq!(engine.resume_process(7).unwrap(), 0);
+        assert!(engine.controller.soft_frozen.lock().unwrap().is_empty());
     }
 
     #[test]
-    fn test_freeze_multiple() {
-        let processes = vec![];
-        let enumerator = MockEnumerator::new(processes, None);
+    fn test_soft_mode_escalates_to_suspend() {
+        let config = FreezeConfig {
+            mode: Some(FreezeMode::Soft),
+            escalate_soft_freeze: true,
+            ..Default::default()
+        };
+        let controller = MockController {
+            threads: 6,
+            ..MockController::new()
+        };
+        let engine = FreezeEngine::new(
+            MockEnumerator::new(vec![], None),
+            controller,
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        assert_eq!(engine.freeze_process(7).unwrap(), 6);
+        assert!(engine
+            .controller
+            .soft_frozen
+            .lock()
+            .unwrap()
+            .contains_key(&7));
+        assert_eq!(engine.controller.get_frozen_pids(), vec![7]);
+
+        assert_eq!(engine.resume_process(7).unwrap(), 1);
+        assert!(engine.controller.soft_frozen.lock().unwrap().is_empty());
+        assert!(engine.controller.get_frozen_pids().is_empty());
+    }
+
+    #[test]
+    fn test_throttle_unsupported() {
+        let enumerator = MockEnumerator::new(vec![], None);
         let controller = MockController::new();
         let categorizer = DefaultCategorizer::new();
         let config = FreezeConfig::default();
 
         let engine = FreezeEngine::new(enumerator, controller, categorizer, config);
 
-        let results = engine.freeze_multiple(&[1, 2, 3]);
-        assert_eq!(results.len(), 3);
-
-        for (_pid, result) in results {
-            assert!(result.is_ok());
-            assert_eq!(result.unwrap(), 1);
-        }
-
-        let frozen = engine.controller.get_frozen_pids();
-        assert_eq!(frozen, vec![1, 2, 3]);
+        assert!(matches!(
+            engine.throttle_process(1),
+            Err(SmartFreezeError::UnsupportedMode(FreezeMode::Throttle))
+        ));
     }
 
     #[test]
-    fn test_find_gaming_processes() {
+    fn test_trim_safe_to_freeze() {
         let processes = vec![
-            create_test_process(1, "steam.exe", 150, false, ProcessCategory::Gaming),
-            create_test_process(2, "game.exe", 500, false, ProcessCategory::Gaming),
-            create_test_process(3, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(1, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "code.exe", 300, false, ProcessCategory::Productivity),
+            create_test_process(3, "small.exe", 50, false, ProcessCategory::Productivity),
+            create_test_process(99, "gone.exe", 200, false, ProcessCategory::Productivity),
         ];
 
         let enumerator = MockEnumerator::new(processes, None);
@@ -348,10 +3611,162 @@ mod tests {
         let config = FreezeConfig::default();
 
         let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
-        let gaming = engine.find_gaming_processes().unwrap();
 
-        assert_eq!(gaming.len(), 2);
-        assert!(gaming.iter().any(|p| p.pid == 1));
-        assert!(gaming.iter().any(|p| p.pid == 2));
+        // pid 3 is below threshold, pid 99 fails and is skipped
+        assert_eq!(engine.trim_safe_to_freeze().unwrap(), 30);
+        assert!(engine.controller.get_frozen_pids().is_empty());
+    }
+
+    #[test]
+    fn test_select_until_target() {
+        let candidates = vec![
+            create_test_process(1, "a.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "b.exe", 800, false, ProcessCategory::Productivity),
+            create_test_process(3, "c.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(4, "d.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let pids = |selected: Vec<ProcessInfo>| selected.iter().map(|p| p.pid).collect::<Vec<_>>();
+
+        assert!(select_until_target(candidates.clone(), 0).is_empty());
+        assert_eq!(pids(select_until_target(candidates.clone(), 800)), vec![2]);
+        assert_eq!(
+            pids(select_until_target(candidates.clone(), 801)),
+            vec![2, 3]
+        );
+        assert_eq!(
+            pids(select_until_target(candidates, 10_000)),
+            vec![2, 3, 4, 1]
+        );
+    }
+
+    #[test]
+    fn test_select_within_memory_cap() {
+        let candidates = vec![
+            create_test_process(1, "a.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "b.exe", 800, false, ProcessCategory::Productivity),
+            create_test_process(3, "c.exe", 500, false, ProcessCategory::Productivity),
+            create_test_process(4, "d.exe", 300, false, ProcessCategory::Productivity),
+        ];
+        let pids = |selected: Vec<ProcessInfo>| selected.iter().map(|p| p.pid).collect::<Vec<_>>();
+
+        // The largest doesn't fit, so nothing smaller is tried
+        assert!(select_within_memory_cap(candidates.clone(), 799).is_empty());
+        assert_eq!(
+            pids(select_within_memory_cap(candidates.clone(), 1299)),
+            vec![2]
+        );
+        assert_eq!(
+            pids(select_within_memory_cap(candidates.clone(), 1300)),
+            vec![2, 3]
+        );
+        assert_eq!(
+            pids(select_within_memory_cap(candidates, 10_000)),
+            vec![2, 3, 4, 1]
+        );
+    }
+
+    #[test]
+    fn test_freeze_until_target() {
+        let processes = vec![
+            create_test_process(1, "chrome.exe", 200, false, ProcessCategory::Productivity),
+            create_test_process(2, "code.exe", 900, false, ProcessCategory::Productivity),
+            create_test_process(3, "slack.exe", 400, false, ProcessCategory::Communication),
+            create_test_process(4, "game.exe", 2000, false, ProcessCategory::Gaming),
+        ];
+
+        let mut enumerator = MockEnumerator::new(processes, None);
+        // 8 GB machine with 3 GB free; aim for 4 GB free
+        enumerator.system_memory = Some((8192, 3072));
+        let config = FreezeConfig {
+            target_free_mb: Some(4096),
+            ..FreezeConfig::default()
+        };
+
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        assert_eq!(engine.memory_deficit_mb(), Some(1024));
+        let frozen: Vec<u32> = engine
+            .freeze_until_target()
+            .unwrap()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(frozen, vec![2, 3]);
+        assert_eq!(engine.controller.get_frozen_pids(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_freeze_until_target_already_met() {
+        let processes = vec![create_test_process(
+            1,
+            "chrome.exe",
+            200,
+            false,
+            ProcessCategory::Productivity,
+        )];
+
+        let mut enumerator = MockEnumerator::new(processes, None);
+        enumerator.system_memory = Some((65536, 40000));
+        let config = FreezeConfig {
+            target_free_mb: Some(4096),
+            ..FreezeConfig::default()
+        };
+
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        assert!(engine.freeze_until_target().unwrap().is_empty());
+        assert!(engine.controller.get_frozen_pids().is_empty());
+    }
+
+    #[test]
+    fn test_current_session_only() {
+        let mut other_user =
+            create_test_process(2, "chrome.exe", 400, false, ProcessCategory::Productivity);
+        other_user.session_id = 2;
+        let mut own =
+            create_test_process(1, "chrome.exe", 300, false, ProcessCategory::Productivity);
+        own.session_id = 1;
+        let processes = vec![own, other_user];
+
+        let mut enumerator = MockEnumerator::new(processes.clone(), None);
+        enumerator.session_id = Some(1);
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            FreezeConfig::default(),
+        );
+
+        let safe = engine.find_safe_to_freeze().unwrap();
+        assert_eq!(safe.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1]);
+        let plan = engine.plan().unwrap();
+        assert_eq!(plan.protected[0].0.pid, 2);
+        assert_eq!(plan.protected[0].1, ProtectReason::OtherSession);
+
+        // Opting out freezes across sessions
+        let mut enumerator = MockEnumerator::new(processes, None);
+        enumerator.session_id = Some(1);
+        let config = FreezeConfig {
+            current_session_only: false,
+            ..FreezeConfig::default()
+        };
+        let mut engine = FreezeEngine::new(
+            enumerator,
+            MockController::new(),
+            DefaultCategorizer::new(),
+            config,
+        );
+
+        assert_eq!(engine.find_safe_to_freeze().unwrap().len(), 2);
     }
 }
@@ -3,12 +3,22 @@
 //! This library provides the core functionality for detecting and freezing
 //! background processes to optimize system performance during gaming.
 
+pub mod audit;
 pub mod categorization;
 pub mod cli;
+pub mod config;
 pub mod freeze_engine;
+pub mod logging;
 pub mod output;
 pub mod persistence;
 pub mod process;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod timeout;
+pub mod trace;
+
+#[cfg(feature = "tui")]
+pub mod tui;
 
 #[cfg(windows)]
 pub mod windows;
@@ -38,6 +48,15 @@ pub enum SmartFreezeError {
     #[error("Process not found: {0}")]
     ProcessNotFound(u32),
 
+    #[error("Process {pid} is already frozen")]
+    AlreadyFrozen { pid: u32 },
+
+    #[error("Access denied to process {pid}")]
+    AccessDenied { pid: u32 },
+
+    #[error("{call} failed with Windows error {code}")]
+    Win32 { call: &'static str, code: u32 },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -46,4 +65,80 @@ pub enum SmartFreezeError {
 
     #[error("Registry error: {0}")]
     Registry(String),
+
+    #[error("Freeze mode '{}' is not supported by this controller", .0.as_str())]
+    UnsupportedMode(freeze_engine::FreezeMode),
+
+    #[error("Operation '{0}' is not supported by this controller")]
+    UnsupportedOperation(&'static str),
+
+    #[error("Task scheduler error: {0}")]
+    TaskScheduler(String),
+
+    #[error("Another SmartFreeze daemon is already running (lock file: {})", .0.display())]
+    AlreadyRunning(std::path::PathBuf),
+
+    #[error("State file version {found} is newer than supported version {supported}")]
+    StateVersion { found: u32, supported: u32 },
+
+    #[error("Process {pid} is protected: {reason}")]
+    ProtectedProcess { pid: u32, reason: String },
+
+    #[error("Invalid hotkey '{hotkey}': {reason}")]
+    InvalidHotkey {
+        hotkey: String,
+        reason: &'static str,
+    },
+
+    #[error("Config file error: {0}")]
+    Config(#[from] toml::de::Error),
+
+    #[error("{operation} of process {pid} timed out after {timeout_ms} ms")]
+    TimedOut {
+        pid: u32,
+        operation: &'static str,
+        timeout_ms: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win32_error_display() {
+        let error = SmartFreezeError::Win32 {
+            call: "CreateToolhelp32Snapshot",
+            code: 8,
+        };
+        assert_eq!(
+            error.to_string(),
+            "CreateToolhelp32Snapshot failed with Windows error 8"
+        );
+    }
+
+    #[test]
+    fn test_freeze_failed_display_includes_reason() {
+        let error = SmartFreezeError::FreezeFailed {
+            pid: 42,
+            reason: "EmptyWorkingSet failed (error code 5)".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to freeze process 42: EmptyWorkingSet failed (error code 5)"
+        );
+    }
+
+    #[test]
+    fn test_timed_out_display() {
+        let error = SmartFreezeError::TimedOut {
+            pid: 42,
+            operation: "Freeze",
+            timeout_ms: 500,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Freeze of process 42 timed out after 500 ms"
+        );
+    }
 }
@@ -0,0 +1,249 @@
+//! Daemon logging to the console and a size-rotated log file
+
+use crate::Result;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable holding the log level (`error` .. `trace`)
+pub const LOG_LEVEL_ENV: &str = "SMARTFREEZE_LOG";
+/// Rotate once the log would grow past this size
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated logs kept (`daemon.log.1` .. `daemon.log.3`)
+pub const KEEP_LOGS: usize = 3;
+
+/// `%LOCALAPPDATA%\SmartFreeze\daemon.log`, or the temp dir if LOCALAPPDATA is unset
+pub fn default_log_path() -> PathBuf {
+    let mut path = match std::env::var_os("LOCALAPPDATA") {
+        Some(local) => PathBuf::from(local).join("SmartFreeze"),
+        None => std::env::temp_dir(),
+    };
+    path.push("daemon.log");
+    path
+}
+
+/// Level from `SMARTFREEZE_LOG`, defaulting to `info`
+pub fn level_from_env() -> LevelFilter {
+    std::env::var(LOG_LEVEL_ENV)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(LevelFilter::Info)
+}
+
+/// Path of the `index`-th rotated log (`daemon.log.1`, ...)
+pub(crate) fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+/// Shift `path` to `path.1`, `path.1` to `path.2`, ..., dropping anything past `keep`
+pub fn rotate_logs(path: &Path, keep: usize) -> Result<()> {
+    if keep == 0 {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    let oldest = rotated_path(path, keep);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..keep).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    if path.exists() {
+        fs::rename(path, rotated_path(path, 1))?;
+    }
+    Ok(())
+}
+
+/// Whether appending `incoming` bytes to a log of `current` bytes would pass `max`
+pub(crate) fn needs_rotation(current: u64, incoming: u64, max: u64) -> bool {
+    current > 0 && current + incoming > max
+}
+
+/// Open log file and the number of bytes already in it
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+/// `log` backend mirroring records to the console and a rotating file
+pub struct RotatingFileLogger {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    level: LevelFilter,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl RotatingFileLogger {
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize, level: LevelFilter) -> Self {
+        Self {
+            path,
+            max_bytes,
+            keep,
+            level,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Install as the global logger at the default path and env-controlled level
+    pub fn init_default() -> std::result::Result<(), log::SetLoggerError> {
+        let level = level_from_env();
+        let logger = Self::new(default_log_path(), MAX_LOG_BYTES, KEEP_LOGS, level);
+        log::set_boxed_logger(Box::new(logger))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    fn open(&self) -> Result<LogFile> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok(LogFile { file, size })
+    }
+
+    /// Append a line, rotating first if it would push the file past the cap
+    fn write_line(&self, line: &str) -> Result<()> {
+        let mut guard = self.file.lock().unwrap_or_else(|e| e.into_inner());
+
+        let incoming = line.len() as u64;
+        if let Some(current) = guard.as_ref() {
+            if needs_rotation(current.size, incoming, self.max_bytes) {
+                *guard = None;
+                rotate_logs(&self.path, self.keep)?;
+            }
+        }
+
+        let log_file = match guard.as_mut() {
+            Some(log_file) => log_file,
+            None => guard.insert(self.open()?),
+        };
+        log_file.file.write_all(line.as_bytes())?;
+        log_file.size += incoming;
+        Ok(())
+    }
+}
+
+impl Log for RotatingFileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error | Level::Warn => eprintln!("[SmartFreeze] {}", record.args()),
+            _ => println!("[SmartFreeze] {}", record.args()),
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let line = format!("{} {:<5} {}\n", timestamp, record.level(), record.args());
+        // Logging must never take the daemon down; the console copy is still shown
+        let _ = self.write_line(&line);
+    }
+
+    fn flush(&self) {
+        if let Some(log_file) = self.file.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            let _ = log_file.file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "smartfreeze_log_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_needs_rotation() {
+        assert!(!needs_rotation(0, 100, 50)); // An empty file always takes the line
+        assert!(!needs_rotation(40, 10, 50));
+        assert!(needs_rotation(41, 10, 50));
+    }
+
+    #[test]
+    fn test_rotate_logs_shifts_and_prunes() {
+        let dir = test_dir("rotate");
+        let path = dir.join("daemon.log");
+
+        fs::write(&path, "current").unwrap();
+        fs::write(rotated_path(&path, 1), "one").unwrap();
+        fs::write(rotated_path(&path, 2), "two").unwrap();
+        fs::write(rotated_path(&path, 3), "three").unwrap();
+
+        rotate_logs(&path, 3).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "current"
+        );
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "one");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 3)).unwrap(), "two");
+        assert!(!rotated_path(&path, 4).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_logger_rotates_at_size_cap() {
+        let dir = test_dir("logger");
+        let path = dir.join("daemon.log");
+        let logger = RotatingFileLogger::new(path.clone(), 20, 2, LevelFilter::Info);
+
+        for line in [
+            "aaaaaaaaaa\n",
+            "bbbbbbbbbb\n",
+            "cccccccccc\n",
+            "dddddddddd\n",
+        ] {
+            logger.write_line(line).unwrap();
+        }
+        logger.flush();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "dddddddddd\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "cccccccccc\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "bbbbbbbbbb\n"
+        );
+        // "aaaa" was pruned past `keep`
+        assert!(!rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,15 +1,47 @@
 //! SmartFreeze - Main entry point
 
-use clap::Parser;
-use smart_freeze::categorization::DefaultCategorizer;
-use smart_freeze::cli::{Action, Args};
-use smart_freeze::freeze_engine::{FreezeConfig, FreezeEngine};
+use smart_freeze::cli::{resolve_state_location, Args, EXIT_STATE_UNREADABLE};
 
 #[cfg(windows)]
-use smart_freeze::windows::{WindowsProcessController, WindowsProcessEnumerator, WindowsRegistry};
+use smart_freeze::audit::AuditLogger;
+#[cfg(windows)]
+use smart_freeze::categorization::{ConfigurableCategorizer, DefaultCategorizer};
+#[cfg(windows)]
+use smart_freeze::cli::{
+    batch_exit_code, exit_code_for, Action, EXIT_INVALID_ARGS, EXIT_NOT_FOUND, EXIT_SUCCESS,
+};
+#[cfg(windows)]
+use smart_freeze::config::Config;
+#[cfg(windows)]
+use smart_freeze::freeze_engine::{FreezeEngine, FreezeMode};
+#[cfg(windows)]
+use smart_freeze::windows::{
+    ScheduledTaskRegistrar, StartupOptions, WindowsProcessController, WindowsProcessEnumerator,
+    WindowsRegistry,
+};
 
 fn main() {
-    let args = Args::parse();
+    let args = Args::parse_with_config();
+
+    // Read-only and platform independent, so handled before the Windows-only paths
+    if args.list_frozen {
+        handle_list_frozen(&args);
+        return;
+    }
+
+    if let Some(pid) = args.is_frozen {
+        handle_is_frozen(pid, &args);
+    }
+
+    if let Some(count) = args.history {
+        handle_history(count);
+        return;
+    }
+
+    if args.demo {
+        handle_demo(&args);
+        return;
+    }
 
     #[cfg(windows)]
     {
@@ -20,7 +52,22 @@ fn main() {
         }
 
         if args.uninstall_startup {
-            handle_uninstall_startup();
+            handle_uninstall_startup(&args);
+            return;
+        }
+
+        if args.install_task {
+            handle_install_task(&args);
+            return;
+        }
+
+        if args.uninstall_task {
+            handle_uninstall_task();
+            return;
+        }
+
+        if args.resume_all {
+            handle_resume_all(&args);
             return;
         }
 
@@ -35,38 +82,48 @@ fn main() {
             );
             println!("System tray icon should appear in taskbar\n");
 
-            smart_freeze::daemon::run_daemon(
-                args.interval,
-                args.threshold,
-                args.keep_communication,
-            );
+            smart_freeze::daemon::run_daemon(Config::from_args(&args));
             return;
         }
 
         // Handle manual freeze/resume actions
         if let Some(action) = args.action {
             if let Some(pid) = args.pid {
-                handle_action(action, pid);
+                handle_action(action, pid, &args);
+                return;
+            } else if let Some(name) = &args.name {
+                handle_action_by_name(action, name, &args);
                 return;
             } else {
-                eprintln!("Error: --pid is required when using --action");
-                std::process::exit(1);
+                eprintln!("Error: --pid or --name is required when using --action");
+                std::process::exit(EXIT_INVALID_ARGS);
             }
         }
 
+        if let Some(target) = &args.explain {
+            handle_explain(target, &args);
+            return;
+        }
+
+        if args.interactive {
+            handle_interactive(&args);
+            return;
+        }
+
         // Default: Show process information
         run_output_mode(&args);
     }
 
     #[cfg(not(windows))]
     {
+        let _ = args;
         eprintln!("SmartFreeze is only supported on Windows");
         std::process::exit(1);
     }
 }
 
 #[cfg(windows)]
-fn handle_install_startup(_args: &Args) {
+fn handle_install_startup(args: &Args) {
     let registry = WindowsRegistry::new();
     let exe_path = std::env::current_exe()
         .expect("Failed to get executable path")
@@ -74,10 +131,32 @@ fn handle_install_startup(_args: &Args) {
         .expect("Invalid executable path")
         .to_string();
 
-    match registry.install_startup(&exe_path) {
-        Ok(()) => {
-            println!("✓ SmartFreeze installed to Windows startup");
+    let options = StartupOptions::from_args(args);
+    let command = options.command_line(&exe_path);
+    let result = if args.all_users {
+        registry
+            .install_startup_all_users(&exe_path, &options)
+            .and_then(|()| registry.read_startup_command_all_users())
+    } else {
+        registry
+            .install_startup(&exe_path, &options)
+            .and_then(|()| registry.read_startup_command())
+    };
+
+    match result {
+        Ok(Some(stored)) if stored == command => {
+            println!(
+                "✓ SmartFreeze installed to Windows startup{}",
+                if args.all_users { " (all users)" } else { "" }
+            );
             println!("  It will auto-start in daemon mode on next boot");
+            println!("  Command: {}", stored);
+        }
+        Ok(stored) => {
+            eprintln!("✗ Startup entry did not persist as written");
+            eprintln!("  Expected: {}", command);
+            eprintln!("  Found:    {}", stored.as_deref().unwrap_or("(none)"));
+            std::process::exit(1);
         }
         Err(e) => {
             eprintln!("✗ Failed to install to startup: {}", e);
@@ -87,74 +166,586 @@ fn handle_install_startup(_args: &Args) {
 }
 
 #[cfg(windows)]
-fn handle_uninstall_startup() {
+fn handle_uninstall_startup(args: &Args) {
+    use smart_freeze::persistence::uninstall_and_resume;
+
     let registry = WindowsRegistry::new();
+    let persistence = resolve_state_location(args).open();
+    let controller = WindowsProcessController::new();
+
+    let uninstall = || {
+        if args.all_users {
+            registry.uninstall_startup_all_users()
+        } else {
+            registry.uninstall_startup()
+        }
+    };
 
-    match registry.uninstall_startup() {
+    match uninstall_and_resume(uninstall, persistence.as_ref(), &controller) {
+        Ok(report) => {
+            println!(
+                "✓ SmartFreeze removed from Windows startup{}",
+                if args.all_users { " (all users)" } else { "" }
+            );
+            let Some(report) = report else {
+                return;
+            };
+            for (pid, reason) in &report.failed {
+                eprintln!("✗ Failed to resume process {}: {}", pid, reason);
+            }
+            for (pid, reason) in &report.skipped {
+                println!("  Skipped process {}: {}", pid, reason);
+            }
+            if !report.resumed.is_empty() || !report.failed.is_empty() {
+                println!(
+                    "✓ Resumed {} processes left frozen ({} failed), saved state cleared",
+                    report.resumed.len(),
+                    report.failed.len()
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!(
+                "✗ Failed to uninstall from startup or clear saved state: {}",
+                e
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(windows)]
+fn handle_install_task(args: &Args) {
+    let registrar = ScheduledTaskRegistrar::new();
+    let exe_path = std::env::current_exe()
+        .expect("Failed to get executable path")
+        .to_str()
+        .expect("Invalid executable path")
+        .to_string();
+
+    match registrar.register(&exe_path, &StartupOptions::from_args(args)) {
         Ok(()) => {
-            println!("✓ SmartFreeze removed from Windows startup");
+            println!("✓ SmartFreeze registered as an elevated logon task");
+            println!("  It will auto-start in daemon mode with admin rights at next logon");
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to register logon task: {}", e);
+            std::process::exit(1);
         }
+    }
+}
+
+#[cfg(windows)]
+fn handle_uninstall_task() {
+    match ScheduledTaskRegistrar::new().unregister() {
+        Ok(()) => println!("✓ SmartFreeze logon task removed"),
         Err(e) => {
-            eprintln!("✗ Failed to uninstall from startup: {}", e);
+            eprintln!("✗ Failed to remove logon task: {}", e);
             std::process::exit(1);
         }
     }
 }
 
+fn handle_list_frozen(args: &Args) {
+    let persistence = resolve_state_location(args).open();
+
+    let state = match persistence.load() {
+        Ok(Some(state)) if !state.is_empty() => state,
+        Ok(_) => {
+            println!("No frozen processes recorded");
+            return;
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to read saved state: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!(
+        "{:<8} {:<30} {:>10} {:<6} Path",
+        "PID", "Name", "Age (s)", "Stale"
+    );
+    println!("{}", "-".repeat(80));
+    for frozen in &state.frozen_processes {
+        println!(
+            "{:<8} {:<30} {:>10} {:<6} {}",
+            frozen.pid,
+            frozen.name,
+            frozen.age_secs(),
+            if frozen.is_stale() { "yes" } else { "no" },
+            frozen.exe_path
+        );
+    }
+}
+
+fn handle_is_frozen(pid: u32, args: &Args) -> ! {
+    match resolve_state_location(args).open().contains(pid) {
+        Ok(true) => {
+            println!("yes");
+            std::process::exit(0);
+        }
+        Ok(false) => {
+            println!("no");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to read saved state: {}", e);
+            std::process::exit(EXIT_STATE_UNREADABLE);
+        }
+    }
+}
+
+fn handle_history(count: usize) {
+    use smart_freeze::audit::{format_history, AuditLogger};
+
+    match AuditLogger::with_default_path().history(count) {
+        Ok(entries) if entries.is_empty() => {
+            println!("No freeze or resume events recorded yet");
+        }
+        Ok(entries) => print!("{}", format_history(&entries)),
+        Err(e) => {
+            eprintln!("✗ Failed to read audit log: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run the output path over canned processes with a controller that freezes nothing
+#[cfg(feature = "testing")]
+fn handle_demo(args: &Args) {
+    use smart_freeze::categorization::DefaultCategorizer;
+    use smart_freeze::config::Config;
+    use smart_freeze::testing::{MockProcessController, MockProcessEnumerator};
+
+    let mut engine = smart_freeze::FreezeEngine::new(
+        MockProcessEnumerator::demo(),
+        MockProcessController::new(),
+        DefaultCategorizer::new(),
+        Config::from_args(args).freeze,
+    );
+    smart_freeze::output::run(&mut engine, args);
+}
+
+#[cfg(not(feature = "testing"))]
+fn handle_demo(_args: &Args) {
+    eprintln!("✗ --demo needs a build with the `testing` feature (cargo build --features testing)");
+    std::process::exit(1);
+}
+
+#[cfg(windows)]
+fn handle_resume_all(args: &Args) {
+    use smart_freeze::persistence::resume_all_from_state;
+
+    let persistence = resolve_state_location(args).open();
+    let controller = WindowsProcessController::new();
+
+    match resume_all_from_state(persistence.as_ref(), &controller) {
+        Ok(None) => {
+            println!("Nothing to resume (no saved state)");
+            std::process::exit(EXIT_NOT_FOUND);
+        }
+        Ok(Some(report)) if report.resumed.is_empty() && report.failed.is_empty() => {
+            for (pid, reason) in &report.skipped {
+                println!("  Skipped process {}: {}", pid, reason);
+            }
+            if report.skipped.is_empty() {
+                println!("Nothing to resume (saved state is empty)");
+            } else {
+                println!("Nothing left to resume; saved state cleared");
+            }
+            std::process::exit(EXIT_NOT_FOUND);
+        }
+        Ok(Some(report)) => {
+            for (pid, error) in &report.failed {
+                eprintln!("✗ Failed to resume process {}: {}", pid, error);
+                print_elevation_hint(error);
+            }
+            for (pid, reason) in &report.skipped {
+                println!("  Skipped process {}: {}", pid, reason);
+            }
+            println!(
+                "✓ Resumed {} processes ({} failed)",
+                report.resumed.len(),
+                report.failed.len()
+            );
+            let errors: Vec<_> = report.failed.into_iter().map(|(_, e)| e).collect();
+            let code = batch_exit_code(report.resumed.len(), &errors);
+            if code != EXIT_SUCCESS {
+                std::process::exit(code);
+            }
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to read saved state: {}", e);
+            std::process::exit(exit_code_for(&e));
+        }
+    }
+}
+
 #[cfg(windows)]
-fn handle_action(action: Action, pid: u32) {
+fn handle_action(action: Action, pid: u32, args: &Args) {
     use smart_freeze::freeze_engine::ProcessController;
 
     let controller = WindowsProcessController::new();
 
+    if let Some(mode) = args.mode {
+        if !controller.supports_mode(mode) {
+            eprintln!(
+                "✗ Freeze mode '{}' is not supported on this system",
+                mode.as_str()
+            );
+            std::process::exit(EXIT_INVALID_ARGS);
+        }
+    }
+
+    // Not even --force: a suspended SmartFreeze could never resume anything
+    if action == Action::Freeze && pid == std::process::id() {
+        eprintln!("✗ Refusing to freeze SmartFreeze itself (PID {})", pid);
+        std::process::exit(EXIT_INVALID_ARGS);
+    }
+
+    let mut engine = create_engine(&Config::from_args(args));
+    engine.set_audit(AuditLogger::with_default_path());
+
     match action {
-        Action::Freeze => match controller.freeze(pid) {
+        Action::Freeze => match engine.freeze_guarded(pid, args.force) {
             Ok(count) => {
                 println!("✓ Froze process {} ({} threads suspended)", pid, count);
+                save_manual_freezes(&engine, &[pid], args);
+            }
+            Err(smart_freeze::SmartFreezeError::AlreadyFrozen { .. }) => {
+                println!("✓ Process {} is already frozen", pid);
+            }
+            Err(e @ smart_freeze::SmartFreezeError::ProtectedProcess { .. }) => {
+                eprintln!("✗ {}", e);
+                eprintln!("  Pass --force to freeze it anyway");
+                std::process::exit(exit_code_for(&e));
             }
             Err(e) => {
                 eprintln!("✗ Failed to freeze process {}: {}", pid, e);
-                std::process::exit(1);
+                print_elevation_hint(&e);
+                std::process::exit(exit_code_for(&e));
             }
         },
-        Action::Resume => match controller.resume(pid) {
+        Action::Resume => match resume_saved(&engine, &[pid], args).remove(0).1 {
+            // Suspend-mode resumes only undo freezes SmartFreeze recorded
+            Ok(0) if matches!(engine.config().mode, None | Some(FreezeMode::Suspend)) => {
+                println!(
+                    "✓ Nothing to resume: process {} has no freeze recorded",
+                    pid
+                );
+            }
             Ok(count) => {
                 println!("✓ Resumed process {} ({} threads resumed)", pid, count);
             }
             Err(e) => {
                 eprintln!("✗ Failed to resume process {}: {}", pid, e);
+                std::process::exit(exit_code_for(&e));
+            }
+        },
+    }
+}
+
+/// Point out that access-denied failures go away when run elevated
+#[cfg(windows)]
+fn print_elevation_hint(error: &smart_freeze::SmartFreezeError) {
+    if matches!(error, smart_freeze::SmartFreezeError::AccessDenied { .. }) {
+        eprintln!("  Hint: the process is elevated or protected; run SmartFreeze as administrator");
+    }
+}
+
+#[cfg(windows)]
+fn handle_action_by_name(action: Action, name: &str, args: &Args) {
+    use smart_freeze::process::ProcessCategory;
+
+    let mut engine = create_engine(&Config::from_args(args));
+    engine.set_audit(AuditLogger::with_default_path());
+
+    if let Err(e) = engine.validate_mode() {
+        eprintln!("✗ {}", e);
+        std::process::exit(exit_code_for(&e));
+    }
+
+    let matches = match engine.find_by_name(name) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("✗ Failed to enumerate processes: {}", e);
+            std::process::exit(exit_code_for(&e));
+        }
+    };
+
+    if matches.is_empty() {
+        eprintln!("✗ No processes match '{}'", name);
+        std::process::exit(EXIT_NOT_FOUND);
+    }
+
+    let mut pids = Vec::new();
+    for process in &matches {
+        // Resuming is always safe; freezing these would hang the desktop, the user's app or us
+        let protected = process.category == ProcessCategory::Critical
+            || process.is_foreground
+            || engine.is_self_or_host(process.pid);
+        if action == Action::Freeze && protected {
+            eprintln!(
+                "⚠ Skipping {} (PID {}): {}",
+                process.name,
+                process.pid,
+                if engine.is_self_or_host(process.pid) {
+                    "SmartFreeze itself or its console"
+                } else if process.is_foreground {
+                    "foreground"
+                } else {
+                    "critical system process"
+                }
+            );
+        } else {
+            pids.push(process.pid);
+        }
+    }
+    if pids.is_empty() {
+        eprintln!("✗ Every process matching '{}' is protected", name);
+        std::process::exit(EXIT_INVALID_ARGS);
+    }
+
+    let (verb, done, results) = match action {
+        Action::Freeze => {
+            let results = engine.freeze_multiple(&pids);
+            let frozen: Vec<u32> = results
+                .iter()
+                .filter(|(_, result)| result.is_ok())
+                .map(|&(pid, _)| pid)
+                .collect();
+            save_manual_freezes(&engine, &frozen, args);
+            ("freeze", "Froze", results)
+        }
+        Action::Resume => ("resume", "Resumed", resume_saved(&engine, &pids, args)),
+    };
+
+    let mut succeeded = 0;
+    let mut errors = Vec::new();
+    for (pid, result) in results {
+        let name = matches
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.name.as_str())
+            .unwrap_or("?");
+        match result {
+            Ok(count) => {
+                println!("✓ {} {} (PID {}, {} threads)", done, name, pid, count);
+                succeeded += 1;
+            }
+            Err(e) => {
+                eprintln!("✗ Failed to {} {} (PID {}): {}", verb, name, pid, e);
+                print_elevation_hint(&e);
+                errors.push(e);
+            }
+        }
+    }
+
+    let code = batch_exit_code(succeeded, &errors);
+    if code != EXIT_SUCCESS {
+        std::process::exit(code);
+    }
+}
+
+/// Save what manual freezes changed, so a later `--action resume` undoes exactly that
+#[cfg(windows)]
+fn save_manual_freezes(engine: &WindowsEngine, pids: &[u32], args: &Args) {
+    use smart_freeze::persistence::record_manual_freezes;
+
+    let persistence = resolve_state_location(args).open();
+    if let Err(e) = record_manual_freezes(persistence.as_ref(), engine, pids) {
+        eprintln!("⚠ Failed to save state: {}", e);
+        eprintln!("  --action resume from another run won't be able to resume it");
+    }
+}
+
+/// Resume `pids` with the freeze records saved for them, then drop them from
+/// the saved state
+#[cfg(windows)]
+fn resume_saved(
+    engine: &WindowsEngine,
+    pids: &[u32],
+    args: &Args,
+) -> Vec<(u32, smart_freeze::Result<usize>)> {
+    use smart_freeze::persistence::resume_manual_freezes;
+
+    let persistence = resolve_state_location(args).open();
+    let (results, saved) = resume_manual_freezes(persistence.as_ref(), engine, pids);
+    if let Err(e) = saved {
+        eprintln!("⚠ Failed to update saved state: {}", e);
+    }
+    results
+}
+
+/// Engine over the live system; exits if the rules file can't be loaded
+#[cfg(windows)]
+fn create_engine(config: &Config) -> WindowsEngine {
+    FreezeEngine::from_config(config).unwrap_or_else(|e| {
+        let path = config.rules_path.clone().unwrap_or_default();
+        eprintln!("Error loading rules from {}: {}", path.display(), e);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(windows)]
+fn create_enumerator(args: &Args) -> WindowsProcessEnumerator {
+    let mut enumerator = match &args.rules {
+        Some(path) => match ConfigurableCategorizer::from_file(path) {
+            Ok(categorizer) => WindowsProcessEnumerator::with_categorizer(Box::new(categorizer)),
+            Err(e) => {
+                eprintln!("Error loading rules from {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => WindowsProcessEnumerator::new(),
+    };
+    enumerator.set_detect_suspended(args.detect_suspended);
+    enumerator.set_show_inaccessible(args.show_inaccessible);
+    enumerator
+}
+
+#[cfg(windows)]
+fn handle_explain(target: &str, args: &Args) {
+    // Rule matches come from the same rules the enumerator categorized with
+    match &args.rules {
+        Some(path) => match ConfigurableCategorizer::from_file(path) {
+            Ok(categorizer) => print_explanations(target, args, categorizer),
+            Err(e) => {
+                eprintln!("Error loading rules from {}: {}", path.display(), e);
                 std::process::exit(1);
             }
         },
+        None => print_explanations(target, args, DefaultCategorizer::new()),
     }
 }
 
+#[cfg(windows)]
+fn print_explanations<Cat: smart_freeze::ProcessCategorizer>(
+    target: &str,
+    args: &Args,
+    categorizer: Cat,
+) {
+    let mut engine = FreezeEngine::new(
+        create_enumerator(args),
+        WindowsProcessController::new(),
+        categorizer,
+        Config::from_args(args).freeze,
+    );
+
+    match engine.explain(target) {
+        Ok(explained) if explained.is_empty() => {
+            eprintln!("✗ No process matches '{}'", target);
+            std::process::exit(EXIT_NOT_FOUND);
+        }
+        Ok(explained) => {
+            let reports: Vec<String> = explained
+                .iter()
+                .map(smart_freeze::output::format_explanation)
+                .collect();
+            print!("{}", reports.join("\n"));
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to enumerate processes: {}", e);
+            std::process::exit(exit_code_for(&e));
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "tui"))]
+fn handle_interactive(args: &Args) {
+    let mut config = Config::from_args(args);
+    // Marks processes frozen by other tools or earlier runs
+    config.detect_suspended = true;
+    let mut engine = create_engine(&config);
+
+    let persistence = resolve_state_location(args).open();
+    let refresh = std::time::Duration::from_secs(args.interval);
+    if let Err(e) = smart_freeze::tui::run(&mut engine, persistence.as_ref(), refresh) {
+        eprintln!("✗ Interactive mode failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(all(windows, not(feature = "tui")))]
+fn handle_interactive(_args: &Args) {
+    eprintln!("✗ --interactive needs a build with the `tui` feature (cargo build --features tui)");
+    std::process::exit(1);
+}
+
 #[cfg(windows)]
 fn run_output_mode(args: &Args) {
-    // Create engine with Windows implementations
-    let enumerator = WindowsProcessEnumerator::new();
-    let controller = WindowsProcessController::new();
-    let categorizer = DefaultCategorizer::new();
+    let mut engine = create_engine(&Config::from_args(args));
 
-    let config = FreezeConfig {
-        min_memory_mb: args.threshold,
-        keep_communication: args.keep_communication,
-    };
+    if let Err(e) = engine.validate_mode() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
-    let mut engine = FreezeEngine::new(enumerator, controller, categorizer, config);
+    if !args.watch {
+        render_output(&mut engine, args);
+    } else if !smart_freeze::output::supports_watch(args.format) {
+        eprintln!("Warning: --watch only applies to table and markdown output; printing once");
+        render_output(&mut engine, args);
+    } else {
+        watch_output(&mut engine, args);
+    }
+}
+
+#[cfg(windows)]
+type WindowsEngine =
+    FreezeEngine<WindowsProcessEnumerator, WindowsProcessController, DefaultCategorizer>;
+
+/// Re-render every `--interval` seconds until Ctrl+C
+#[cfg(windows)]
+fn watch_output(engine: &mut WindowsEngine, args: &Args) {
+    use smart_freeze::output::{CLEAR_SCREEN, HIDE_CURSOR};
+    use std::io::Write;
+    use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+    // The default handler still terminates us after the cursor is restored
+    unsafe {
+        SetConsoleCtrlHandler(Some(restore_cursor), 1);
+    }
+    print!("{}", HIDE_CURSOR);
+
+    loop {
+        print!("{}", CLEAR_SCREEN);
+        render_output(engine, args);
+        println!("\nRefreshing every {}s (Ctrl+C to stop)", args.interval);
+        let _ = std::io::stdout().flush();
+
+        std::thread::sleep(std::time::Duration::from_secs(args.interval));
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn restore_cursor(_ctrl_type: u32) -> windows_sys::Win32::Foundation::BOOL {
+    use std::io::Write;
+
+    print!("{}", smart_freeze::output::SHOW_CURSOR);
+    let _ = std::io::stdout().flush();
+    0
+}
+
+/// Enumerate once and print in the selected format
+#[cfg(windows)]
+fn render_output(engine: &mut WindowsEngine, args: &Args) {
+    match smart_freeze::output::collect_processes(engine, args) {
+        Ok(listed) => {
+            let safe_count = if args.all {
+                listed.iter().filter(|p| p.freezable == Some(true)).count()
+            } else {
+                listed.len()
+            };
 
-    // Get processes
-    match engine.find_safe_to_freeze() {
-        Ok(safe_processes) => {
             // Use output formatter
             use smart_freeze::output::{
-                CsvFormatter, JsonFormatter, OutputFormatter, TableFormatter,
+                CsvFormatter, JsonFormatter, MarkdownFormatter, NdjsonFormatter, OutputFormatter,
+                PrometheusFormatter, TableFormatter,
             };
 
             match args.format {
                 smart_freeze::cli::OutputFormat::Table => {
-                    let _formatter = TableFormatter;
-
                     // Enhanced table output with protected processes
                     println!("Smart Freeze Engine - Dry Run Mode");
                     println!("===================================\n");
@@ -165,48 +756,16 @@ fn run_output_mode(args: &Args) {
 
                     println!("🎯 DRY RUN - Showing what would happen in daemon mode:\n");
 
-                    if !safe_processes.is_empty() {
-                        println!(
-                            "❄️  WOULD FREEZE ({} processes, >{} MB):",
-                            safe_processes.len(),
-                            args.threshold
-                        );
-                        println!("{}", "=".repeat(70));
-                        println!(
-                            "{:<8} {:<40} {:>12} {:<10}",
-                            "PID", "Name", "Memory (MB)", "Category"
-                        );
-                        println!("{}", "-".repeat(70));
-
-                        for process in &safe_processes {
-                            println!(
-                                "{:<8} {:<40} {:>12} {:<10}",
-                                process.pid,
-                                process.name,
-                                process.memory_mb,
-                                process.category.as_str()
-                            );
-                        }
+                    print!("{}", TableFormatter.render_listing(&listed, args));
 
-                        println!(
-                            "\n   Total memory to free: {} MB",
-                            safe_processes.iter().map(|p| p.memory_mb).sum::<u64>()
-                        );
-                    } else {
-                        println!("❄️  WOULD FREEZE: None (no processes match criteria)");
-                    }
-
-                    // Show protected processes
-                    if let Ok(all_processes) = engine.enumerate_processes() {
-                        use smart_freeze::process::ProcessCategory;
+                    // Show protected processes (small ones are omitted as noise)
+                    if let Ok(plan) = engine.plan() {
+                        use smart_freeze::freeze_engine::ProtectReason;
 
-                        let protected: Vec<_> = all_processes
+                        let protected: Vec<_> = plan
+                            .protected
                             .iter()
-                            .filter(|p| {
-                                p.is_foreground
-                                    || p.category == ProcessCategory::Critical
-                                    || p.category == ProcessCategory::Gaming
-                            })
+                            .filter(|(_, reason)| *reason != ProtectReason::BelowThreshold)
                             .collect();
 
                         if !protected.is_empty() {
@@ -218,20 +777,13 @@ fn run_output_mode(args: &Args) {
                             );
                             println!("{}", "-".repeat(70));
 
-                            for process in protected.iter().take(20) {
-                                let reason = if process.is_foreground {
-                                    "Foreground"
-                                } else if process.category == ProcessCategory::Critical {
-                                    "Critical"
-                                } else if process.category == ProcessCategory::Gaming {
-                                    "Gaming"
-                                } else {
-                                    "Unknown"
-                                };
-
+                            for (process, reason) in protected.iter().take(20) {
                                 println!(
                                     "{:<8} {:<40} {:>12} {:<10}",
-                                    process.pid, process.name, process.memory_mb, reason
+                                    process.pid,
+                                    process.name,
+                                    process.memory_mb,
+                                    reason.as_str()
                                 );
                             }
 
@@ -244,7 +796,7 @@ fn run_output_mode(args: &Args) {
 
                             println!(
                                 "\n   Total protected memory: {} MB",
-                                protected.iter().map(|p| p.memory_mb).sum::<u64>()
+                                protected.iter().map(|(p, _)| p.memory_mb).sum::<u64>()
                             );
                         }
                     }
@@ -254,19 +806,38 @@ fn run_output_mode(args: &Args) {
                     if let Ok(all) = engine.enumerate_processes() {
                         println!("   Total processes running: {}", all.len());
                     }
-                    println!("   Would freeze: {} processes", safe_processes.len());
+                    println!("   Would freeze: {} processes", safe_count);
                     println!("   Memory threshold: {} MB", args.threshold);
                     println!("\n💡 This is a DRY RUN. To actually freeze processes, use:");
                     println!("   --action freeze --pid <PID>  (manual)");
                     println!("   --daemon                     (automatic when gaming)");
                 }
+                smart_freeze::cli::OutputFormat::Json if !args.json_legacy => match engine.plan() {
+                    Ok(plan) => JsonFormatter.format_plan(&plan, args),
+                    Err(e) => {
+                        eprintln!("Error building freeze plan: {}", e);
+                        std::process::exit(1);
+                    }
+                },
                 smart_freeze::cli::OutputFormat::Json => {
                     let formatter = JsonFormatter;
-                    formatter.format_processes(&safe_processes, args);
+                    formatter.format_processes(&listed, args);
                 }
                 smart_freeze::cli::OutputFormat::Csv => {
                     let formatter = CsvFormatter;
-                    formatter.format_processes(&safe_processes, args);
+                    formatter.format_processes(&listed, args);
+                }
+                smart_freeze::cli::OutputFormat::Markdown => {
+                    let formatter = MarkdownFormatter;
+                    formatter.format_processes(&listed, args);
+                }
+                smart_freeze::cli::OutputFormat::Ndjson => {
+                    let formatter = NdjsonFormatter;
+                    formatter.format_processes(&listed, args);
+                }
+                smart_freeze::cli::OutputFormat::Prometheus => {
+                    let formatter = PrometheusFormatter;
+                    formatter.format_processes(&listed, args);
                 }
             }
         }
@@ -1,25 +1,86 @@
 //! CSV output formatting
 
 use crate::cli::Args;
-use crate::output::OutputFormatter;
+use crate::output::{select_for_output, OutputFormatter};
 use crate::process::ProcessInfo;
 
 pub struct CsvFormatter;
 
-impl OutputFormatter for CsvFormatter {
-    fn format_processes(&self, processes: &[ProcessInfo], _args: &Args) {
-        println!("PID,Name,MemoryMB,Category,Foreground,FullPath");
-        for process in processes {
-            println!(
-                "{},{},{},{},{},\"{}\"",
-                process.pid,
-                process.name,
-                process.memory_mb,
-                process.category.as_str(),
-                process.is_foreground,
-                process.full_path
-            );
+const HEADER: &str = "PID,Name,MemoryMB,Category,Foreground,FullPath";
+
+/// Quote a field per RFC 4180 when it contains a delimiter, quote or line break
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Render one process as a CSV record (without trailing newline)
+///
+/// With `with_cpu` a CpuPercent column is added (for `--top-cpu`), with `with_counts`
+/// Threads, Handles and Suspended columns (for `--verbose`), and with `with_freezable`
+/// a trailing Freezable column (for `--all`).
+fn format_row(
+    process: &ProcessInfo,
+    with_cpu: bool,
+    with_counts: bool,
+    with_freezable: bool,
+) -> String {
+    let mut fields = vec![
+        process.pid.to_string(),
+        process.name.clone(),
+        process.memory_mb.to_string(),
+        process.category.as_str().to_string(),
+        process.is_foreground.to_string(),
+        process.full_path.clone(),
+    ];
+    if with_cpu {
+        fields.push(format!("{:.1}", process.cpu_percent));
+    }
+    if with_counts {
+        fields.push(process.thread_count.to_string());
+        fields.push(process.handle_count.to_string());
+        fields.push(process.is_suspended.to_string());
+    }
+    if with_freezable {
+        fields.push(process.freezable.unwrap_or(false).to_string());
+    }
+
+    fields
+        .iter()
+        .map(|field| escape_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl CsvFormatter {
+    /// Render the header and one line per displayed process
+    pub fn render(&self, processes: &[ProcessInfo], args: &Args) -> String {
+        let mut output = String::from(HEADER);
+        if args.top_cpu {
+            output.push_str(",CpuPercent");
+        }
+        if args.verbose {
+            output.push_str(",Threads,Handles,Suspended");
+        }
+        if args.all {
+            output.push_str(",Freezable");
+        }
+        output.push('\n');
+
+        for process in select_for_output(processes, args) {
+            output.push_str(&format_row(&process, args.top_cpu, args.verbose, args.all));
+            output.push('\n');
         }
+        output
+    }
+}
+
+impl OutputFormatter for CsvFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        print!("{}", self.render(processes, args));
     }
 }
 
@@ -30,6 +91,8 @@ mod tests {
 
     #[test]
     fn test_csv_output() {
+        use clap::Parser;
+
         let formatter = CsvFormatter;
         let processes = vec![ProcessInfo::new(
             1234,
@@ -40,22 +103,137 @@ mod tests {
             ProcessCategory::Productivity,
         )];
 
-        let args = Args {
-            threshold: 100,
-            format: crate::cli::OutputFormat::Csv,
-            all: false,
-            top: 10,
-            verbose: false,
-            action: None,
-            pid: None,
-            daemon: false,
-            install_startup: false,
-            uninstall_startup: false,
-            interval: 60,
-            keep_communication: false,
-        };
+        let args = Args::parse_from(["smart-freeze", "--format", "csv"]);
 
         // Should not panic
         formatter.format_processes(&processes, &args);
     }
+
+    /// Minimal RFC 4180 record parser used to check round-tripping
+    fn parse_record(line: &str) -> Vec<String> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, in_quotes) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', _) => in_quotes = !in_quotes,
+                (',', false) => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        fields.push(field);
+        fields
+    }
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("plain.exe"), "plain.exe");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn test_csv_row_round_trips_special_characters() {
+        let process = ProcessInfo::new(
+            42,
+            "weird,\"name\".exe".to_string(),
+            "C:\\Program Files\\weird,\"name\".exe".to_string(),
+            300,
+            true,
+            ProcessCategory::Unknown,
+        );
+
+        let row = format_row(&process, false, false, false);
+        assert_eq!(
+            row,
+            "42,\"weird,\"\"name\"\".exe\",300,Unknown,true,\"C:\\Program Files\\weird,\"\"name\"\".exe\""
+        );
+
+        let fields = parse_record(&row);
+        assert_eq!(fields.len(), HEADER.split(',').count());
+        assert_eq!(fields[1], process.name);
+        assert_eq!(fields[5], process.full_path);
+    }
+
+    #[test]
+    fn test_csv_top_and_all() {
+        use clap::Parser;
+
+        let mut small = ProcessInfo::new(
+            1,
+            "small.exe".to_string(),
+            String::new(),
+            150,
+            false,
+            ProcessCategory::Productivity,
+        );
+        small.freezable = Some(true);
+        let mut big = small.clone();
+        big.pid = 2;
+        big.memory_mb = 800;
+        big.freezable = Some(false);
+
+        let args = Args::parse_from(["smart-freeze", "--all", "--top", "1"]);
+        let output = CsvFormatter.render(&[small, big], &args);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(",Freezable"));
+        assert!(lines[1].starts_with("2,"));
+        assert!(lines[1].ends_with(",false"));
+    }
+
+    #[test]
+    fn test_csv_verbose_adds_counts() {
+        use clap::Parser;
+
+        let mut process = ProcessInfo::new(
+            7,
+            "app.exe".to_string(),
+            String::new(),
+            200,
+            false,
+            ProcessCategory::Productivity,
+        );
+        process.thread_count = 24;
+        process.handle_count = 512;
+        process.is_suspended = true;
+        process.freezable = Some(true);
+
+        let args = Args::parse_from(["smart-freeze", "--verbose", "--all"]);
+        let output = CsvFormatter.render(&[process], &args);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines[0].ends_with(",Threads,Handles,Suspended,Freezable"));
+        assert!(lines[1].ends_with(",24,512,true,true"));
+    }
+
+    #[test]
+    fn test_csv_top_cpu_adds_cpu_column() {
+        use clap::Parser;
+
+        let mut process = ProcessInfo::new(
+            7,
+            "app.exe".to_string(),
+            String::new(),
+            200,
+            false,
+            ProcessCategory::Productivity,
+        );
+        process.cpu_percent = 37.25;
+
+        let args = Args::parse_from(["smart-freeze", "--top-cpu"]);
+        let output = CsvFormatter.render(&[process], &args);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines[0].ends_with(",FullPath,CpuPercent"));
+        assert!(lines[1].ends_with(",37.2"));
+    }
 }
@@ -0,0 +1,139 @@
+//! `--explain` report for a single process
+
+use crate::freeze_engine::{Explanation, ProtectReason};
+use std::fmt::Write as _;
+
+/// Category, matching rules and freeze verdict of one process
+pub fn format_explanation(explanation: &Explanation) -> String {
+    let process = &explanation.process;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "{} (PID {})", process.name, process.pid);
+    if !process.full_path.is_empty() {
+        let _ = writeln!(out, "  Path:      {}", process.full_path);
+    }
+    let _ = writeln!(
+        out,
+        "  Parent:    {} (PID {})",
+        explanation.parent_name.as_deref().unwrap_or("?"),
+        process.parent_pid
+    );
+    let _ = writeln!(
+        out,
+        "  Category:  {} ({})",
+        process.category.as_str(),
+        process
+            .category_reason
+            .as_deref()
+            .unwrap_or("no reason recorded")
+    );
+
+    if let Some(publisher) = &process.publisher {
+        match explanation.publisher_category {
+            Some(category) => {
+                let _ = writeln!(
+                    out,
+                    "  Publisher: {} (implies {})",
+                    publisher,
+                    category.as_str()
+                );
+            }
+            None => {
+                let _ = writeln!(out, "  Publisher: {}", publisher);
+            }
+        }
+    }
+
+    if explanation.matches.is_empty() {
+        let _ = writeln!(out, "  Rules:     none matched the name or path");
+    } else {
+        let _ = writeln!(out, "  Rules (highest precedence first):");
+        for (category, reason) in &explanation.matches {
+            let _ = writeln!(out, "    {:<14} {}", category.as_str(), reason);
+        }
+    }
+
+    let _ = writeln!(out, "  Verdict:   {}", verdict(explanation));
+    out
+}
+
+/// Whether the process would be frozen, and what keeps it running otherwise
+fn verdict(explanation: &Explanation) -> String {
+    let process = &explanation.process;
+    let Some(reason) = explanation.protect_reason else {
+        return format!("would be frozen ({} MB)", process.memory_mb);
+    };
+
+    let detail = match reason {
+        ProtectReason::BelowThreshold => format!(
+            "{} MB is below the {} MB threshold",
+            process.memory_mb, explanation.threshold_mb
+        ),
+        ProtectReason::BelowCpuThreshold => {
+            format!("{:.1}% CPU is below --min-cpu", process.cpu_percent)
+        }
+        ProtectReason::TooYoung => {
+            format!("started {}s ago", process.age_secs.unwrap_or_default())
+        }
+        ProtectReason::Communication => "communication app (--keep-communication)".to_string(),
+        ProtectReason::NeverFreeze => "listed in never_freeze".to_string(),
+        ProtectReason::VisibleWindow => "has a visible window".to_string(),
+        ProtectReason::Inaccessible => "couldn't be opened".to_string(),
+        _ => reason.as_str().to_lowercase(),
+    };
+    format!("kept running: {}", detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::{ProcessCategory, ProcessInfo};
+
+    fn explanation(protect_reason: Option<ProtectReason>) -> Explanation {
+        let mut process = ProcessInfo::new(
+            42,
+            "discord.exe".to_string(),
+            "C:\\Users\\me\\AppData\\Local\\Discord\\discord.exe".to_string(),
+            350,
+            false,
+            ProcessCategory::Communication,
+        );
+        process.parent_pid = 7;
+        process.category_reason = Some("matched name substring 'discord'".to_string());
+
+        Explanation {
+            process,
+            matches: vec![(
+                ProcessCategory::Communication,
+                "matched name substring 'discord'".to_string(),
+            )],
+            publisher_category: None,
+            parent_name: Some("explorer.exe".to_string()),
+            threshold_mb: 100,
+            protect_reason,
+        }
+    }
+
+    #[test]
+    fn test_explanation_shows_rule_and_protection() {
+        let report = format_explanation(&explanation(Some(ProtectReason::Communication)));
+
+        assert!(report.starts_with("discord.exe (PID 42)\n"));
+        assert!(report.contains("  Parent:    explorer.exe (PID 7)\n"));
+        assert!(report.contains("  Category:  Communication (matched name substring 'discord')\n"));
+        assert!(report.contains("Communication  matched name substring 'discord'"));
+        assert!(report
+            .ends_with("  Verdict:   kept running: communication app (--keep-communication)\n"));
+    }
+
+    #[test]
+    fn test_explanation_verdicts() {
+        assert!(format_explanation(&explanation(None))
+            .ends_with("  Verdict:   would be frozen (350 MB)\n"));
+
+        let mut below = explanation(Some(ProtectReason::BelowThreshold));
+        below.threshold_mb = 500;
+        assert!(format_explanation(&below)
+            .ends_with("kept running: 350 MB is below the 500 MB threshold\n"));
+    }
+}
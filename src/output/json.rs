@@ -1,21 +1,98 @@
 //! JSON output formatting
 
 use crate::cli::Args;
-use crate::output::OutputFormatter;
+use crate::freeze_engine::FreezePlan;
+use crate::output::{select_for_output, OutputFormatter};
 use crate::process::ProcessInfo;
-use serde_json::json;
+use serde_json::{json, Map, Value};
 
 pub struct JsonFormatter;
 
-impl OutputFormatter for JsonFormatter {
-    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
-        let output = json!({
+impl JsonFormatter {
+    /// Build the JSON document for the displayed processes
+    ///
+    /// With `--all`, counts and totals only include freezable processes.
+    pub fn to_value(&self, processes: &[ProcessInfo], args: &Args) -> Value {
+        let shown = select_for_output(processes, args);
+        let freezable: Vec<&ProcessInfo> = shown
+            .iter()
+            .filter(|p| !args.all || p.freezable.unwrap_or(false))
+            .collect();
+
+        json!({
             "threshold_mb": args.threshold,
-            "safe_to_freeze_count": processes.len(),
-            "total_memory_mb": processes.iter().map(|p| p.memory_mb).sum::<u64>(),
-            "processes": processes,
-        });
+            "safe_to_freeze_count": freezable.len(),
+            "total_memory_mb": freezable.iter().map(|p| p.memory_mb).sum::<u64>(),
+            "processes": shown,
+            "by_category": Self::by_category(processes),
+        })
+    }
+
+    /// Build the dry-run plan document: what would be frozen, what is kept and why
+    ///
+    /// `--top` limits `would_freeze` as it does the flat listing. Protected
+    /// processes are always listed with their reasons, and the summary covers
+    /// the whole plan.
+    pub fn plan_to_value(&self, plan: &FreezePlan, args: &Args) -> Value {
+        let strip = |process: &ProcessInfo| {
+            let mut process = process.clone();
+            if !args.verbose {
+                process.category_reason = None;
+            }
+            process
+        };
+
+        let would_freeze = select_for_output(&plan.would_freeze, args);
+        let protected: Vec<Value> = plan
+            .protected
+            .iter()
+            .map(|(process, reason)| json!({"process": strip(process), "reason": reason.as_str()}))
+            .collect();
+        let all: Vec<ProcessInfo> = plan
+            .would_freeze
+            .iter()
+            .chain(plan.protected.iter().map(|(process, _)| process))
+            .cloned()
+            .collect();
 
+        json!({
+            "would_freeze": would_freeze,
+            "protected": protected,
+            "summary": {
+                "threshold_mb": args.threshold,
+                "would_freeze_count": plan.would_freeze.len(),
+                "protected_count": plan.protected.len(),
+                "total_memory_to_free_mb": plan.total_memory_to_free_mb,
+                "by_category": Self::by_category(&all),
+            },
+        })
+    }
+
+    pub fn format_plan(&self, plan: &FreezePlan, args: &Args) {
+        let output = self.plan_to_value(plan, args);
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    /// Count and memory per category, over every process passed in (not just `--top`)
+    fn by_category(processes: &[ProcessInfo]) -> Value {
+        let mut totals: Map<String, Value> = Map::new();
+
+        for process in processes {
+            let entry = totals
+                .entry(process.category.as_str())
+                .or_insert_with(|| json!({"count": 0, "total_memory_mb": 0}));
+            entry["count"] = json!(entry["count"].as_u64().unwrap_or(0) + 1);
+            entry["total_memory_mb"] =
+                json!(entry["total_memory_mb"].as_u64().unwrap_or(0) + process.memory_mb);
+        }
+
+        Value::Object(totals)
+    }
+}
+
+impl OutputFormatter for JsonFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        let output = self.to_value(processes, args);
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
 }
@@ -27,6 +104,8 @@ mod tests {
 
     #[test]
     fn test_json_output() {
+        use clap::Parser;
+
         let formatter = JsonFormatter;
         let processes = vec![ProcessInfo::new(
             1234,
@@ -37,22 +116,174 @@ mod tests {
             ProcessCategory::Productivity,
         )];
 
-        let args = Args {
-            threshold: 100,
-            format: crate::cli::OutputFormat::Json,
-            all: false,
-            top: 10,
-            verbose: false,
-            action: None,
-            pid: None,
-            daemon: false,
-            install_startup: false,
-            uninstall_startup: false,
-            interval: 60,
-            keep_communication: false,
-        };
+        let args = Args::parse_from(["smart-freeze", "--format", "json"]);
 
         // Should not panic
         formatter.format_processes(&processes, &args);
     }
+
+    #[test]
+    fn test_json_top_and_all() {
+        use clap::Parser;
+
+        let mut processes = Vec::new();
+        for (pid, memory_mb, freezable) in [(1, 300, true), (2, 900, false), (3, 500, true)] {
+            let mut p = ProcessInfo::new(
+                pid,
+                format!("p{}.exe", pid),
+                String::new(),
+                memory_mb,
+                false,
+                ProcessCategory::Productivity,
+            );
+            p.freezable = Some(freezable);
+            processes.push(p);
+        }
+
+        let args = Args::parse_from(["smart-freeze", "--all", "--top", "2"]);
+        let value = JsonFormatter.to_value(&processes, &args);
+
+        assert_eq!(value["processes"].as_array().unwrap().len(), 2);
+        assert_eq!(value["processes"][0]["pid"], 2);
+        assert_eq!(value["processes"][0]["freezable"], false);
+        assert_eq!(value["safe_to_freeze_count"], 1);
+        assert_eq!(value["total_memory_mb"], 500);
+    }
+
+    #[test]
+    fn test_json_by_category() {
+        use clap::Parser;
+
+        let processes: Vec<ProcessInfo> = [
+            (1, 300, ProcessCategory::Productivity),
+            (2, 900, ProcessCategory::Productivity),
+            (3, 150, ProcessCategory::Communication),
+            (4, 120, ProcessCategory::BackgroundService),
+        ]
+        .into_iter()
+        .map(|(pid, memory_mb, category)| {
+            ProcessInfo::new(
+                pid,
+                format!("p{}.exe", pid),
+                String::new(),
+                memory_mb,
+                false,
+                category,
+            )
+        })
+        .collect();
+
+        // --top doesn't limit the breakdown
+        let args = Args::parse_from(["smart-freeze", "--top", "1"]);
+        let value = JsonFormatter.to_value(&processes, &args);
+        let by_category = &value["by_category"];
+
+        assert_eq!(by_category.as_object().unwrap().len(), 3);
+        assert_eq!(by_category["Productivity"]["count"], 2);
+        assert_eq!(by_category["Productivity"]["total_memory_mb"], 1200);
+        assert_eq!(by_category["Communication"]["count"], 1);
+        assert_eq!(by_category["Communication"]["total_memory_mb"], 150);
+        assert_eq!(by_category["Background"]["total_memory_mb"], 120);
+        assert!(by_category.get("Gaming").is_none());
+    }
+
+    #[test]
+    fn test_json_plan_protected_reasons() {
+        use crate::freeze_engine::ProtectReason;
+        use clap::Parser;
+
+        let process = |pid, name: &str, category, is_foreground| {
+            ProcessInfo::new(
+                pid,
+                name.to_string(),
+                String::new(),
+                400,
+                is_foreground,
+                category,
+            )
+        };
+        let plan = FreezePlan {
+            would_freeze: vec![process(
+                1,
+                "chrome.exe",
+                ProcessCategory::Productivity,
+                false,
+            )],
+            protected: vec![
+                (
+                    process(2, "csrss.exe", ProcessCategory::Critical, false),
+                    ProtectReason::Critical,
+                ),
+                (
+                    process(3, "game.exe", ProcessCategory::Gaming, false),
+                    ProtectReason::Gaming,
+                ),
+                (
+                    process(4, "editor.exe", ProcessCategory::Productivity, true),
+                    ProtectReason::Foreground,
+                ),
+            ],
+            total_memory_to_free_mb: 400,
+        };
+
+        let args = Args::parse_from(["smart-freeze", "--format", "json"]);
+        let value = JsonFormatter.plan_to_value(&plan, &args);
+
+        assert_eq!(value["would_freeze"][0]["pid"], 1);
+        let protected = value["protected"].as_array().unwrap();
+        let reasons: Vec<(u64, &str)> = protected
+            .iter()
+            .map(|p| {
+                (
+                    p["process"]["pid"].as_u64().unwrap(),
+                    p["reason"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![(2, "Critical"), (3, "Gaming"), (4, "Foreground")]
+        );
+        assert_eq!(value["summary"]["would_freeze_count"], 1);
+        assert_eq!(value["summary"]["protected_count"], 3);
+        assert_eq!(value["summary"]["total_memory_to_free_mb"], 400);
+        assert_eq!(value["summary"]["by_category"]["Productivity"]["count"], 2);
+    }
+
+    #[test]
+    fn test_plan_would_freeze_honors_top() {
+        use crate::freeze_engine::ProtectReason;
+        use clap::Parser;
+
+        let process = |pid: u32, memory_mb: u64| {
+            ProcessInfo::new(
+                pid,
+                format!("p{}.exe", pid),
+                String::new(),
+                memory_mb,
+                false,
+                ProcessCategory::Productivity,
+            )
+        };
+        let plan = FreezePlan {
+            would_freeze: vec![process(1, 100), process(2, 900), process(3, 500)],
+            protected: vec![(process(4, 50), ProtectReason::Foreground)],
+            total_memory_to_free_mb: 1500,
+        };
+
+        let args = Args::parse_from(["smart-freeze", "--format", "json", "--top", "2"]);
+        let value = JsonFormatter.plan_to_value(&plan, &args);
+
+        let pids: Vec<u64> = value["would_freeze"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["pid"].as_u64().unwrap())
+            .collect();
+        assert_eq!(pids, vec![2, 3]);
+        assert_eq!(value["protected"].as_array().unwrap().len(), 1);
+        // The summary still describes the whole plan
+        assert_eq!(value["summary"]["would_freeze_count"], 3);
+        assert_eq!(value["summary"]["total_memory_to_free_mb"], 1500);
+    }
 }
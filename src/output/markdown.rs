@@ -0,0 +1,107 @@
+//! Markdown output formatting
+
+use crate::cli::Args;
+use crate::output::{select_for_output, OutputFormatter};
+use crate::process::ProcessInfo;
+
+pub struct MarkdownFormatter;
+
+/// Escape characters that would break a table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+impl MarkdownFormatter {
+    /// Render a GitHub-flavored Markdown table followed by a memory summary
+    pub fn render(&self, processes: &[ProcessInfo], args: &Args) -> String {
+        let shown = select_for_output(processes, args);
+        let mut output = String::new();
+
+        let cpu = if args.top_cpu { " CPU % |" } else { "" };
+        let cpu_rule = if args.top_cpu { "------:|" } else { "" };
+        if args.all {
+            output.push_str(&format!(
+                "| PID | Name | Memory MB |{} Category | Freezable |\n",
+                cpu
+            ));
+            output.push_str(&format!(
+                "|----:|------|----------:|{}----------|-----------|\n",
+                cpu_rule
+            ));
+        } else {
+            output.push_str(&format!("| PID | Name | Memory MB |{} Category |\n", cpu));
+            output.push_str(&format!(
+                "|----:|------|----------:|{}----------|\n",
+                cpu_rule
+            ));
+        }
+
+        for process in &shown {
+            output.push_str(&format!(
+                "| {} | {} | {} |",
+                process.pid,
+                escape_cell(&process.name),
+                process.memory_mb
+            ));
+            if args.top_cpu {
+                output.push_str(&format!(" {:.1} |", process.cpu_percent));
+            }
+            output.push_str(&format!(" {} |", process.category.as_str()));
+            if args.all {
+                let freezable = if process.freezable.unwrap_or(false) {
+                    "yes"
+                } else {
+                    "no"
+                };
+                output.push_str(&format!(" {} |", freezable));
+            }
+            output.push('\n');
+        }
+
+        output.push_str(&format!(
+            "\n**Total memory:** {} MB across {} processes\n",
+            shown.iter().map(|p| p.memory_mb).sum::<u64>(),
+            shown.len()
+        ));
+        output
+    }
+}
+
+impl OutputFormatter for MarkdownFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        print!("{}", self.render(processes, args));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+    use clap::Parser;
+
+    #[test]
+    fn test_markdown_table_structure() {
+        let processes = vec![ProcessInfo::new(
+            1234,
+            "odd|name.exe".to_string(),
+            "C:\\odd.exe".to_string(),
+            200,
+            false,
+            ProcessCategory::Productivity,
+        )];
+        let args = Args::parse_from(["smart-freeze", "--format", "markdown"]);
+
+        let output = MarkdownFormatter.render(&processes, &args);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "| PID | Name | Memory MB | Category |");
+        assert!(lines[1].starts_with('|') && lines[1].ends_with('|'));
+        assert!(lines[1]
+            .trim_matches('|')
+            .split('|')
+            .all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-' || c == ':')));
+        assert_eq!(lines[1].matches('|').count(), lines[0].matches('|').count());
+        assert_eq!(lines[2], "| 1234 | odd\\|name.exe | 200 | Productivity |");
+        assert!(output.contains("**Total memory:** 200 MB"));
+    }
+}
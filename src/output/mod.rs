@@ -1,22 +1,90 @@
 //! Output formatting for different formats
 
 mod csv;
+mod explain;
 mod json;
+mod markdown;
+mod ndjson;
+mod prometheus;
 mod table;
 
 pub use csv::CsvFormatter;
+pub use explain::format_explanation;
 pub use json::JsonFormatter;
+pub use markdown::MarkdownFormatter;
+pub use ndjson::NdjsonFormatter;
+pub use prometheus::PrometheusFormatter;
 pub use table::TableFormatter;
 
 use crate::cli::{Args, OutputFormat};
 use crate::freeze_engine::FreezeEngine;
 use crate::process::ProcessInfo;
 
+/// ANSI sequences used by `--watch`
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Whether a format makes sense to redraw in place (`--watch`)
+pub fn supports_watch(format: OutputFormat) -> bool {
+    matches!(format, OutputFormat::Table | OutputFormat::Markdown)
+}
+
 /// Trait for output formatting
 pub trait OutputFormatter {
     fn format_processes(&self, processes: &[ProcessInfo], args: &Args);
 }
 
+/// Highest CPU first, PID as a deterministic tiebreak
+pub fn sort_by_cpu(processes: &mut [ProcessInfo]) {
+    processes.sort_by(|a, b| {
+        b.cpu_percent
+            .total_cmp(&a.cpu_percent)
+            .then(a.pid.cmp(&b.pid))
+    });
+}
+
+/// Whether CPU figures are still from the first sample (0.0 for every process)
+pub fn cpu_unsampled(processes: &[ProcessInfo]) -> bool {
+    !processes.is_empty() && processes.iter().all(|p| p.cpu_percent == 0.0)
+}
+
+/// Apply `--top`: keep the N highest-memory processes (highest-CPU with `--top-cpu`)
+///
+/// Category reasons are only kept with `--verbose`.
+pub fn select_for_output(processes: &[ProcessInfo], args: &Args) -> Vec<ProcessInfo> {
+    let mut selected = processes.to_vec();
+    if args.top_cpu {
+        sort_by_cpu(&mut selected);
+    } else {
+        selected.sort_by(|a, b| b.memory_mb.cmp(&a.memory_mb).then(a.pid.cmp(&b.pid)));
+    }
+    selected.truncate(args.top);
+    if !args.verbose {
+        for process in &mut selected {
+            process.category_reason = None;
+        }
+    }
+    selected
+}
+
+/// Processes to display: everything with `--all`, otherwise the safe-to-freeze set
+pub fn collect_processes<E, C, Cat>(
+    engine: &mut FreezeEngine<E, C, Cat>,
+    args: &Args,
+) -> crate::Result<Vec<ProcessInfo>>
+where
+    E: crate::freeze_engine::ProcessEnumerator,
+    C: crate::freeze_engine::ProcessController,
+    Cat: crate::categorization::ProcessCategorizer,
+{
+    if args.all {
+        engine.evaluate_processes()
+    } else {
+        engine.find_safe_to_freeze()
+    }
+}
+
 /// Run output display based on format
 pub fn run<E, C, Cat>(engine: &mut FreezeEngine<E, C, Cat>, args: &Args)
 where
@@ -24,20 +92,108 @@ where
     C: crate::freeze_engine::ProcessController,
     Cat: crate::categorization::ProcessCategorizer,
 {
+    let processes = collect_processes(engine, args).unwrap_or_default();
+    if args.top_cpu && cpu_unsampled(&processes) {
+        eprintln!("Note: CPU usage needs a second sample; every process reads 0% on the first refresh (try --watch)");
+    }
+
     match args.format {
-        OutputFormat::Table => {
-            let formatter = TableFormatter;
-            formatter.format_processes(&[], args);
-        }
-        OutputFormat::Json => {
-            let formatter = JsonFormatter;
-            let safe = engine.find_safe_to_freeze().unwrap_or_default();
-            formatter.format_processes(&safe, args);
-        }
-        OutputFormat::Csv => {
-            let formatter = CsvFormatter;
-            let safe = engine.find_safe_to_freeze().unwrap_or_default();
-            formatter.format_processes(&safe, args);
-        }
+        OutputFormat::Table => TableFormatter.format_processes(&processes, args),
+        OutputFormat::Json if !args.json_legacy => match engine.plan() {
+            Ok(plan) => JsonFormatter.format_plan(&plan, args),
+            Err(_) => JsonFormatter.format_processes(&processes, args),
+        },
+        OutputFormat::Json => JsonFormatter.format_processes(&processes, args),
+        OutputFormat::Csv => CsvFormatter.format_processes(&processes, args),
+        OutputFormat::Markdown => MarkdownFormatter.format_processes(&processes, args),
+        OutputFormat::Ndjson => NdjsonFormatter.format_processes(&processes, args),
+        OutputFormat::Prometheus => PrometheusFormatter.format_processes(&processes, args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+    use clap::Parser;
+
+    fn process(pid: u32, memory_mb: u64) -> ProcessInfo {
+        ProcessInfo::new(
+            pid,
+            format!("p{}.exe", pid),
+            String::new(),
+            memory_mb,
+            false,
+            ProcessCategory::Productivity,
+        )
+    }
+
+    #[test]
+    fn test_supports_watch() {
+        assert!(supports_watch(OutputFormat::Table));
+        assert!(supports_watch(OutputFormat::Markdown));
+        assert!(!supports_watch(OutputFormat::Json));
+        assert!(!supports_watch(OutputFormat::Csv));
+        assert!(!supports_watch(OutputFormat::Ndjson));
+        assert!(!supports_watch(OutputFormat::Prometheus));
+    }
+
+    #[test]
+    fn test_select_for_output_limits_to_top() {
+        let args = Args::parse_from(["smart-freeze", "--top", "2"]);
+        let processes = vec![process(1, 100), process(2, 900), process(3, 500)];
+
+        let selected = select_for_output(&processes, &args);
+
+        let pids: Vec<u32> = selected.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_top_cpu_sorts_by_cpu_with_stable_ties() {
+        let args = Args::parse_from(["smart-freeze", "--top-cpu", "--top", "4"]);
+        let mut processes = vec![
+            process(5, 100),
+            process(3, 900),
+            process(9, 500),
+            process(1, 50),
+        ];
+        processes[0].cpu_percent = 12.5;
+        processes[1].cpu_percent = 3.0;
+        processes[2].cpu_percent = 12.5;
+        processes[3].cpu_percent = 40.0;
+
+        let pids = |selected: Vec<ProcessInfo>| selected.iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids(select_for_output(&processes, &args)), vec![1, 5, 9, 3]);
+
+        // Equal CPU falls back to PID whatever the input order
+        processes.reverse();
+        assert_eq!(pids(select_for_output(&processes, &args)), vec![1, 5, 9, 3]);
+    }
+
+    #[test]
+    fn test_cpu_unsampled() {
+        let mut processes = vec![process(1, 100), process(2, 200)];
+        assert!(cpu_unsampled(&processes));
+
+        processes[1].cpu_percent = 0.4;
+        assert!(!cpu_unsampled(&processes));
+        assert!(!cpu_unsampled(&[]));
+    }
+
+    #[test]
+    fn test_select_for_output_keeps_reason_only_when_verbose() {
+        let mut with_reason = process(1, 100);
+        with_reason.category_reason = Some("matched name substring 'chrome'".to_string());
+        let processes = vec![with_reason];
+
+        let quiet = select_for_output(&processes, &Args::parse_from(["smart-freeze"]));
+        let verbose = select_for_output(&processes, &Args::parse_from(["smart-freeze", "-v"]));
+
+        assert_eq!(quiet[0].category_reason, None);
+        assert_eq!(
+            verbose[0].category_reason.as_deref(),
+            Some("matched name substring 'chrome'")
+        );
     }
 }
@@ -0,0 +1,93 @@
+//! Newline-delimited JSON output formatting
+
+use crate::cli::Args;
+use crate::output::{select_for_output, OutputFormatter};
+use crate::process::ProcessInfo;
+use serde_json::json;
+
+pub struct NdjsonFormatter;
+
+impl NdjsonFormatter {
+    /// One compact JSON object per line, no header or trailer
+    pub fn render(&self, processes: &[ProcessInfo], args: &Args) -> String {
+        let mut output = String::new();
+
+        for process in select_for_output(processes, args) {
+            let mut line = json!({
+                "pid": process.pid,
+                "name": process.name,
+                "memory_mb": process.memory_mb,
+                "cpu_percent": process.cpu_percent,
+                "category": process.category,
+                "is_foreground": process.is_foreground,
+                "full_path": process.full_path,
+            });
+            if let Some(freezable) = process.freezable {
+                line["freezable"] = json!(freezable);
+            }
+
+            output.push_str(&line.to_string());
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+impl OutputFormatter for NdjsonFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        print!("{}", self.render(processes, args));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+    use clap::Parser;
+
+    #[test]
+    fn test_ndjson_lines_are_standalone_json() {
+        let processes = vec![
+            ProcessInfo::new(
+                1,
+                "chrome.exe".to_string(),
+                "C:\\chrome.exe".to_string(),
+                500,
+                false,
+                ProcessCategory::Productivity,
+            ),
+            ProcessInfo::new(
+                2,
+                "line\nbreak.exe".to_string(),
+                "C:\\odd.exe".to_string(),
+                300,
+                true,
+                ProcessCategory::Unknown,
+            ),
+        ];
+        let args = Args::parse_from(["smart-freeze", "--format", "ndjson"]);
+
+        let output = NdjsonFormatter.render(&processes, &args);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        for line in &lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            for key in [
+                "pid",
+                "name",
+                "memory_mb",
+                "cpu_percent",
+                "category",
+                "is_foreground",
+                "full_path",
+            ] {
+                assert!(value.get(key).is_some(), "missing {}", key);
+            }
+        }
+
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["name"], "line\nbreak.exe");
+    }
+}
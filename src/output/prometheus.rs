@@ -0,0 +1,147 @@
+//! Prometheus text exposition output formatting
+
+use crate::cli::Args;
+use crate::output::{select_for_output, OutputFormatter};
+use crate::process::ProcessInfo;
+use std::fmt::Write;
+
+pub struct PrometheusFormatter;
+
+/// Escape a label value: backslash, double quote and newline
+pub fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl PrometheusFormatter {
+    /// Per-process memory gauges for the `--top` rows, then totals over every listed process
+    ///
+    /// Without `--all` the list is the safe-to-freeze set; with it, processes
+    /// count as safe when the engine marked them freezable.
+    pub fn render(&self, processes: &[ProcessInfo], args: &Args) -> String {
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "# HELP smartfreeze_process_memory_mb Working set of a listed process in MB"
+        );
+        let _ = writeln!(output, "# TYPE smartfreeze_process_memory_mb gauge");
+        for process in select_for_output(processes, args) {
+            let _ = writeln!(
+                output,
+                "smartfreeze_process_memory_mb{{pid=\"{}\",name=\"{}\",category=\"{}\"}} {}",
+                process.pid,
+                escape_label(&process.name),
+                process.category.as_str(),
+                process.memory_mb
+            );
+        }
+
+        let safe = processes
+            .iter()
+            .filter(|p| p.freezable.unwrap_or(true))
+            .count();
+        let total_mb: u64 = processes.iter().map(|p| p.memory_mb).sum();
+
+        let _ = writeln!(
+            output,
+            "# HELP smartfreeze_safe_to_freeze_total Processes that would be frozen"
+        );
+        let _ = writeln!(output, "# TYPE smartfreeze_safe_to_freeze_total gauge");
+        let _ = writeln!(output, "smartfreeze_safe_to_freeze_total {}", safe);
+        let _ = writeln!(
+            output,
+            "# HELP smartfreeze_total_memory_mb Combined working set of listed processes in MB"
+        );
+        let _ = writeln!(output, "# TYPE smartfreeze_total_memory_mb gauge");
+        let _ = writeln!(output, "smartfreeze_total_memory_mb {}", total_mb);
+
+        output
+    }
+}
+
+impl OutputFormatter for PrometheusFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        print!("{}", self.render(processes, args));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+    use clap::Parser;
+
+    /// `name{labels} value` or `name value`, with a valid metric name and numeric value
+    fn is_well_formed(line: &str) -> bool {
+        let Some((series, value)) = line.rsplit_once(' ') else {
+            return false;
+        };
+        if value.parse::<f64>().is_err() {
+            return false;
+        }
+        let name = match series.find('{') {
+            Some(open) if series.ends_with('}') => &series[..open],
+            Some(_) => return false,
+            None => series,
+        };
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("plain.exe"), "plain.exe");
+        assert_eq!(
+            escape_label("a\"b\\c\nd"),
+            "a\\\"b\\\\c\\nd",
+            "quote, backslash and newline are escaped"
+        );
+    }
+
+    #[test]
+    fn test_prometheus_lines_are_well_formed() {
+        let mut kept = ProcessInfo::new(
+            2,
+            "we\"ird\\\nname.exe".to_string(),
+            String::new(),
+            300,
+            true,
+            ProcessCategory::Unknown,
+        );
+        kept.freezable = Some(false);
+        let mut freezable = ProcessInfo::new(
+            1,
+            "chrome.exe".to_string(),
+            "C:\\chrome.exe".to_string(),
+            500,
+            false,
+            ProcessCategory::Productivity,
+        );
+        freezable.freezable = Some(true);
+        let args = Args::parse_from(["smart-freeze", "--format", "prometheus", "--all"]);
+
+        let output = PrometheusFormatter.render(&[freezable, kept], &args);
+
+        for line in output.lines().filter(|l| !l.starts_with('#')) {
+            assert!(is_well_formed(line), "malformed: {:?}", line);
+        }
+        assert!(output.contains(
+            "smartfreeze_process_memory_mb{pid=\"1\",name=\"chrome.exe\",category=\"Productivity\"} 500"
+        ));
+        assert!(output.contains("name=\"we\\\"ird\\\\\\nname.exe\""));
+        assert!(output.contains("smartfreeze_safe_to_freeze_total 1\n"));
+        assert!(output.contains("smartfreeze_total_memory_mb 800\n"));
+    }
+}
@@ -1,52 +1,240 @@
 //! Table output formatting
 
 use crate::cli::Args;
-use crate::output::OutputFormatter;
+use crate::output::{select_for_output, OutputFormatter};
 use crate::process::{ProcessCategory, ProcessInfo};
+use std::fmt::Write;
+use std::io::IsTerminal;
 
 pub struct TableFormatter;
 
+const RESET: &str = "\x1b[0m";
+
+/// Wrap text in the ANSI color for a category (always colors; see `colors_enabled`)
+pub fn colorize(category: ProcessCategory, text: &str) -> String {
+    let code = match category {
+        ProcessCategory::Critical => "\x1b[31m",
+        ProcessCategory::Gaming => "\x1b[32m",
+        ProcessCategory::Communication => "\x1b[36m",
+        ProcessCategory::Productivity => "\x1b[33m",
+        ProcessCategory::BackgroundService | ProcessCategory::Inaccessible => "\x1b[2m",
+        ProcessCategory::Unknown => return text.to_string(),
+    };
+    format!("{}{}{}", code, text, RESET)
+}
+
+/// Colors are used only on a terminal, without `--no-color` or `NO_COLOR`
+pub fn colors_enabled(no_color_flag: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
 impl TableFormatter {
     fn category_to_str(&self, category: ProcessCategory) -> &'static str {
         category.as_str()
     }
-}
 
-impl OutputFormatter for TableFormatter {
-    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
-        println!("Smart Freeze Engine - Dry Run Mode");
-        println!("===================================\n");
+    /// Render the process listing section, honoring `--all` and `--top`
+    ///
+    /// Without `--all` the input is the safe-to-freeze set; with it, every
+    /// process is listed with a Freezable column.
+    pub fn render_listing(&self, processes: &[ProcessInfo], args: &Args) -> String {
+        self.render_listing_with(processes, args, colors_enabled(args.no_color))
+    }
+
+    /// Pad a cell to `width` first so escape codes don't break alignment
+    fn cell(&self, category: ProcessCategory, text: &str, width: usize, color: bool) -> String {
+        let padded = format!("{:<width$}", text, width = width);
+        if color {
+            colorize(category, &padded)
+        } else {
+            padded
+        }
+    }
+
+    /// Indented line explaining the category (present only with `--verbose`)
+    fn write_reason(&self, out: &mut String, process: &ProcessInfo) {
+        if let Some(reason) = &process.category_reason {
+            let _ = writeln!(out, "{:<8} ↳ {}", "", reason);
+        }
+    }
+
+    /// The CPU column is only shown with `--top-cpu`
+    fn cpu_width(&self, args: &Args) -> usize {
+        if args.top_cpu {
+            7
+        } else {
+            0
+        }
+    }
+
+    fn cpu_header(&self, args: &Args) -> String {
+        if args.top_cpu {
+            format!(" {:>6}", "CPU %")
+        } else {
+            String::new()
+        }
+    }
+
+    fn cpu_cell(&self, process: &ProcessInfo, args: &Args) -> String {
+        if args.top_cpu {
+            format!(" {:>6.1}", process.cpu_percent)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Thread/handle/suspended columns are only shown with `--verbose`
+    fn counts_width(&self, args: &Args) -> usize {
+        if args.verbose {
+            26
+        } else {
+            0
+        }
+    }
+
+    fn counts_header(&self, args: &Args) -> String {
+        if args.verbose {
+            format!(" {:>7} {:>7} {:<9}", "Threads", "Handles", "Suspended")
+        } else {
+            String::new()
+        }
+    }
+
+    fn counts_cell(&self, process: &ProcessInfo, args: &Args) -> String {
+        if args.verbose {
+            format!(
+                " {:>7} {:>7} {:<9}",
+                process.thread_count,
+                process.handle_count,
+                if process.is_suspended { "yes" } else { "no" }
+            )
+        } else {
+            String::new()
+        }
+    }
+
+    fn render_listing_with(&self, processes: &[ProcessInfo], args: &Args, color: bool) -> String {
+        let shown = select_for_output(processes, args);
+        let mut out = String::new();
+
+        if shown.is_empty() {
+            let _ = writeln!(out, "❄️  WOULD FREEZE: None (no processes match criteria)");
+            return out;
+        }
+
+        if args.all {
+            let _ = writeln!(
+                out,
+                "📋 ALL PROCESSES (showing {} of {}, >{} MB freezable):",
+                shown.len(),
+                processes.len(),
+                args.threshold
+            );
+            let width = 80 + self.cpu_width(args) + self.counts_width(args);
+            let _ = writeln!(out, "{}", "=".repeat(width));
+            let _ = writeln!(
+                out,
+                "{:<8} {:<40} {:>12}{} {:<10} {:<9}{}",
+                "PID",
+                "Name",
+                "Memory (MB)",
+                self.cpu_header(args),
+                "Category",
+                "Freezable",
+                self.counts_header(args)
+            );
+            let _ = writeln!(out, "{}", "-".repeat(width));
 
-        // Display processes that would be frozen
-        if !processes.is_empty() {
-            println!(
+            for process in &shown {
+                let _ = writeln!(
+                    out,
+                    "{:<8} {} {:>12}{} {} {:<9}{}",
+                    process.pid,
+                    self.cell(process.category, &process.name, 40, color),
+                    process.memory_mb,
+                    self.cpu_cell(process, args),
+                    self.cell(
+                        process.category,
+                        self.category_to_str(process.category),
+                        10,
+                        color
+                    ),
+                    if process.freezable.unwrap_or(false) {
+                        "yes"
+                    } else {
+                        "no"
+                    },
+                    self.counts_cell(process, args)
+                );
+                self.write_reason(&mut out, process);
+            }
+        } else {
+            let _ = writeln!(
+                out,
                 "❄️  WOULD FREEZE ({} processes, >{} MB):",
                 processes.len(),
                 args.threshold
             );
-            println!("{}", "=".repeat(70));
-            println!(
-                "{:<8} {:<40} {:>12} {:<10}",
-                "PID", "Name", "Memory (MB)", "Category"
+            let width = 70 + self.cpu_width(args) + self.counts_width(args);
+            let _ = writeln!(out, "{}", "=".repeat(width));
+            let _ = writeln!(
+                out,
+                "{:<8} {:<40} {:>12}{} {:<10}{}",
+                "PID",
+                "Name",
+                "Memory (MB)",
+                self.cpu_header(args),
+                "Category",
+                self.counts_header(args)
             );
-            println!("{}", "-".repeat(70));
+            let _ = writeln!(out, "{}", "-".repeat(width));
+
+            for process in &shown {
+                let _ = writeln!(
+                    out,
+                    "{:<8} {} {:>12}{} {}{}",
+                    process.pid,
+                    self.cell(process.category, &process.name, 40, color),
+                    process.memory_mb,
+                    self.cpu_cell(process, args),
+                    self.cell(
+                        process.category,
+                        self.category_to_str(process.category),
+                        10,
+                        color
+                    ),
+                    self.counts_cell(process, args)
+                );
+                self.write_reason(&mut out, process);
+            }
 
-            for process in processes {
-                let category_str = self.category_to_str(process.category);
-                println!(
-                    "{:<8} {:<40} {:>12} {:<10}",
-                    process.pid, process.name, process.memory_mb, category_str
+            if shown.len() < processes.len() {
+                let _ = writeln!(
+                    out,
+                    "   ... and {} more (use --top to show more)",
+                    processes.len() - shown.len()
                 );
             }
 
-            println!(
+            let _ = writeln!(
+                out,
                 "\n   Total memory to free: {} MB",
                 processes.iter().map(|p| p.memory_mb).sum::<u64>()
             );
-        } else {
-            println!("❄️  WOULD FREEZE: None (no processes match criteria)");
         }
 
+        out
+    }
+}
+
+impl OutputFormatter for TableFormatter {
+    fn format_processes(&self, processes: &[ProcessInfo], args: &Args) {
+        println!("Smart Freeze Engine - Dry Run Mode");
+        println!("===================================\n");
+
+        print!("{}", self.render_listing(processes, args));
+
         println!("\n💡 This is a DRY RUN. To actually freeze processes, use:");
         println!("   --action freeze --pid <PID>  (manual)");
         println!("   --daemon                     (automatic when gaming)");
@@ -70,23 +258,126 @@ mod tests {
 
     #[test]
     fn test_empty_output() {
+        use clap::Parser;
+
         let formatter = TableFormatter;
-        let args = Args {
-            threshold: 100,
-            format: crate::cli::OutputFormat::Table,
-            all: false,
-            top: 10,
-            verbose: false,
-            action: None,
-            pid: None,
-            daemon: false,
-            install_startup: false,
-            uninstall_startup: false,
-            interval: 60,
-            keep_communication: false,
-        };
+        let args = Args::parse_from(["smart-freeze", "--format", "table"]);
 
         // Should not panic
         formatter.format_processes(&[], &args);
     }
+
+    #[test]
+    fn test_render_listing_top_and_all() {
+        use clap::Parser;
+
+        let mut processes = Vec::new();
+        for pid in 1..=5 {
+            let mut p = ProcessInfo::new(
+                pid,
+                format!("proc{}.exe", pid),
+                String::new(),
+                u64::from(pid) * 100,
+                false,
+                ProcessCategory::Productivity,
+            );
+            p.freezable = Some(pid % 2 == 0);
+            processes.push(p);
+        }
+
+        let args = Args::parse_from(["smart-freeze", "--top", "2"]);
+        let listing = TableFormatter.render_listing(&processes, &args);
+        assert!(listing.contains("proc5.exe"));
+        assert!(listing.contains("proc4.exe"));
+        assert!(!listing.contains("proc3.exe"));
+        assert!(listing.contains("... and 3 more"));
+
+        let args = Args::parse_from(["smart-freeze", "--all", "--top", "3"]);
+        let listing = TableFormatter.render_listing(&processes, &args);
+        assert!(listing.contains("Freezable"));
+        assert!(listing.contains("showing 3 of 5"));
+        assert!(!listing.contains("proc2.exe"));
+    }
+
+    #[test]
+    fn test_verbose_listing_shows_category_reason() {
+        use clap::Parser;
+
+        let mut process = ProcessInfo::new(
+            1,
+            "discord.exe".to_string(),
+            String::new(),
+            300,
+            false,
+            ProcessCategory::Communication,
+        );
+        process.category_reason = Some("matched name substring 'discord'".to_string());
+        process.thread_count = 12;
+        process.handle_count = 340;
+        let processes = vec![process];
+
+        let args = Args::parse_from(["smart-freeze", "--verbose"]);
+        let listing = TableFormatter.render_listing_with(&processes, &args, false);
+        assert!(listing.contains("↳ matched name substring 'discord'"));
+        assert!(listing.contains("Threads Handles Suspended"));
+        assert!(listing.contains("     12     340 no"));
+
+        let args = Args::parse_from(["smart-freeze"]);
+        let listing = TableFormatter.render_listing_with(&processes, &args, false);
+        assert!(!listing.contains("↳"));
+        assert!(!listing.contains("Threads"));
+    }
+
+    #[test]
+    fn test_colorize() {
+        assert_eq!(
+            colorize(ProcessCategory::Critical, "explorer.exe"),
+            "\x1b[31mexplorer.exe\x1b[0m"
+        );
+        assert_eq!(
+            colorize(ProcessCategory::Gaming, "game.exe"),
+            "\x1b[32mgame.exe\x1b[0m"
+        );
+        assert_eq!(colorize(ProcessCategory::Unknown, "x.exe"), "x.exe");
+    }
+
+    #[test]
+    fn test_colored_listing_stays_aligned() {
+        use clap::Parser;
+
+        let processes = vec![
+            ProcessInfo::new(
+                1,
+                "a.exe".to_string(),
+                String::new(),
+                300,
+                false,
+                ProcessCategory::Communication,
+            ),
+            ProcessInfo::new(
+                2,
+                "longer_name.exe".to_string(),
+                String::new(),
+                200,
+                false,
+                ProcessCategory::Productivity,
+            ),
+        ];
+        let args = Args::parse_from(["smart-freeze"]);
+
+        let plain = TableFormatter.render_listing_with(&processes, &args, false);
+        let colored = TableFormatter.render_listing_with(&processes, &args, true);
+
+        assert_ne!(plain, colored);
+        let stripped = colored
+            .replace("\x1b[36m", "")
+            .replace("\x1b[33m", "")
+            .replace(RESET, "");
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn test_no_color_flag_disables_colors() {
+        assert!(!colors_enabled(true));
+    }
 }
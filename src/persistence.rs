@@ -1,20 +1,36 @@
 //! State persistence for crash recovery
 
-use crate::Result;
+use crate::categorization::ProcessCategorizer;
+use crate::freeze_engine::{
+    FreezeEngine, FreezeMode, FreezeRecord, ProcessController, ProcessEnumerator,
+};
+use crate::{Result, SmartFreezeError};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const MAX_STATE_AGE_SECS: u64 = 3600; // 1 hour
 
+/// Current `PersistentState` layout version (files without one are version 0)
+pub const STATE_VERSION: u32 = 2;
+
 /// Frozen process information for persistence
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FrozenProcess {
     pub pid: u32,
     pub name: String,
+    /// Absent in version 0 files
+    #[serde(default)]
     pub exe_path: String,
     pub timestamp: u64,
+    /// What the freeze changed; empty in files before version 2
+    #[serde(default)]
+    pub record: FreezeRecord,
+    /// How it was frozen (`None`: suspended, as in files before version 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<FreezeMode>,
 }
 
 impl FrozenProcess {
@@ -29,40 +45,91 @@ impl FrozenProcess {
             name,
             exe_path,
             timestamp,
+            record: FreezeRecord::default(),
+            mode: None,
         }
     }
 
-    /// Check if this frozen process is stale (too old)
-    pub fn is_stale(&self) -> bool {
+    /// Record the freeze mode, so recovery undoes a soft freeze or throttle too
+    pub fn with_mode(mut self, mode: Option<FreezeMode>) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Attach what the controller changed, so another run can undo exactly that
+    pub fn with_record(mut self, record: FreezeRecord) -> Self {
+        self.record = record;
+        self
+    }
+
+    /// Whether `live_path` (the running process's executable) is the one that
+    /// was frozen; unknown paths on either side match, so nothing is skipped
+    /// just because it couldn't be queried
+    pub fn matches_exe(&self, live_path: Option<&str>) -> bool {
+        match live_path {
+            Some(live) if !self.exe_path.is_empty() => live.eq_ignore_ascii_case(&self.exe_path),
+            _ => true,
+        }
+    }
+
+    /// Seconds since this process was frozen (0 if the timestamp is in the future)
+    pub fn age_secs(&self) -> u64 {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
-        now - self.timestamp > MAX_STATE_AGE_SECS
+        now.saturating_sub(self.timestamp)
+    }
+
+    /// Check if this frozen process is stale (too old)
+    pub fn is_stale(&self) -> bool {
+        self.age_secs() > MAX_STATE_AGE_SECS
     }
 }
 
 /// Persistent state container
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PersistentState {
+    #[serde(default)]
+    pub version: u32,
     pub frozen_processes: Vec<FrozenProcess>,
 }
 
 impl PersistentState {
     pub fn new() -> Self {
         Self {
+            version: STATE_VERSION,
             frozen_processes: Vec::new(),
         }
     }
 
+    /// Parse a state file, migrating older layouts to the current version
+    pub fn from_json(content: &str) -> Result<Self> {
+        let mut state: PersistentState = serde_json::from_str(content)?;
+
+        if state.version > STATE_VERSION {
+            return Err(SmartFreezeError::StateVersion {
+                found: state.version,
+                supported: STATE_VERSION,
+            });
+        }
+
+        // v0 -> v1 -> v2: only add fields, which serde defaults already fill in
+        state.version = STATE_VERSION;
+        Ok(state)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.frozen_processes.is_empty()
     }
 
     pub fn add(&mut self, pid: u32, name: String, exe_path: String) {
-        self.frozen_processes
-            .push(FrozenProcess::new(pid, name, exe_path));
+        self.push(FrozenProcess::new(pid, name, exe_path));
+    }
+
+    pub fn push(&mut self, process: FrozenProcess) {
+        self.frozen_processes.push(process);
     }
 
     pub fn remove(&mut self, pid: u32) {
@@ -73,6 +140,14 @@ impl PersistentState {
         self.frozen_processes.clear();
     }
 
+    /// PID -> (name, exe path) of the tracked processes, for `FreezeEngine::resume_by_category`
+    pub fn name_lookup(&self) -> HashMap<u32, (String, String)> {
+        self.frozen_processes
+            .iter()
+            .map(|p| (p.pid, (p.name.clone(), p.exe_path.clone())))
+            .collect()
+    }
+
     /// Get only non-stale processes
     pub fn get_valid_processes(&self) -> Vec<&FrozenProcess> {
         self.frozen_processes
@@ -93,6 +168,45 @@ pub trait StatePersistence: Send + Sync {
     fn save(&self, state: &PersistentState) -> Result<()>;
     fn load(&self) -> Result<Option<PersistentState>>;
     fn delete(&self) -> Result<()>;
+
+    /// Whether the saved state records `pid` as frozen (`false` if nothing is saved)
+    fn contains(&self, pid: u32) -> Result<bool> {
+        Ok(self
+            .load()?
+            .is_some_and(|state| state.frozen_processes.iter().any(|p| p.pid == pid)))
+    }
+}
+
+/// Where the saved state lives (`--state-registry`, else `--state-file`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateLocation {
+    File(PathBuf),
+    /// `HKCU\Software\SmartFreeze`, out of reach of temp-dir cleanup
+    #[cfg(windows)]
+    Registry,
+}
+
+impl StateLocation {
+    /// Persistence backend for this location
+    pub fn open(&self) -> Box<dyn StatePersistence> {
+        match self {
+            StateLocation::File(path) => Box::new(FileStatePersistence::new(path.clone())),
+            #[cfg(windows)]
+            StateLocation::Registry => {
+                Box::new(crate::windows::RegistryStatePersistence::with_default_key())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for StateLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateLocation::File(path) => write!(f, "{}", path.display()),
+            #[cfg(windows)]
+            StateLocation::Registry => write!(f, "HKCU\\Software\\SmartFreeze"),
+        }
+    }
 }
 
 /// File-based state persistence
@@ -100,6 +214,9 @@ pub struct FileStatePersistence {
     path: PathBuf,
 }
 
+/// Environment variable overriding the state file location (`--state-file` wins)
+pub const STATE_PATH_ENV: &str = "SMARTFREEZE_STATE";
+
 impl FileStatePersistence {
     pub fn new(path: PathBuf) -> Self {
         Self { path }
@@ -116,11 +233,32 @@ impl FileStatePersistence {
     }
 }
 
+/// Sibling of `path` with `.tmp` appended to the file name
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Write to a sibling `.tmp` file, then rename it over `path`
+///
+/// The rename is atomic on the same volume, so a crash mid-write never
+/// leaves a truncated file behind.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let tmp_path = tmp_path_for(path);
+
+    fs::write(&tmp_path, contents)?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
 impl StatePersistence for FileStatePersistence {
+    /// Saved atomically, so a crash mid-save never leaves a truncated state file
     fn save(&self, state: &PersistentState) -> Result<()> {
-        let json = serde_json::to_string_pretty(state)?;
-        fs::write(&self.path, json)?;
-        Ok(())
+        write_atomically(&self.path, &serde_json::to_string_pretty(state)?)
     }
 
     fn load(&self) -> Result<Option<PersistentState>> {
@@ -129,8 +267,7 @@ impl StatePersistence for FileStatePersistence {
         }
 
         let content = fs::read_to_string(&self.path)?;
-        let state: PersistentState = serde_json::from_str(&content)?;
-        Ok(Some(state))
+        PersistentState::from_json(&content).map(Some)
     }
 
     fn delete(&self) -> Result<()> {
@@ -141,9 +278,397 @@ impl StatePersistence for FileStatePersistence {
     }
 }
 
+/// Tray icon color used when none is configured (light blue)
+pub const DEFAULT_ICON_COLOR: [u8; 3] = [64, 128, 255];
+
+/// Tray preferences that survive restarts
+///
+/// Loaded when the daemon starts; changes made through the tray are saved back.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraySettings {
+    /// Whether auto-freeze is enabled (toggled from the tray)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// RGB color of the tray icon
+    #[serde(default = "default_icon_color")]
+    pub icon_color: [u8; 3],
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_icon_color() -> [u8; 3] {
+    DEFAULT_ICON_COLOR
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            icon_color: default_icon_color(),
+        }
+    }
+}
+
+impl TraySettings {
+    /// `%APPDATA%\SmartFreeze\smartfreeze_settings.json`, or the temp dir if APPDATA is unset
+    pub fn default_path() -> PathBuf {
+        let mut path = match std::env::var_os("APPDATA") {
+            Some(appdata) => PathBuf::from(appdata).join("SmartFreeze"),
+            None => std::env::temp_dir(),
+        };
+        path.push("smartfreeze_settings.json");
+        path
+    }
+
+    /// Load settings, falling back to defaults if the file is missing
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save atomically (see [`FileStatePersistence`]'s `save`)
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomically(path, &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Change only `enabled` in the file at `path`, keeping the other preferences
+    ///
+    /// A file that can't be read is left untouched rather than overwritten
+    /// with defaults.
+    pub fn save_enabled(path: &Path, enabled: bool) -> Result<()> {
+        let mut settings = Self::load_from(path)?;
+        settings.enabled = enabled;
+        settings.save_to(path)
+    }
+}
+
+/// Single-instance guard for the daemon, released on drop
+///
+/// Holds an exclusive OS lock on a file next to the state file, so the lock
+/// also disappears if the daemon crashes.
+#[derive(Debug)]
+pub struct DaemonLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl DaemonLock {
+    pub fn default_path() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push("smartfreeze_daemon.lock");
+        path
+    }
+
+    /// Acquire the lock at the default location
+    pub fn acquire() -> Result<Self> {
+        Self::acquire_at(Self::default_path())
+    }
+
+    /// Acquire the lock at a specific path, failing if another holder exists
+    pub fn acquire_at(path: PathBuf) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Self { file, path }),
+            Err(TryLockError::WouldBlock) => Err(SmartFreezeError::AlreadyRunning(path)),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for DaemonLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Outcome of resuming every persisted frozen process
+#[derive(Debug, Default)]
+pub struct ResumeReport {
+    pub resumed: Vec<u32>,
+    pub failed: Vec<(u32, SmartFreezeError)>,
+    /// Dropped without resuming: exited, or the PID now runs another executable
+    pub skipped: Vec<(u32, String)>,
+}
+
+/// Resume every process recorded in the persisted state, however old
+///
+/// Each entry's freeze record is handed to `controller` first, so it undoes
+/// exactly what the earlier run changed. Entries whose PID now belongs to a
+/// different executable, or that have exited, are dropped. Returns `None` when
+/// there is no state file. Entries that failed to resume are kept on disk;
+/// the file is deleted once nothing is left to resume.
+pub fn resume_all_from_state<P, C>(persistence: &P, controller: &C) -> Result<Option<ResumeReport>>
+where
+    P: StatePersistence + ?Sized,
+    C: ProcessController + ?Sized,
+{
+    let Some(state) = persistence.load()? else {
+        return Ok(None);
+    };
+
+    let mut report = ResumeReport::default();
+    let mut remaining = PersistentState::new();
+
+    for frozen in state.frozen_processes {
+        let live_path = controller.exe_path(frozen.pid);
+        if !frozen.matches_exe(live_path.as_deref()) {
+            report.skipped.push((
+                frozen.pid,
+                format!("PID now runs {}", live_path.unwrap_or_default()),
+            ));
+            continue;
+        }
+
+        controller.adopt_freeze(frozen.pid, &frozen.record);
+        match undo_freeze(controller, &frozen) {
+            Ok(_) => report.resumed.push(frozen.pid),
+            Err(SmartFreezeError::ProcessNotFound(_)) => {
+                report
+                    .skipped
+                    .push((frozen.pid, "no longer running".to_string()));
+            }
+            Err(e) => {
+                report.failed.push((frozen.pid, e));
+                remaining.push(frozen);
+            }
+        }
+    }
+
+    if remaining.is_empty() {
+        persistence.delete()?;
+    } else {
+        persistence.save(&remaining)?;
+    }
+
+    Ok(Some(report))
+}
+
+/// Undo a saved freeze in the mode it was made, returning the threads resumed
+///
+/// Soft freezes also get their priority and affinity back; only escalated
+/// ones (with suspended threads on record) are resumed.
+fn undo_freeze<C>(controller: &C, frozen: &FrozenProcess) -> Result<usize>
+where
+    C: ProcessController + ?Sized,
+{
+    match frozen.mode {
+        Some(FreezeMode::Throttle) => controller
+            .set_efficiency_mode(frozen.pid, false)
+            .map(|()| 0),
+        Some(FreezeMode::Soft) => {
+            let resumed = if frozen.record.threads.is_empty() {
+                Ok(0)
+            } else {
+                controller.resume(frozen.pid)
+            };
+            let restored = controller.restore_priority_and_affinity(frozen.pid);
+            let threads = resumed?;
+            restored.map(|()| threads)
+        }
+        _ => match controller.resume(frozen.pid)? {
+            // The controller won't guess which threads were ours
+            0 if frozen.record.threads.is_empty() => Err(SmartFreezeError::ResumeFailed {
+                pid: frozen.pid,
+                reason: "no record of which threads were suspended".to_string(),
+            }),
+            threads => Ok(threads),
+        },
+    }
+}
+
+/// Save processes just frozen by hand (`--action freeze` or the picker), with
+/// what each freeze changed, so a later run can resume exactly that
+pub fn record_manual_freezes<E, C, Cat>(
+    persistence: &dyn StatePersistence,
+    engine: &FreezeEngine<E, C, Cat>,
+    pids: &[u32],
+) -> Result<()>
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    if pids.is_empty() {
+        return Ok(());
+    }
+
+    let mut state = persistence.load()?.unwrap_or_default();
+    for &pid in pids {
+        let exe_path = engine.exe_path(pid).unwrap_or_default();
+        let name = Path::new(&exe_path)
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        state.remove(pid);
+        state.push(
+            FrozenProcess::new(pid, name, exe_path)
+                .with_record(engine.freeze_record(pid))
+                .with_mode(engine.config().mode),
+        );
+    }
+    persistence.save(&state)
+}
+
+/// Resume `pids` with the freeze records saved for them, then drop them from
+/// the saved state
+///
+/// A saved entry whose PID now runs a different executable is ignored. The
+/// resumes go ahead even if the state can't be read or updated; that error is
+/// returned next to the per-PID results.
+pub fn resume_manual_freezes<E, C, Cat>(
+    persistence: &dyn StatePersistence,
+    engine: &FreezeEngine<E, C, Cat>,
+    pids: &[u32],
+) -> (Vec<(u32, Result<usize>)>, Result<()>)
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    let mut state = match persistence.load() {
+        Ok(state) => state,
+        Err(e) => return (engine.resume_multiple(pids), Err(e)),
+    };
+    if let Some(state) = &state {
+        for entry in &state.frozen_processes {
+            if pids.contains(&entry.pid) && entry.matches_exe(engine.exe_path(entry.pid).as_deref())
+            {
+                engine.adopt_freeze(entry.pid, &entry.record);
+            }
+        }
+    }
+
+    let results = engine.resume_multiple(pids);
+
+    let saved = match &mut state {
+        Some(state) => {
+            let before = state.frozen_processes.len();
+            for (pid, result) in &results {
+                if matches!(result, Ok(_) | Err(SmartFreezeError::ProcessNotFound(_))) {
+                    state.remove(*pid);
+                }
+            }
+            match state.frozen_processes.len() {
+                len if len == before => Ok(()),
+                0 => persistence.delete(),
+                _ => persistence.save(state),
+            }
+        }
+        None => Ok(()),
+    };
+    (results, saved)
+}
+
+/// Run `uninstall`, then resume everything persisted and delete the state file
+///
+/// Used when autostart is removed, so nothing stays suspended once the tool is
+/// gone. Every entry is tried, however old, with the same executable check as
+/// `resume_all_from_state`. The state is left untouched if `uninstall` fails;
+/// otherwise it is deleted even when some processes failed to resume.
+pub fn uninstall_and_resume<F, P, C>(
+    uninstall: F,
+    persistence: &P,
+    controller: &C,
+) -> Result<Option<ResumeReport>>
+where
+    F: FnOnce() -> Result<()>,
+    P: StatePersistence + ?Sized,
+    C: ProcessController + ?Sized,
+{
+    uninstall()?;
+
+    let report = resume_all_from_state(persistence, controller)?;
+    if report.is_some() {
+        persistence.delete()?;
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::SmartFreezeError;
+
+    /// Controller that fails to resume the given PIDs
+    #[derive(Default)]
+    struct FailingController {
+        fail: Vec<u32>,
+        /// PIDs that have exited
+        gone: Vec<u32>,
+        /// PIDs with no adopted thread record, which resume nothing
+        untracked: Vec<u32>,
+        /// Live executable paths (unknown for other PIDs)
+        exe_paths: HashMap<u32, String>,
+        /// Records handed over by `adopt_freeze`
+        adopted: std::sync::Mutex<Vec<(u32, FreezeRecord)>>,
+        /// Undo calls made, in order
+        undone: std::sync::Mutex<Vec<(u32, &'static str)>>,
+    }
+
+    impl ProcessController for FailingController {
+        fn freeze(&self, _pid: u32) -> Result<usize> {
+            Ok(1)
+        }
+
+        fn resume(&self, pid: u32) -> Result<usize> {
+            if self.gone.contains(&pid) {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+            if self.fail.contains(&pid) {
+                return Err(SmartFreezeError::ResumeFailed {
+                    pid,
+                    reason: "access denied".to_string(),
+                });
+            }
+            if self.untracked.contains(&pid) {
+                return Ok(0);
+            }
+            self.undone.lock().unwrap().push((pid, "resume"));
+            Ok(1)
+        }
+
+        fn set_efficiency_mode(&self, pid: u32, enabled: bool) -> Result<()> {
+            assert!(!enabled);
+            self.undone.lock().unwrap().push((pid, "unthrottle"));
+            Ok(())
+        }
+
+        fn restore_priority_and_affinity(&self, pid: u32) -> Result<()> {
+            self.undone.lock().unwrap().push((pid, "restore"));
+            Ok(())
+        }
+
+        fn adopt_freeze(&self, pid: u32, record: &FreezeRecord) {
+            self.adopted.lock().unwrap().push((pid, record.clone()));
+        }
+
+        fn exe_path(&self, pid: u32) -> Option<String> {
+            self.exe_paths.get(&pid).cloned()
+        }
+    }
+
+    /// State file path unique to one test, so tests can run in parallel
+    fn test_state_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "smartfreeze_test_{}_{}.json",
+            test,
+            std::process::id()
+        ))
+    }
 
     #[test]
     fn test_frozen_process_creation() {
@@ -184,6 +709,10 @@ mod tests {
         state.remove(1234);
         assert_eq!(state.frozen_processes.len(), 1);
         assert_eq!(state.frozen_processes[0].pid, 5678);
+        assert_eq!(
+            state.name_lookup().get(&5678),
+            Some(&("another.exe".to_string(), "C:\\another.exe".to_string()))
+        );
 
         state.clear();
         assert!(state.is_empty());
@@ -238,6 +767,79 @@ mod tests {
         persistence.delete().unwrap();
     }
 
+    #[test]
+    fn test_file_persistence_preserves_exe_path() {
+        let temp_path = std::env::temp_dir().join("smartfreeze_test_exe_path.json");
+        let persistence = FileStatePersistence::new(temp_path);
+
+        let mut state = PersistentState::new();
+        state.add(
+            4321,
+            "Spotify.exe".to_string(),
+            "C:\\Users\\User\\AppData\\Roaming\\Spotify\\Spotify.exe".to_string(),
+        );
+        persistence.save(&state).unwrap();
+
+        let loaded = persistence.load().unwrap().unwrap();
+        assert_eq!(
+            loaded.frozen_processes[0].exe_path,
+            "C:\\Users\\User\\AppData\\Roaming\\Spotify\\Spotify.exe"
+        );
+        assert_eq!(loaded, state);
+
+        persistence.delete().unwrap();
+    }
+
+    #[test]
+    fn test_file_persistence_save_replaces_corrupt_file() {
+        let temp_path = std::env::temp_dir().join("smartfreeze_test_atomic.json");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        // Simulate a save that was interrupted halfway through
+        fs::write(&temp_path, "{\"frozen_processes\": [{\"pid\": 12").unwrap();
+        assert!(persistence.load().is_err());
+
+        let mut state = PersistentState::new();
+        state.add(1234, "test.exe".to_string(), "C:\\test.exe".to_string());
+        persistence.save(&state).unwrap();
+
+        assert_eq!(persistence.load().unwrap().unwrap(), state);
+        assert!(!tmp_path_for(&temp_path).exists());
+
+        persistence.delete().unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v0_state() {
+        let v0 = r#"{"frozen_processes": [
+            {"pid": 1234, "name": "chrome.exe", "exe_path": "C:\\chrome.exe", "timestamp": 1700000000},
+            {"pid": 5678, "name": "old.exe", "timestamp": 1700000001}
+        ]}"#;
+
+        let state = PersistentState::from_json(v0).unwrap();
+
+        assert_eq!(state.version, STATE_VERSION);
+        assert_eq!(state.frozen_processes.len(), 2);
+        assert_eq!(state.frozen_processes[0].pid, 1234);
+        assert_eq!(state.frozen_processes[0].exe_path, "C:\\chrome.exe");
+        assert_eq!(state.frozen_processes[0].timestamp, 1700000000);
+        assert_eq!(state.frozen_processes[1].name, "old.exe");
+        assert_eq!(state.frozen_processes[1].exe_path, "");
+    }
+
+    #[test]
+    fn test_reject_future_state_version() {
+        let future = format!(
+            r#"{{"version": {}, "frozen_processes": []}}"#,
+            STATE_VERSION + 1
+        );
+
+        assert!(matches!(
+            PersistentState::from_json(&future),
+            Err(SmartFreezeError::StateVersion { .. })
+        ));
+    }
+
     #[test]
     fn test_file_persistence_load_nonexistent() {
         let temp_path = std::env::temp_dir().join("smartfreeze_nonexistent.json");
@@ -267,4 +869,388 @@ mod tests {
         // Delete again (should not error)
         assert!(persistence.delete().is_ok());
     }
+
+    #[test]
+    fn test_list_frozen_age_and_staleness() {
+        let temp_path = std::env::temp_dir().join("smartfreeze_test_list_frozen.json");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let json = format!(
+            r#"{{"frozen_processes": [
+                {{"pid": 1, "name": "recent.exe", "exe_path": "C:\\r.exe", "timestamp": {}}},
+                {{"pid": 2, "name": "old.exe", "exe_path": "C:\\o.exe", "timestamp": {}}}
+            ]}}"#,
+            now - 120,
+            now - MAX_STATE_AGE_SECS - 60
+        );
+        fs::write(&temp_path, json).unwrap();
+
+        let persistence = FileStatePersistence::new(temp_path);
+        let state = persistence.load().unwrap().unwrap();
+        let recent = &state.frozen_processes[0];
+        let old = &state.frozen_processes[1];
+
+        assert!((120..125).contains(&recent.age_secs()));
+        assert!(!recent.is_stale());
+        assert!(old.age_secs() > MAX_STATE_AGE_SECS);
+        assert!(old.is_stale());
+
+        persistence.delete().unwrap();
+    }
+
+    #[test]
+    fn test_contains_checks_saved_pids() {
+        let temp_path = std::env::temp_dir().join("smartfreeze_test_contains.json");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+        let _ = persistence.delete();
+        assert!(!persistence.contains(1234).unwrap());
+
+        fs::write(
+            &temp_path,
+            r#"{"frozen_processes": [
+                {"pid": 1234, "name": "chrome.exe", "exe_path": "C:\\chrome.exe", "timestamp": 0}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert!(persistence.contains(1234).unwrap());
+        assert!(!persistence.contains(5678).unwrap());
+
+        persistence.delete().unwrap();
+    }
+
+    #[test]
+    fn test_daemon_settings_save_load() {
+        let path = std::env::temp_dir()
+            .join("smartfreeze_test_settings")
+            .join("settings.json");
+        let _ = fs::remove_file(&path);
+
+        // Missing file means enabled
+        assert!(TraySettings::load_from(&path).unwrap().enabled);
+
+        let settings = TraySettings {
+            enabled: false,
+            ..TraySettings::default()
+        };
+        settings.save_to(&path).unwrap();
+        assert_eq!(TraySettings::load_from(&path).unwrap(), settings);
+
+        // Unknown layouts fall back to the default for missing keys
+        fs::write(&path, "{}").unwrap();
+        assert!(TraySettings::load_from(&path).unwrap().enabled);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_enabled_keeps_other_settings_and_unreadable_files() {
+        let path = std::env::temp_dir()
+            .join("smartfreeze_test_save_enabled")
+            .join("settings.json");
+        let _ = fs::remove_file(&path);
+
+        let settings = TraySettings {
+            enabled: true,
+            icon_color: [220, 20, 60],
+        };
+        settings.save_to(&path).unwrap();
+        TraySettings::save_enabled(&path, false).unwrap();
+        let loaded = TraySettings::load_from(&path).unwrap();
+        assert!(!loaded.enabled);
+        assert_eq!(loaded.icon_color, [220, 20, 60]);
+        assert!(!tmp_path_for(&path).exists());
+
+        // A corrupt file is reported and kept as it is
+        fs::write(&path, "{ not json").unwrap();
+        assert!(TraySettings::save_enabled(&path, true).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tray_settings_icon_color() {
+        let path = std::env::temp_dir()
+            .join("smartfreeze_test_icon_color")
+            .join("settings.json");
+        let _ = fs::remove_file(&path);
+
+        // Missing file means the default color
+        assert_eq!(
+            TraySettings::load_from(&path).unwrap().icon_color,
+            DEFAULT_ICON_COLOR
+        );
+
+        let settings = TraySettings {
+            enabled: false,
+            icon_color: [220, 20, 60],
+        };
+        settings.save_to(&path).unwrap();
+        assert_eq!(TraySettings::load_from(&path).unwrap(), settings);
+
+        // Settings saved before the color existed keep working
+        fs::write(&path, r#"{"enabled": false}"#).unwrap();
+        let loaded = TraySettings::load_from(&path).unwrap();
+        assert!(!loaded.enabled);
+        assert_eq!(loaded.icon_color, DEFAULT_ICON_COLOR);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tray_settings_json_layout() {
+        let json = serde_json::to_value(TraySettings::default()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"enabled": true, "icon_color": [64, 128, 255]})
+        );
+    }
+
+    #[test]
+    fn test_daemon_lock_is_exclusive() {
+        let path = std::env::temp_dir().join("smartfreeze_test_daemon.lock");
+
+        let first = DaemonLock::acquire_at(path.clone()).unwrap();
+        assert!(matches!(
+            DaemonLock::acquire_at(path.clone()),
+            Err(SmartFreezeError::AlreadyRunning(_))
+        ));
+
+        drop(first);
+        let again = DaemonLock::acquire_at(path.clone());
+        assert!(again.is_ok());
+    }
+
+    #[test]
+    fn test_resume_all_from_state() {
+        let temp_path = std::env::temp_dir().join("smartfreeze_test_resume_all.json");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let mut state = PersistentState::new();
+        state.add(1, "ok.exe".to_string(), "C:\\ok.exe".to_string());
+        state.add(2, "gone.exe".to_string(), "C:\\gone.exe".to_string());
+        let mut stale = FrozenProcess::new(3, "stale.exe".to_string(), "C:\\s.exe".to_string());
+        stale.timestamp = 0;
+        state.frozen_processes.push(stale);
+        persistence.save(&state).unwrap();
+
+        let controller = FailingController {
+            fail: vec![2],
+            ..Default::default()
+        };
+        let report = resume_all_from_state(&persistence, &controller)
+            .unwrap()
+            .unwrap();
+
+        // Frozen for over an hour, but still ours: resumed all the same
+        assert_eq!(report.resumed, vec![1, 3]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, 2);
+
+        // Only the failed entry stays on disk
+        let left = persistence.load().unwrap().unwrap();
+        assert_eq!(left.frozen_processes.len(), 1);
+        assert_eq!(left.frozen_processes[0].pid, 2);
+
+        let controller = FailingController::default();
+        resume_all_from_state(&persistence, &controller).unwrap();
+        assert!(!temp_path.exists());
+        assert!(resume_all_from_state(&persistence, &controller)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_resume_all_checks_exe_and_hands_over_records() {
+        let temp_path = test_state_path("resume_checks");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let record = FreezeRecord {
+            threads: vec![(100, 0), (101, 1)],
+            priority: None,
+        };
+        let mut state = PersistentState::new();
+        state.push(
+            FrozenProcess::new(
+                1,
+                "chrome.exe".to_string(),
+                "C:\\Chrome\\chrome.exe".to_string(),
+            )
+            .with_record(record.clone()),
+        );
+        state.add(2, "reused.exe".to_string(), "C:\\reused.exe".to_string());
+        state.add(3, "exited.exe".to_string(), "C:\\exited.exe".to_string());
+        persistence.save(&state).unwrap();
+
+        let controller = FailingController {
+            gone: vec![3],
+            exe_paths: HashMap::from([
+                (1, "c:\\chrome\\CHROME.EXE".to_string()),
+                (2, "C:\\other.exe".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let report = resume_all_from_state(&persistence, &controller)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.resumed, vec![1]);
+        assert!(report.failed.is_empty());
+        let skipped: Vec<u32> = report.skipped.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(skipped, vec![2, 3]);
+        // The reused PID was never touched; the others got their records first
+        assert_eq!(
+            *controller.adopted.lock().unwrap(),
+            vec![(1, record), (3, FreezeRecord::default())]
+        );
+        assert!(!temp_path.exists());
+    }
+
+    #[test]
+    fn test_resume_all_undoes_each_mode() {
+        let temp_path = test_state_path("resume_modes");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let entry = |pid: u32, mode: Option<FreezeMode>, threads: Vec<(u32, u32)>| {
+            FrozenProcess::new(pid, format!("{}.exe", pid), String::new())
+                .with_mode(mode)
+                .with_record(FreezeRecord {
+                    threads,
+                    priority: Some((0x20, 0b1111)),
+                })
+        };
+        let mut state = PersistentState::new();
+        state.push(entry(1, None, vec![(10, 0)]));
+        state.push(entry(2, Some(FreezeMode::Soft), Vec::new()));
+        state.push(entry(3, Some(FreezeMode::Soft), vec![(30, 0)]));
+        state.push(entry(4, Some(FreezeMode::Throttle), Vec::new()));
+        persistence.save(&state).unwrap();
+        assert_eq!(persistence.load().unwrap().unwrap(), state);
+
+        let controller = FailingController::default();
+        let report = resume_all_from_state(&persistence, &controller)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.resumed, vec![1, 2, 3, 4]);
+        assert_eq!(
+            *controller.undone.lock().unwrap(),
+            vec![
+                (1, "resume"),
+                (2, "restore"),
+                (3, "resume"),
+                (3, "restore"),
+                (4, "unthrottle"),
+            ]
+        );
+        assert!(!temp_path.exists());
+    }
+
+    #[test]
+    fn test_resume_all_keeps_entries_without_thread_record() {
+        let temp_path = test_state_path("resume_untracked");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let mut state = PersistentState::new();
+        state.add(5, "old.exe".to_string(), String::new());
+        persistence.save(&state).unwrap();
+
+        let controller = FailingController {
+            untracked: vec![5],
+            ..Default::default()
+        };
+        let report = resume_all_from_state(&persistence, &controller)
+            .unwrap()
+            .unwrap();
+
+        assert!(report.resumed.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(persistence.load().unwrap().unwrap(), state);
+
+        let _ = fs::remove_file(temp_path);
+    }
+
+    #[test]
+    fn test_record_survives_save_load() {
+        let temp_path = test_state_path("record_round_trip");
+        let persistence = FileStatePersistence::new(temp_path);
+
+        let mut state = PersistentState::new();
+        state.push(
+            FrozenProcess::new(7, "app.exe".to_string(), "C:\\app.exe".to_string()).with_record(
+                FreezeRecord {
+                    threads: vec![(70, 0)],
+                    priority: Some((0x20, 0b1111)),
+                },
+            ),
+        );
+        persistence.save(&state).unwrap();
+
+        assert_eq!(persistence.load().unwrap().unwrap(), state);
+        persistence.delete().unwrap();
+    }
+
+    #[test]
+    fn test_uninstall_resumes_and_clears_state() {
+        let temp_path = test_state_path("uninstall");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let mut state = PersistentState::new();
+        state.add(1, "ok.exe".to_string(), "C:\\ok.exe".to_string());
+        state.add(2, "gone.exe".to_string(), "C:\\gone.exe".to_string());
+        let mut stale = FrozenProcess::new(3, "stale.exe".to_string(), "C:\\s.exe".to_string());
+        stale.timestamp = 0;
+        state.push(stale);
+        persistence.save(&state).unwrap();
+
+        let mut uninstalled = false;
+        let controller = FailingController {
+            fail: vec![2],
+            ..Default::default()
+        };
+        let report = uninstall_and_resume(
+            || {
+                uninstalled = true;
+                Ok(())
+            },
+            &persistence,
+            &controller,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(uninstalled);
+        // Entries older than an hour are resumed before the file goes too
+        assert_eq!(report.resumed, vec![1, 3]);
+        assert_eq!(report.failed[0].0, 2);
+        // Cleared even though PID 2 failed to resume
+        assert!(!temp_path.exists());
+
+        let report = uninstall_and_resume(|| Ok(()), &persistence, &controller).unwrap();
+        assert!(report.is_none());
+    }
+
+    #[test]
+    fn test_failed_uninstall_keeps_state() {
+        let temp_path = test_state_path("uninstall_failed");
+        let persistence = FileStatePersistence::new(temp_path.clone());
+
+        let mut state = PersistentState::new();
+        state.add(1, "ok.exe".to_string(), "C:\\ok.exe".to_string());
+        persistence.save(&state).unwrap();
+
+        let controller = FailingController::default();
+        let result = uninstall_and_resume(
+            || Err(SmartFreezeError::Registry("access denied".to_string())),
+            &persistence,
+            &controller,
+        );
+
+        assert!(matches!(result, Err(SmartFreezeError::Registry(_))));
+        assert_eq!(persistence.load().unwrap().unwrap(), state);
+        persistence.delete().unwrap();
+    }
 }
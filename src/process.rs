@@ -1,9 +1,10 @@
 //! Process information and categorization types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Process importance category
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProcessCategory {
     /// Critical system processes (never freeze)
     Critical,
@@ -17,6 +18,8 @@ pub enum ProcessCategory {
     Productivity,
     /// Unknown/uncategorized processes
     Unknown,
+    /// Couldn't be opened (listed with `--show-inaccessible`, never frozen)
+    Inaccessible,
 }
 
 impl ProcessCategory {
@@ -28,20 +31,52 @@ impl ProcessCategory {
             ProcessCategory::BackgroundService => "Background",
             ProcessCategory::Productivity => "Productivity",
             ProcessCategory::Unknown => "Unknown",
+            ProcessCategory::Inaccessible => "Inaccessible",
         }
     }
 }
 
 /// Represents a process with its resource usage
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessInfo {
     pub pid: u32,
+    pub parent_pid: u32,
     pub name: String,
     pub full_path: String,
     pub memory_mb: u64,
+    #[serde(default)]
     pub cpu_percent: f64,
+    /// Seconds since the process was created (`None` if unknown)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_secs: Option<u64>,
+    #[serde(default)]
+    pub thread_count: u32,
+    /// Open handles (0 if the process couldn't be opened)
+    #[serde(default)]
+    pub handle_count: u32,
     pub is_foreground: bool,
+    /// All threads suspended (only checked with `--detect-suspended`)
+    #[serde(default)]
+    pub is_suspended: bool,
+    /// Terminal Services session the process runs in
+    #[serde(default)]
+    pub session_id: u32,
     pub category: ProcessCategory,
+    /// Code-signing subject of the executable, if it was looked up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// Why the categorizer chose `category` (shown with `--verbose`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_reason: Option<String>,
+    /// Owns a visible, non-minimized top-level window
+    #[serde(default)]
+    pub has_visible_window: bool,
+    /// Full command line, if it could be read (protected processes hide it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    /// Whether the engine would freeze this process (`None` if not evaluated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freezable: Option<bool>,
 }
 
 impl ProcessInfo {
@@ -55,12 +90,23 @@ impl ProcessInfo {
     ) -> Self {
         Self {
             pid,
+            parent_pid: 0,
             name,
             full_path,
             memory_mb,
             cpu_percent: 0.0,
+            age_secs: None,
+            thread_count: 0,
+            handle_count: 0,
             is_foreground,
+            is_suspended: false,
+            session_id: 0,
             category,
+            publisher: None,
+            category_reason: None,
+            has_visible_window: false,
+            command_line: None,
+            freezable: None,
         }
     }
 
@@ -69,10 +115,39 @@ impl ProcessInfo {
         !self.is_foreground
             && self.category != ProcessCategory::Critical
             && self.category != ProcessCategory::Gaming
+            && self.category != ProcessCategory::Inaccessible
             && !(keep_communication && self.category == ProcessCategory::Communication)
     }
 }
 
+/// A top-level window as seen by `EnumWindows`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopLevelWindow {
+    pub pid: u32,
+    pub visible: bool,
+    pub minimized: bool,
+}
+
+/// PIDs owning at least one visible, non-minimized top-level window
+pub fn pids_with_visible_windows(windows: &[TopLevelWindow]) -> HashSet<u32> {
+    windows
+        .iter()
+        .filter(|w| w.pid != 0 && w.visible && !w.minimized)
+        .map(|w| w.pid)
+        .collect()
+}
+
+/// Whether a process is suspended, given each thread's suspend count
+///
+/// `None` marks a thread whose count couldn't be read, which counts as
+/// running; a process with no threads is not suspended.
+pub fn threads_suspended(suspend_counts: &[Option<u32>]) -> bool {
+    !suspend_counts.is_empty()
+        && suspend_counts
+            .iter()
+            .all(|count| count.is_some_and(|count| count > 0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +217,78 @@ mod tests {
         assert!(process.is_safe_to_freeze(false));
         assert!(process.is_safe_to_freeze(true));
     }
+
+    #[test]
+    fn test_process_info_json_round_trip() {
+        let mut chrome = ProcessInfo::new(
+            1234,
+            "chrome.exe".to_string(),
+            "C:\\Program Files\\Google\\Chrome\\chrome.exe".to_string(),
+            500,
+            false,
+            ProcessCategory::Productivity,
+        );
+        chrome.parent_pid = 1000;
+        chrome.cpu_percent = 12.5;
+        chrome.age_secs = Some(3600);
+        chrome.freezable = Some(true);
+        let discord = ProcessInfo::new(
+            5678,
+            "discord.exe".to_string(),
+            "C:\\Discord\\discord.exe".to_string(),
+            300,
+            true,
+            ProcessCategory::Communication,
+        );
+        let processes = vec![chrome, discord];
+
+        let json = serde_json::to_string(&processes).unwrap();
+        let parsed: Vec<ProcessInfo> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, processes);
+    }
+
+    #[test]
+    fn test_process_info_parses_without_cpu_percent() {
+        let json = r#"{"pid":42,"parent_pid":4,"name":"app.exe","full_path":"C:\\app.exe",
+            "memory_mb":150,"is_foreground":false,"category":"BackgroundService"}"#;
+
+        let process: ProcessInfo = serde_json::from_str(json).unwrap();
+
+        assert_eq!(process.pid, 42);
+        assert_eq!(process.cpu_percent, 0.0);
+        assert_eq!(process.freezable, None);
+    }
+
+    #[test]
+    fn test_threads_suspended() {
+        assert!(threads_suspended(&[Some(1), Some(2)]));
+        assert!(!threads_suspended(&[Some(1), Some(0)]));
+        assert!(!threads_suspended(&[Some(1), None]));
+        assert!(!threads_suspended(&[]));
+    }
+
+    #[test]
+    fn test_pids_with_visible_windows() {
+        let window = |pid, visible, minimized| TopLevelWindow {
+            pid,
+            visible,
+            minimized,
+        };
+        let windows = [
+            // 10: a hidden helper window and a visible main window
+            window(10, false, false),
+            window(10, true, false),
+            // 20: only minimized
+            window(20, true, true),
+            // 30: only hidden
+            window(30, false, false),
+            window(40, true, false),
+            // Owner unknown
+            window(0, true, false),
+        ];
+
+        let pids = pids_with_visible_windows(&windows);
+        assert_eq!(pids, HashSet::from([10, 40]));
+    }
 }
@@ -0,0 +1,261 @@
+//! Canned enumerator and recording controller that run the engine on any OS
+//!
+//! Built with the `testing` feature; `--demo` runs the output path on these.
+
+use crate::categorization::{DefaultCategorizer, ProcessCategorizer};
+use crate::freeze_engine::{FreezeRecord, ProcessController, ProcessEnumerator};
+use crate::process::ProcessInfo;
+use crate::{Result, SmartFreezeError};
+use std::sync::Mutex;
+
+/// PID of the game in the `demo` snapshot (also the foreground process)
+pub const DEMO_GAME_PID: u32 = 4200;
+
+/// Enumerator returning a fixed snapshot instead of the running processes
+pub struct MockProcessEnumerator {
+    pub processes: Vec<ProcessInfo>,
+    pub foreground_pid: Option<u32>,
+    pub fullscreen: bool,
+    pub system_memory: Option<(u64, u64)>,
+    pub session_id: Option<u32>,
+    pub idle_secs: Option<u64>,
+    /// Snapshots returned by successive `enumerate` calls (last one repeats)
+    pub sequence: Vec<Vec<ProcessInfo>>,
+}
+
+impl MockProcessEnumerator {
+    pub fn new(processes: Vec<ProcessInfo>, foreground_pid: Option<u32>) -> Self {
+        Self {
+            processes,
+            foreground_pid,
+            fullscreen: false,
+            system_memory: None,
+            session_id: None,
+            idle_secs: None,
+            sequence: Vec::new(),
+        }
+    }
+
+    pub fn with_sequence(sequence: Vec<Vec<ProcessInfo>>) -> Self {
+        Self {
+            sequence,
+            ..Self::new(Vec::new(), None)
+        }
+    }
+
+    /// A typical gaming desktop, categorized by the built-in rules
+    pub fn demo() -> Self {
+        const DEMO: [(u32, &str, &str, u64, f64); 9] = [
+            (4, "System", "", 1, 0.1),
+            (612, "csrss.exe", "C:\\Windows\\System32\\csrss.exe", 6, 0.0),
+            (2140, "explorer.exe", "C:\\Windows\\explorer.exe", 180, 0.4),
+            (
+                DEMO_GAME_PID,
+                "eldenring.exe",
+                "D:\\SteamLibrary\\steamapps\\common\\ELDEN RING\\Game\\eldenring.exe",
+                7400,
+                61.0,
+            ),
+            (
+                3310,
+                "chrome.exe",
+                "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe",
+                1850,
+                3.2,
+            ),
+            (
+                3384,
+                "Discord.exe",
+                "C:\\Users\\demo\\AppData\\Local\\Discord\\app-1.0.9\\Discord.exe",
+                420,
+                0.8,
+            ),
+            (
+                3520,
+                "Spotify.exe",
+                "C:\\Users\\demo\\AppData\\Roaming\\Spotify\\Spotify.exe",
+                310,
+                0.6,
+            ),
+            (
+                3790,
+                "Code.exe",
+                "C:\\Users\\demo\\AppData\\Local\\Programs\\Microsoft VS Code\\Code.exe",
+                960,
+                1.1,
+            ),
+            (
+                3902,
+                "OneDrive.exe",
+                "C:\\Program Files\\Microsoft OneDrive\\OneDrive.exe",
+                75,
+                0.2,
+            ),
+        ];
+
+        let categorizer = DefaultCategorizer::new();
+        let processes = DEMO
+            .iter()
+            .map(|&(pid, name, path, memory_mb, cpu_percent)| {
+                let (category, reason) = categorizer.categorize_with_reason(pid, name, path);
+                let mut process = ProcessInfo::new(
+                    pid,
+                    name.to_string(),
+                    path.to_string(),
+                    memory_mb,
+                    pid == DEMO_GAME_PID,
+                    category,
+                );
+                process.parent_pid = if path.is_empty() { 0 } else { 2140 };
+                process.cpu_percent = cpu_percent;
+                process.age_secs = Some(3600);
+                process.category_reason = Some(reason);
+                process
+            })
+            .collect();
+
+        Self {
+            fullscreen: true,
+            system_memory: Some((32768, 6144)),
+            ..Self::new(processes, Some(DEMO_GAME_PID))
+        }
+    }
+}
+
+impl ProcessEnumerator for MockProcessEnumerator {
+    fn enumerate(&mut self) -> Result<Vec<ProcessInfo>> {
+        if !self.sequence.is_empty() {
+            self.processes = self.sequence.remove(0);
+        }
+        Ok(self.processes.clone())
+    }
+
+    fn get_foreground_pid(&self) -> Option<u32> {
+        self.foreground_pid
+    }
+
+    fn is_foreground_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    fn system_memory_mb(&self) -> Option<(u64, u64)> {
+        self.system_memory
+    }
+
+    fn current_session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+
+    fn idle_seconds(&self) -> Option<u64> {
+        self.idle_secs
+    }
+}
+
+/// Controller that suspends nothing and only records which PIDs are frozen
+#[derive(Debug, Default)]
+pub struct MockProcessController {
+    frozen: Mutex<Vec<u32>>,
+}
+
+impl MockProcessController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// PIDs currently frozen, in the order they were frozen
+    pub fn frozen_pids(&self) -> Vec<u32> {
+        self.frozen.lock().unwrap().clone()
+    }
+}
+
+impl ProcessController for MockProcessController {
+    fn freeze(&self, pid: u32) -> Result<usize> {
+        let mut frozen = self.frozen.lock().unwrap();
+        if frozen.contains(&pid) {
+            return Err(SmartFreezeError::AlreadyFrozen { pid });
+        }
+        frozen.push(pid);
+        Ok(1)
+    }
+
+    fn resume(&self, pid: u32) -> Result<usize> {
+        let mut frozen = self.frozen.lock().unwrap();
+        let before = frozen.len();
+        frozen.retain(|&p| p != pid);
+        Ok(before - frozen.len())
+    }
+
+    /// One fake thread per frozen PID, like the single thread `freeze` reports
+    fn freeze_record(&self, pid: u32) -> FreezeRecord {
+        FreezeRecord {
+            threads: if self.frozen.lock().unwrap().contains(&pid) {
+                vec![(pid, 0)]
+            } else {
+                Vec::new()
+            },
+            priority: None,
+        }
+    }
+
+    /// Like a fresh run of the real controller: only recorded freezes can be resumed
+    fn adopt_freeze(&self, pid: u32, record: &FreezeRecord) {
+        let mut frozen = self.frozen.lock().unwrap();
+        if !record.threads.is_empty() && !frozen.contains(&pid) {
+            frozen.push(pid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+
+    #[test]
+    fn test_demo_snapshot_is_categorized() {
+        let demo = MockProcessEnumerator::demo();
+        let category = |pid: u32| {
+            demo.processes
+                .iter()
+                .find(|p| p.pid == pid)
+                .map(|p| p.category)
+                .unwrap()
+        };
+
+        assert_eq!(category(DEMO_GAME_PID), ProcessCategory::Gaming);
+        assert_eq!(category(612), ProcessCategory::Critical);
+        assert_eq!(category(3384), ProcessCategory::Communication);
+        assert_eq!(demo.get_foreground_pid(), Some(DEMO_GAME_PID));
+    }
+
+    #[test]
+    fn test_controller_records_freezes() {
+        let controller = MockProcessController::new();
+
+        assert_eq!(controller.freeze(10).unwrap(), 1);
+        assert!(matches!(
+            controller.freeze(10),
+            Err(SmartFreezeError::AlreadyFrozen { pid: 10 })
+        ));
+        assert_eq!(controller.frozen_pids(), vec![10]);
+        assert_eq!(controller.resume(10).unwrap(), 1);
+        assert_eq!(controller.resume(10).unwrap(), 0);
+        assert!(controller.frozen_pids().is_empty());
+    }
+
+    #[test]
+    fn test_controller_adopts_recorded_freezes() {
+        let first = MockProcessController::new();
+        first.freeze(10).unwrap();
+        let record = first.freeze_record(10);
+
+        // A new controller knows nothing until the record is handed over
+        let second = MockProcessController::new();
+        assert_eq!(second.resume(10).unwrap(), 0);
+        second.adopt_freeze(10, &record);
+        assert_eq!(second.resume(10).unwrap(), 1);
+
+        second.adopt_freeze(11, &FreezeRecord::default());
+        assert!(second.frozen_pids().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Bounded waits for per-process work that can hang on a stuck process
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// How long one process's queries, freeze or resume may take before it is skipped
+pub const PROCESS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Run `op` on a worker thread, waiting at most `timeout` for its result
+///
+/// `None` if it took longer; the worker keeps running and its result is dropped.
+pub fn with_timeout<T, F>(timeout: Duration, op: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    with_timeout_or_else(timeout, op, drop)
+}
+
+/// `with_timeout`, handing a result that arrives too late to `on_late` on the
+/// worker thread (e.g. to undo a freeze the caller already reported as failed)
+pub fn with_timeout_or_else<T, F, L>(timeout: Duration, op: F, on_late: L) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+    L: FnOnce(T) + Send + 'static,
+{
+    // Zero capacity: a send only succeeds once the caller has received, so a
+    // result is either returned or handed to `on_late`, never lost in between
+    let (sender, receiver) = mpsc::sync_channel(0);
+    thread::spawn(move || {
+        if let Err(mpsc::SendError(late)) = sender.send(op()) {
+            on_late(late);
+        }
+    });
+    receiver.recv_timeout(timeout).ok()
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// One worker thread reused across `with_timeout`-style calls
+///
+/// A new thread is only spawned for the first call and after one times out;
+/// the stuck thread is abandoned and exits once its operation returns.
+#[derive(Default)]
+pub struct TimeoutWorker {
+    jobs: Option<mpsc::Sender<Job>>,
+}
+
+impl TimeoutWorker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `op` on the worker, waiting at most `timeout` for its result
+    pub fn run<T, F>(&mut self, timeout: Duration, op: F) -> Option<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let mut job: Job = Box::new(move || {
+            let _ = sender.send(op());
+        });
+
+        // A worker whose thread panicked refuses the job; start a fresh one
+        for _ in 0..2 {
+            let jobs = self.jobs.get_or_insert_with(spawn_worker);
+            match jobs.send(job) {
+                Ok(()) => break,
+                Err(mpsc::SendError(refused)) => {
+                    job = refused;
+                    self.jobs = None;
+                }
+            }
+        }
+
+        let result = receiver.recv_timeout(timeout).ok();
+        if result.is_none() {
+            self.jobs = None;
+        }
+        result
+    }
+}
+
+/// Start a thread running jobs until its sender is dropped
+fn spawn_worker() -> mpsc::Sender<Job> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    thread::spawn(move || {
+        for job in receiver {
+            job();
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const SHORT: Duration = Duration::from_millis(50);
+    const SLOW: Duration = Duration::from_secs(2);
+
+    /// An operation taking `delay`, then returning `value`
+    fn operation(delay: Duration, value: u32) -> impl FnOnce() -> u32 + Send + 'static {
+        move || {
+            thread::sleep(delay);
+            value
+        }
+    }
+
+    #[test]
+    fn test_fast_operation_returns_its_result() {
+        assert_eq!(with_timeout(SLOW, operation(Duration::ZERO, 7)), Some(7));
+    }
+
+    #[test]
+    fn test_slow_operation_is_skipped_without_blocking() {
+        let started = Instant::now();
+        let delays = [Duration::ZERO, SLOW, Duration::ZERO];
+
+        let done: Vec<u32> = delays
+            .iter()
+            .zip(1..)
+            .filter_map(|(&delay, pid)| with_timeout(SHORT, operation(delay, pid)))
+            .collect();
+
+        assert_eq!(done, vec![1, 3]);
+        assert!(started.elapsed() < SLOW);
+    }
+
+    #[test]
+    fn test_late_result_goes_to_on_late() {
+        let (late_sender, late_receiver) = mpsc::channel();
+
+        let result = with_timeout_or_else(SHORT, operation(SHORT * 4, 42), move |late| {
+            late_sender.send(late).unwrap();
+        });
+
+        assert_eq!(result, None);
+        assert_eq!(late_receiver.recv_timeout(SLOW), Ok(42));
+    }
+
+    #[test]
+    fn test_worker_reuses_its_thread_until_a_timeout() {
+        let mut worker = TimeoutWorker::new();
+        let first = worker.run(SLOW, || thread::current().id()).unwrap();
+        assert_eq!(worker.run(SLOW, || thread::current().id()), Some(first));
+
+        assert_eq!(worker.run(SHORT, operation(SLOW, 1)), None);
+
+        // The stuck thread is left behind; the next call gets a fresh one
+        let replaced = worker.run(SLOW, || thread::current().id()).unwrap();
+        assert_ne!(replaced, first);
+    }
+
+    #[test]
+    fn test_worker_recovers_from_a_panic() {
+        let mut worker = TimeoutWorker::new();
+        assert_eq!(worker.run(SLOW, || -> u32 { panic!("query failed") }), None);
+        assert_eq!(worker.run(SLOW, operation(Duration::ZERO, 9)), Some(9));
+    }
+
+    #[test]
+    fn test_result_in_time_skips_on_late() {
+        let (late_sender, late_receiver) = mpsc::channel();
+
+        let result = with_timeout_or_else(SLOW, operation(Duration::ZERO, 5), move |late| {
+            late_sender.send(late).unwrap();
+        });
+
+        assert_eq!(result, Some(5));
+        // The worker drops `on_late` unused once it has handed over the result
+        assert!(late_receiver.recv_timeout(SLOW).is_err());
+    }
+}
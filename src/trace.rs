@@ -0,0 +1,129 @@
+//! Decision tracing for debugging freeze selection
+
+use crate::process::ProcessInfo;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Outcome of evaluating a single process
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceDecision {
+    pub pid: u32,
+    pub name: String,
+    pub selected: bool,
+    pub reason: String,
+}
+
+/// One recorded call to `find_safe_to_freeze`
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEntry {
+    pub timestamp: u64,
+    pub min_memory_mb: u64,
+    pub keep_communication: bool,
+    pub input: Vec<ProcessInfo>,
+    pub decisions: Vec<TraceDecision>,
+}
+
+impl TraceEntry {
+    pub fn new(
+        min_memory_mb: u64,
+        keep_communication: bool,
+        input: Vec<ProcessInfo>,
+        decisions: Vec<TraceDecision>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            timestamp,
+            min_memory_mb,
+            keep_communication,
+            input,
+            decisions,
+        }
+    }
+
+    /// PIDs that were selected for freezing
+    pub fn selected_pids(&self) -> Vec<u32> {
+        self.decisions
+            .iter()
+            .filter(|d| d.selected)
+            .map(|d| d.pid)
+            .collect()
+    }
+}
+
+/// Trait for decision trace sinks (allows different storage backends)
+pub trait DecisionTrace: Send + Sync {
+    fn record(&self, entry: &TraceEntry) -> Result<()>;
+}
+
+/// Appends one JSON object per decision to a trace file
+pub struct FileDecisionTrace {
+    path: PathBuf,
+}
+
+impl FileDecisionTrace {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl DecisionTrace for FileDecisionTrace {
+    fn record(&self, entry: &TraceEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+
+    #[test]
+    fn test_file_trace_appends_lines() {
+        let path = std::env::temp_dir().join("smartfreeze_test_trace.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let trace = FileDecisionTrace::new(path.clone());
+
+        let process = ProcessInfo::new(
+            1234,
+            "chrome.exe".to_string(),
+            "C:\\chrome.exe".to_string(),
+            200,
+            false,
+            ProcessCategory::Productivity,
+        );
+        let decision = TraceDecision {
+            pid: 1234,
+            name: "chrome.exe".to_string(),
+            selected: true,
+            reason: "selected".to_string(),
+        };
+        let entry = TraceEntry::new(100, false, vec![process], vec![decision]);
+
+        trace.record(&entry).unwrap();
+        trace.record(&entry).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let parsed: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed["input"][0]["pid"], 1234);
+        assert_eq!(parsed["decisions"][0]["selected"], true);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
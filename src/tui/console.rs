@@ -0,0 +1,199 @@
+//! Windows console driver for the picker: raw key input and ANSI redraws
+
+use super::{Command, Key, TuiState};
+use crate::categorization::ProcessCategorizer;
+use crate::freeze_engine::{FreezeEngine, ProcessController, ProcessEnumerator};
+use crate::output::{CLEAR_SCREEN, HIDE_CURSOR, SHOW_CURSOR};
+use crate::persistence::StatePersistence;
+use crate::Result;
+use std::io::Write;
+use std::mem;
+use std::time::{Duration, Instant};
+use windows_sys::Win32::Foundation::{HANDLE, WAIT_OBJECT_0};
+use windows_sys::Win32::System::Console::{
+    GetConsoleMode, GetConsoleScreenBufferInfo, GetStdHandle, ReadConsoleInputW, SetConsoleMode,
+    CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT,
+    ENABLE_PROCESSED_INPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, INPUT_RECORD, KEY_EVENT,
+    STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+};
+use windows_sys::Win32::System::Threading::WaitForSingleObject;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    VK_DOWN, VK_END, VK_ESCAPE, VK_F5, VK_HOME, VK_NEXT, VK_PRIOR, VK_RETURN, VK_SPACE, VK_UP,
+};
+
+/// Lines used by the header and footer around the list
+const CHROME_LINES: usize = 6;
+/// Ctrl+C, delivered as a key because processed input is turned off
+const CTRL_C: u16 = 3;
+
+/// Map a console key event to a picker key
+fn key_from_event(virtual_key: u16, unicode_char: u16) -> Option<Key> {
+    match virtual_key {
+        VK_UP => return Some(Key::Up),
+        VK_DOWN => return Some(Key::Down),
+        VK_PRIOR => return Some(Key::PageUp),
+        VK_NEXT => return Some(Key::PageDown),
+        VK_HOME => return Some(Key::Home),
+        VK_END => return Some(Key::End),
+        VK_SPACE => return Some(Key::Space),
+        VK_RETURN => return Some(Key::Enter),
+        VK_F5 => return Some(Key::Refresh),
+        VK_ESCAPE => return Some(Key::Quit),
+        _ => {}
+    }
+
+    if unicode_char == CTRL_C {
+        return Some(Key::Quit);
+    }
+    match char::from_u32(u32::from(unicode_char))?.to_ascii_lowercase() {
+        'r' => Some(Key::Resume),
+        'q' => Some(Key::Quit),
+        _ => None,
+    }
+}
+
+/// Puts the console into raw key mode and restores it when dropped
+struct RawConsole {
+    input: HANDLE,
+    output: HANDLE,
+    input_mode: CONSOLE_MODE,
+    output_mode: CONSOLE_MODE,
+}
+
+impl RawConsole {
+    fn enter() -> Result<Self> {
+        unsafe {
+            let input = GetStdHandle(STD_INPUT_HANDLE);
+            let output = GetStdHandle(STD_OUTPUT_HANDLE);
+
+            let mut input_mode = 0;
+            let mut output_mode = 0;
+            if GetConsoleMode(input, &mut input_mode) == 0 {
+                return Err(crate::windows::last_error("GetConsoleMode"));
+            }
+            GetConsoleMode(output, &mut output_mode);
+
+            SetConsoleMode(
+                input,
+                input_mode & !(ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT),
+            );
+            SetConsoleMode(output, output_mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+            print!("{}", HIDE_CURSOR);
+
+            Ok(Self {
+                input,
+                output,
+                input_mode,
+                output_mode,
+            })
+        }
+    }
+
+    /// Visible console rows (24 if unknown)
+    fn height(&self) -> usize {
+        unsafe {
+            let mut info: CONSOLE_SCREEN_BUFFER_INFO = mem::zeroed();
+            if GetConsoleScreenBufferInfo(self.output, &mut info) == 0 {
+                return 24;
+            }
+            (info.srWindow.Bottom - info.srWindow.Top + 1).max(1) as usize
+        }
+    }
+
+    /// Wait up to `timeout` for a key press
+    fn read_key(&self, timeout: Duration) -> Option<Key> {
+        unsafe {
+            if WaitForSingleObject(self.input, timeout.as_millis() as u32) != WAIT_OBJECT_0 {
+                return None;
+            }
+
+            let mut record: INPUT_RECORD = mem::zeroed();
+            let mut read = 0;
+            if ReadConsoleInputW(self.input, &mut record, 1, &mut read) == 0 || read == 0 {
+                return None;
+            }
+            if u32::from(record.EventType) != KEY_EVENT {
+                return None;
+            }
+
+            let event = record.Event.KeyEvent;
+            if event.bKeyDown == 0 {
+                return None;
+            }
+            key_from_event(event.wVirtualKeyCode, event.uChar.UnicodeChar)
+        }
+    }
+}
+
+impl Drop for RawConsole {
+    fn drop(&mut self) {
+        print!("{}", SHOW_CURSOR);
+        let _ = std::io::stdout().flush();
+        unsafe {
+            SetConsoleMode(self.input, self.input_mode);
+            SetConsoleMode(self.output, self.output_mode);
+        }
+    }
+}
+
+/// Run the picker until Q/Esc, refreshing the list every `refresh`
+///
+/// Freezes are saved to `persistence`, so they outlive the picker.
+pub fn run<E, C, Cat>(
+    engine: &mut FreezeEngine<E, C, Cat>,
+    persistence: &dyn StatePersistence,
+    refresh: Duration,
+) -> Result<()>
+where
+    E: ProcessEnumerator,
+    C: ProcessController,
+    Cat: ProcessCategorizer,
+{
+    let console = RawConsole::enter()?;
+    let mut state = TuiState::new();
+    state.set_processes(engine.evaluate_processes()?);
+    let mut refreshed = Instant::now();
+
+    loop {
+        let page = console.height().saturating_sub(CHROME_LINES).max(1);
+        print!("{}{}", CLEAR_SCREEN, state.render(page));
+        let _ = std::io::stdout().flush();
+
+        let wait = refresh.saturating_sub(refreshed.elapsed());
+        let command = match console.read_key(wait) {
+            Some(key) => state.handle_key(key, page),
+            None if refreshed.elapsed() >= refresh => Command::Refresh,
+            None => Command::None,
+        };
+
+        match command {
+            Command::None => {}
+            // Same refusals as --action freeze: critical, foreground, SmartFreeze and its console
+            Command::Freeze(pids) => state.freeze(engine, persistence, &pids)?,
+            Command::Resume(pids) => state.resume(engine, persistence, &pids),
+            Command::Refresh => {
+                state.set_processes(engine.evaluate_processes()?);
+                refreshed = Instant::now();
+            }
+            Command::Quit => break,
+        }
+    }
+
+    print!("{}", CLEAR_SCREEN);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_from_event() {
+        assert_eq!(key_from_event(VK_UP, 0), Some(Key::Up));
+        assert_eq!(key_from_event(VK_SPACE, u16::from(b' ')), Some(Key::Space));
+        assert_eq!(key_from_event(0x52, u16::from(b'R')), Some(Key::Resume));
+        assert_eq!(key_from_event(0x51, u16::from(b'q')), Some(Key::Quit));
+        assert_eq!(key_from_event(0x43, CTRL_C), Some(Key::Quit));
+        assert_eq!(key_from_event(0x58, u16::from(b'x')), None);
+    }
+}
@@ -0,0 +1,488 @@
+//! Interactive process picker (`--interactive`, behind the `tui` feature)
+//!
+//! `TuiState` holds the list, cursor and selection and turns key presses into
+//! commands; it renders to a plain string so it can be tested without a console.
+//! The console driver lives in `console` (Windows only).
+
+#[cfg(windows)]
+mod console;
+
+#[cfg(windows)]
+pub use console::run;
+
+use crate::categorization::ProcessCategorizer;
+use crate::freeze_engine::{FreezeEngine, FreezeMode, ProcessController, ProcessEnumerator};
+use crate::persistence::{record_manual_freezes, resume_manual_freezes, StatePersistence};
+use crate::process::ProcessInfo;
+use crate::{Result, SmartFreezeError};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Keys the picker reacts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    /// Toggle the row under the cursor
+    Space,
+    /// Freeze the selection
+    Enter,
+    /// Resume the selection
+    Resume,
+    /// Refresh the process list now
+    Refresh,
+    Quit,
+}
+
+/// What the driver must do after a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    None,
+    Freeze(Vec<u32>),
+    Resume(Vec<u32>),
+    Refresh,
+    Quit,
+}
+
+/// Process list, cursor and selection of the picker
+#[derive(Debug, Default)]
+pub struct TuiState {
+    rows: Vec<ProcessInfo>,
+    cursor: usize,
+    /// First row shown
+    scroll: usize,
+    selected: HashSet<u32>,
+    /// Frozen by this session, or found suspended during enumeration
+    frozen: HashSet<u32>,
+    status: String,
+}
+
+impl TuiState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the list, keeping the cursor, selection and frozen marks on PIDs still present
+    pub fn set_processes(&mut self, processes: Vec<ProcessInfo>) {
+        let cursor_pid = self.rows.get(self.cursor).map(|p| p.pid);
+        let present: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+
+        self.selected.retain(|pid| present.contains(pid));
+        self.frozen.retain(|pid| present.contains(pid));
+        self.frozen
+            .extend(processes.iter().filter(|p| p.is_suspended).map(|p| p.pid));
+
+        self.cursor = cursor_pid
+            .and_then(|pid| processes.iter().position(|p| p.pid == pid))
+            .unwrap_or(self.cursor)
+            .min(processes.len().saturating_sub(1));
+        self.rows = processes;
+    }
+
+    pub fn rows(&self) -> &[ProcessInfo] {
+        &self.rows
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_selected(&self, pid: u32) -> bool {
+        self.selected.contains(&pid)
+    }
+
+    pub fn is_frozen(&self, pid: u32) -> bool {
+        self.frozen.contains(&pid)
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Move the cursor by `delta` rows, clamped to the list
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Select or unselect the row under the cursor
+    pub fn toggle_selected(&mut self) {
+        if let Some(process) = self.rows.get(self.cursor) {
+            if !self.selected.remove(&process.pid) {
+                self.selected.insert(process.pid);
+            }
+        }
+    }
+
+    /// Selected PIDs in list order, or the row under the cursor if nothing is selected
+    pub fn selection(&self) -> Vec<u32> {
+        if self.selected.is_empty() {
+            return self
+                .rows
+                .get(self.cursor)
+                .map(|p| p.pid)
+                .into_iter()
+                .collect();
+        }
+        self.rows
+            .iter()
+            .map(|p| p.pid)
+            .filter(|pid| self.selected.contains(pid))
+            .collect()
+    }
+
+    /// Apply a key press; `page` is the number of rows visible at once
+    pub fn handle_key(&mut self, key: Key, page: usize) -> Command {
+        let page = page.max(1) as isize;
+        match key {
+            Key::Up => self.move_cursor(-1),
+            Key::Down => self.move_cursor(1),
+            Key::PageUp => self.move_cursor(-page),
+            Key::PageDown => self.move_cursor(page),
+            Key::Home => self.cursor = 0,
+            Key::End => self.move_cursor(isize::MAX),
+            Key::Space => {
+                self.toggle_selected();
+                self.move_cursor(1);
+            }
+            Key::Enter => return self.nonempty(Command::Freeze(self.selection())),
+            Key::Resume => return self.nonempty(Command::Resume(self.selection())),
+            Key::Refresh => return Command::Refresh,
+            Key::Quit => return Command::Quit,
+        }
+        Command::None
+    }
+
+    fn nonempty(&self, command: Command) -> Command {
+        match &command {
+            Command::Freeze(pids) | Command::Resume(pids) if pids.is_empty() => Command::None,
+            _ => command,
+        }
+    }
+
+    /// Record freeze outcomes: mark successes frozen and clear the selection
+    pub fn apply_freeze_results(&mut self, results: &[(u32, Result<usize>)]) {
+        let mut failed = Vec::new();
+        for (pid, result) in results {
+            match result {
+                Ok(_) | Err(SmartFreezeError::AlreadyFrozen { .. }) => {
+                    self.frozen.insert(*pid);
+                }
+                Err(e) => failed.push(format!("{}: {}", pid, e)),
+            }
+        }
+        self.finish("Froze", results.len() - failed.len(), failed);
+    }
+
+    /// Record resume outcomes: unmark successes and clear the selection
+    pub fn apply_resume_results(&mut self, results: &[(u32, Result<usize>)]) {
+        let mut failed = Vec::new();
+        for (pid, result) in results {
+            match result {
+                Ok(_) => {
+                    self.frozen.remove(pid);
+                }
+                Err(e) => failed.push(format!("{}: {}", pid, e)),
+            }
+        }
+        self.finish("Resumed", results.len() - failed.len(), failed);
+    }
+
+    /// Freeze `pids` with the same refusals as `--action freeze`
+    ///
+    /// Successes are saved with their freeze records, so `--action resume`,
+    /// `--resume-all` or a later session can still resume them after the
+    /// picker exits.
+    pub fn freeze<E, C, Cat>(
+        &mut self,
+        engine: &mut FreezeEngine<E, C, Cat>,
+        persistence: &dyn StatePersistence,
+        pids: &[u32],
+    ) -> Result<()>
+    where
+        E: ProcessEnumerator,
+        C: ProcessController,
+        Cat: ProcessCategorizer,
+    {
+        let results = engine.freeze_multiple_guarded(pids)?;
+        self.apply_freeze_results(&results);
+
+        let frozen: Vec<u32> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(pid, _)| *pid)
+            .collect();
+        if let Err(e) = record_manual_freezes(persistence, engine, &frozen) {
+            self.warn(&format!("state not saved: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Resume `pids` with the freeze records saved for them
+    ///
+    /// In suspend mode a resume that finds no recorded freeze changes nothing,
+    /// so the row stays marked frozen.
+    pub fn resume<E, C, Cat>(
+        &mut self,
+        engine: &FreezeEngine<E, C, Cat>,
+        persistence: &dyn StatePersistence,
+        pids: &[u32],
+    ) where
+        E: ProcessEnumerator,
+        C: ProcessController,
+        Cat: ProcessCategorizer,
+    {
+        let (results, saved) = resume_manual_freezes(persistence, engine, pids);
+        let suspend_mode = matches!(engine.config().mode, None | Some(FreezeMode::Suspend));
+        let results: Vec<_> = results
+            .into_iter()
+            .map(|(pid, result)| match result {
+                Ok(0) if suspend_mode => (
+                    pid,
+                    Err(SmartFreezeError::ResumeFailed {
+                        pid,
+                        reason: "no freeze recorded".to_string(),
+                    }),
+                ),
+                result => (pid, result),
+            })
+            .collect();
+        self.apply_resume_results(&results);
+
+        if let Err(e) = saved {
+            self.warn(&format!("saved state not updated: {}", e));
+        }
+    }
+
+    /// Append a problem to the status line
+    fn warn(&mut self, message: &str) {
+        self.status.push_str("; ");
+        self.status.push_str(message);
+    }
+
+    fn finish(&mut self, verb: &str, succeeded: usize, failed: Vec<String>) {
+        self.selected.clear();
+        self.status = if failed.is_empty() {
+            format!("{} {} process(es)", verb, succeeded)
+        } else {
+            format!(
+                "{} {} process(es); failed {}",
+                verb,
+                succeeded,
+                failed.join(", ")
+            )
+        };
+    }
+
+    /// Scroll so the cursor is within the `height` visible rows
+    fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+    }
+
+    /// Render a screen of `height` list rows plus header and footer
+    pub fn render(&mut self, height: usize) -> String {
+        self.scroll_to_cursor(height);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "SmartFreeze - {} processes, {} selected",
+            self.rows.len(),
+            self.selected.len()
+        );
+        let _ = writeln!(
+            out,
+            "  {:<3} {:<8} {:<40} {:>12} {:<10} {:<6}",
+            "", "PID", "Name", "Memory (MB)", "Category", "State"
+        );
+
+        for (index, process) in self.rows.iter().enumerate().skip(self.scroll).take(height) {
+            let _ = writeln!(
+                out,
+                "{} {:<3} {:<8} {:<40} {:>12} {:<10} {:<6}",
+                if index == self.cursor { '>' } else { ' ' },
+                if self.selected.contains(&process.pid) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                },
+                process.pid,
+                process.name,
+                process.memory_mb,
+                process.category.as_str(),
+                if self.frozen.contains(&process.pid) {
+                    "frozen"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "\n↑/↓ move  Space select  Enter freeze  R resume  F5 refresh  Q quit"
+        );
+        if !self.status.is_empty() {
+            let _ = writeln!(out, "{}", self.status);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessCategory;
+
+    fn process(pid: u32, memory_mb: u64) -> ProcessInfo {
+        ProcessInfo::new(
+            pid,
+            format!("p{}.exe", pid),
+            String::new(),
+            memory_mb,
+            false,
+            ProcessCategory::Productivity,
+        )
+    }
+
+    fn state_with(pids: &[u32]) -> TuiState {
+        let mut state = TuiState::new();
+        state.set_processes(pids.iter().map(|&pid| process(pid, 100)).collect());
+        state
+    }
+
+    #[test]
+    fn test_multi_select_and_freeze() {
+        let mut state = state_with(&[10, 20, 30]);
+
+        assert_eq!(state.handle_key(Key::Space, 10), Command::None);
+        state.handle_key(Key::Down, 10);
+        state.handle_key(Key::Space, 10);
+        assert_eq!(
+            state.handle_key(Key::Enter, 10),
+            Command::Freeze(vec![10, 30])
+        );
+
+        state.apply_freeze_results(&[
+            (10, Ok(4)),
+            (30, Err(SmartFreezeError::AccessDenied { pid: 30 })),
+        ]);
+        assert!(state.is_frozen(10));
+        assert!(!state.is_frozen(30));
+        // Selection cleared: back to the row under the cursor
+        assert_eq!(state.selection(), vec![30]);
+        assert!(state.status().contains("failed 30"));
+    }
+
+    #[test]
+    fn test_cursor_is_clamped() {
+        let mut state = state_with(&[1, 2, 3]);
+        state.handle_key(Key::Up, 10);
+        assert_eq!(state.cursor(), 0);
+        state.handle_key(Key::PageDown, 10);
+        assert_eq!(state.cursor(), 2);
+        state.handle_key(Key::End, 10);
+        assert_eq!(state.cursor(), 2);
+        state.handle_key(Key::Home, 10);
+        assert_eq!(state.cursor(), 0);
+
+        let mut empty = TuiState::new();
+        empty.handle_key(Key::Down, 10);
+        assert_eq!(empty.cursor(), 0);
+        assert_eq!(empty.handle_key(Key::Enter, 10), Command::None);
+    }
+
+    #[test]
+    fn test_refresh_keeps_cursor_and_marks() {
+        let mut state = state_with(&[1, 2, 3]);
+        state.handle_key(Key::Down, 10);
+        state.handle_key(Key::Down, 10);
+        state.apply_freeze_results(&[(1, Ok(1))]);
+        state.toggle_selected(); // selects 3
+
+        // 1 exited, 4 appeared already suspended, order changed
+        let mut suspended = process(4, 50);
+        suspended.is_suspended = true;
+        state.set_processes(vec![process(3, 900), suspended, process(2, 10)]);
+
+        assert_eq!(state.rows()[state.cursor()].pid, 3);
+        assert!(!state.is_frozen(1));
+        assert!(state.is_frozen(4));
+        assert_eq!(state.selection(), vec![3]);
+    }
+
+    #[test]
+    fn test_resume_unmarks_frozen() {
+        let mut state = state_with(&[1, 2]);
+        state.apply_freeze_results(&[(1, Ok(1)), (2, Ok(1))]);
+        state.apply_resume_results(&[(2, Ok(1))]);
+
+        assert!(state.is_frozen(1));
+        assert!(!state.is_frozen(2));
+        assert_eq!(state.status(), "Resumed 1 process(es)");
+    }
+
+    #[test]
+    fn test_picker_freeze_can_be_resumed_after_quitting() {
+        use crate::categorization::DefaultCategorizer;
+        use crate::freeze_engine::FreezeConfig;
+        use crate::persistence::FileStatePersistence;
+        use crate::testing::{MockProcessController, MockProcessEnumerator};
+
+        let path = std::env::temp_dir().join("smartfreeze_test_tui_state.json");
+        let _ = std::fs::remove_file(&path);
+        let persistence = FileStatePersistence::new(path.clone());
+        let session = || {
+            FreezeEngine::new(
+                MockProcessEnumerator::new(vec![process(10, 300)], None),
+                MockProcessController::new(),
+                DefaultCategorizer::new(),
+                FreezeConfig::default(),
+            )
+        };
+
+        let mut engine = session();
+        let mut state = TuiState::new();
+        state.set_processes(engine.evaluate_processes().unwrap());
+        state.freeze(&mut engine, &persistence, &[10]).unwrap();
+        assert!(state.is_frozen(10));
+        assert!(persistence.contains(10).unwrap());
+
+        // After quitting, a new session's controller only knows what was saved
+        let engine = session();
+        let mut state = TuiState::new();
+        let mut suspended = process(10, 300);
+        suspended.is_suspended = true;
+        state.set_processes(vec![suspended.clone()]);
+        state.resume(&engine, &persistence, &[10]);
+        assert!(!state.is_frozen(10));
+        assert_eq!(state.status(), "Resumed 1 process(es)");
+        assert!(!path.exists());
+
+        // Nothing recorded any more: the row stays frozen
+        state.set_processes(vec![suspended]);
+        state.resume(&engine, &persistence, &[10]);
+        assert!(state.is_frozen(10));
+        assert!(state.status().contains("no freeze recorded"));
+    }
+
+    #[test]
+    fn test_render_scrolls_to_cursor() {
+        let mut state = state_with(&[1, 2, 3, 4, 5]);
+        state.handle_key(Key::End, 2);
+        state.toggle_selected();
+
+        let screen = state.render(2);
+        assert!(screen.contains("> [x] 5 "));
+        assert!(screen.contains("  [ ] 4 "));
+        assert!(!screen.contains("[ ] 3 "));
+    }
+}
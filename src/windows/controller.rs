@@ -1,68 +1,502 @@
 //! Windows process control implementation
 
-use crate::freeze_engine::ProcessController;
+use super::enumerator::{image_path, thread_suspend_count};
+use super::handle::OwnedHandle;
+use super::registry::WindowsRegistry;
+use crate::freeze_engine::{
+    FreezeCapabilities, FreezeMode, FreezeRecord, ProcessController, WorkingSetTrim,
+};
+use crate::process::threads_suspended;
+use crate::timeout::{with_timeout_or_else, PROCESS_TIMEOUT};
 use crate::{Result, SmartFreezeError};
-use std::process::Command;
-use windows_sys::Win32::Foundation::CloseHandle;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use windows_sys::Win32::Foundation::{
+    GetLastError, ERROR_ACCESS_DENIED, ERROR_ELEVATION_REQUIRED, ERROR_INVALID_PARAMETER,
+    ERROR_MORE_DATA, HANDLE, STILL_ACTIVE,
+};
+use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+};
+use windows_sys::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicProcessIdList,
+    QueryInformationJobObject,
+};
+use windows_sys::Win32::System::ProcessStatus::{
+    EmptyWorkingSet, GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+};
 use windows_sys::Win32::System::Threading::{
-    OpenProcess, TerminateProcess, PROCESS_QUERY_INFORMATION, PROCESS_TERMINATE,
+    CreateProcessW, GetExitCodeProcess, GetPriorityClass, GetProcessAffinityMask, GetProcessId,
+    OpenProcess, OpenThread, ProcessPowerThrottling, ResumeThread, SetPriorityClass,
+    SetProcessAffinityMask, SetProcessInformation, SuspendThread, DETACHED_PROCESS,
+    IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_INFORMATION,
+    PROCESS_POWER_THROTTLING_CURRENT_VERSION, PROCESS_POWER_THROTTLING_EXECUTION_SPEED,
+    PROCESS_POWER_THROTTLING_STATE, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION,
+    PROCESS_SET_QUOTA, PROCESS_TERMINATE, STARTUPINFOW, THREAD_SUSPEND_RESUME,
 };
+use windows_sys::Win32::UI::Shell::{ShellExecuteExW, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// Return value of `SuspendThread`/`ResumeThread` on failure
+const THREAD_CALL_FAILED: u32 = u32::MAX;
+
+/// Most job members read back by `freeze_job`; later ones are ignored
+const MAX_JOB_MEMBERS: usize = 256;
+
+/// A job created by `freeze_job` and the PIDs frozen with it
+struct FrozenJob {
+    /// Keeps the job alive so `resume_job` can read back its members
+    job: OwnedHandle,
+    pids: Vec<u32>,
+}
+
+/// `JOBOBJECT_BASIC_PROCESS_ID_LIST` with room for `MAX_JOB_MEMBERS` PIDs
+#[repr(C)]
+struct JobProcessIdList {
+    assigned: u32,
+    listed: u32,
+    pids: [usize; MAX_JOB_MEMBERS],
+}
 
 /// Windows-specific process controller
-pub struct WindowsProcessController;
+///
+/// Freezing suspends every thread of the target process. The controller
+/// remembers which threads it suspended (and their previous suspend count)
+/// so that resuming only undoes its own suspensions. Clones share that record,
+/// which lets a freeze or resume run on a worker thread with a timeout.
+#[derive(Clone)]
+pub struct WindowsProcessController {
+    /// PID -> (thread ID, suspend count before our `SuspendThread`)
+    suspended: Arc<Mutex<HashMap<u32, Vec<(u32, u32)>>>>,
+    /// PID -> (priority class, affinity mask) before `set_priority_and_affinity`
+    original_priority: Arc<Mutex<HashMap<u32, (u32, usize)>>>,
+    /// Jobs frozen together by `freeze_job`
+    jobs: Arc<Mutex<Vec<FrozenJob>>>,
+    /// PIDs resumed after `resume` had already reported a timeout
+    late_resumes: Arc<Mutex<Vec<u32>>>,
+    /// Reads the calling thread's last Win32 error (replaceable in tests)
+    last_error: fn() -> u32,
+}
+
+/// `GetLastError` for the calling thread
+fn os_last_error() -> u32 {
+    unsafe { GetLastError() }
+}
+
+/// Map the error code of a failed thread call to a freeze error
+///
+/// `ERROR_INVALID_PARAMETER` usually means a thread exited mid-freeze, so it
+/// stays a retryable `FreezeFailed`; only `process_exists` can tell that the
+/// whole process is gone.
+fn freeze_error(pid: u32, code: u32) -> SmartFreezeError {
+    call_failed(pid, "SuspendThread", code)
+}
+
+/// Freeze error for a failed Win32 call on `pid`, keeping its error code
+fn call_failed(pid: u32, call: &str, code: u32) -> SmartFreezeError {
+    match code {
+        ERROR_ACCESS_DENIED => SmartFreezeError::AccessDenied { pid },
+        code => SmartFreezeError::FreezeFailed {
+            pid,
+            reason: format!("{} failed (error code {})", call, code),
+        },
+    }
+}
+
+/// Error for a freeze or resume abandoned after `PROCESS_TIMEOUT`
+fn timed_out(pid: u32, operation: &'static str) -> SmartFreezeError {
+    SmartFreezeError::TimedOut {
+        pid,
+        operation,
+        timeout_ms: PROCESS_TIMEOUT.as_millis() as u64,
+    }
+}
+
+/// Current working set of an open process in MB (0 if it can't be queried)
+fn working_set_mb(process_handle: HANDLE) -> u64 {
+    unsafe {
+        let mut pmc: PROCESS_MEMORY_COUNTERS = mem::zeroed();
+        pmc.cb = mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        if GetProcessMemoryInfo(process_handle, &mut pmc, pmc.cb) != 0 {
+            pmc.WorkingSetSize as u64 / (1024 * 1024)
+        } else {
+            0
+        }
+    }
+}
 
 impl WindowsProcessController {
     pub fn new() -> Self {
-        Self
+        Self::with_last_error(os_last_error)
     }
 
-    /// Restart a process from its executable path
-    pub fn restart_process(&self, exe_path: &str) -> Result<u32> {
-        // Start the process detached (no window, background)
-        match Command::new(exe_path).spawn() {
-            Ok(child) => Ok(child.id()),
-            Err(e) => Err(SmartFreezeError::ResumeFailed {
-                pid: 0,
-                reason: format!("Failed to restart {}: {}", exe_path, e),
-            }),
+    /// Controller reading Win32 error codes through `last_error`
+    fn with_last_error(last_error: fn() -> u32) -> Self {
+        Self {
+            suspended: Arc::default(),
+            original_priority: Arc::default(),
+            jobs: Arc::default(),
+            late_resumes: Arc::default(),
+            last_error,
         }
     }
 
-    /// Terminate a process to free RAM
-    fn freeze_process_internal(&self, pid: u32) -> Result<usize> {
+    /// Launch an executable through the shell with the "runas" verb
+    fn restart_process_elevated(&self, application: &[u16]) -> Result<u32> {
+        let verb = WindowsRegistry::to_wide_string("runas");
+
         unsafe {
-            // Open process with terminate permission
-            let process_handle = OpenProcess(PROCESS_TERMINATE | PROCESS_QUERY_INFORMATION, 0, pid);
+            let mut exec_info: SHELLEXECUTEINFOW = mem::zeroed();
+            exec_info.cbSize = mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+            exec_info.fMask = SEE_MASK_NOCLOSEPROCESS;
+            exec_info.lpVerb = verb.as_ptr();
+            exec_info.lpFile = application.as_ptr();
+            exec_info.nShow = SW_SHOWNORMAL;
 
-            if process_handle.is_null() {
-                return Err(SmartFreezeError::FreezeFailed {
-                    pid,
-                    reason: "Failed to open process (may need admin privileges)".to_string(),
-                });
+            let launched = ShellExecuteExW(&mut exec_info) != 0;
+            let process = if launched {
+                OwnedHandle::new(exec_info.hProcess)
+            } else {
+                None
+            };
+            let Some(process) = process else {
+                return Err(SmartFreezeError::Io(io::Error::last_os_error()));
+            };
+
+            Ok(GetProcessId(process.raw()))
+        }
+    }
+
+    /// Collect the thread IDs owned by a process
+    fn list_threads(&self, pid: u32) -> Result<Vec<u32>> {
+        unsafe {
+            let Some(snapshot) = OwnedHandle::new(CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0))
+            else {
+                return Err(super::last_error("CreateToolhelp32Snapshot"));
+            };
+
+            let mut threads = Vec::new();
+            let mut entry: THREADENTRY32 = mem::zeroed();
+            entry.dwSize = mem::size_of::<THREADENTRY32>() as u32;
+
+            if Thread32First(snapshot.raw(), &mut entry) != 0 {
+                loop {
+                    if entry.th32OwnerProcessID == pid {
+                        threads.push(entry.th32ThreadID);
+                    }
+
+                    if Thread32Next(snapshot.raw(), &mut entry) == 0 {
+                        break;
+                    }
+                }
+            }
+
+            Ok(threads)
+        }
+    }
+
+    /// Open `pid` with `access`, mapping failure to a freeze error
+    fn open_process(&self, access: u32, pid: u32) -> Result<OwnedHandle> {
+        unsafe { OwnedHandle::new(OpenProcess(access, 0, pid)) }
+            .ok_or_else(|| call_failed(pid, "OpenProcess", (self.last_error)()))
+    }
+
+    /// Suspend a single thread, returning its previous suspend count
+    ///
+    /// On failure returns the Win32 error code of the failing call.
+    fn suspend_thread(&self, tid: u32) -> std::result::Result<u32, u32> {
+        unsafe {
+            let Some(thread) = OwnedHandle::new(OpenThread(THREAD_SUSPEND_RESUME, 0, tid)) else {
+                return Err((self.last_error)());
+            };
+
+            let previous = SuspendThread(thread.raw());
+            let code = (self.last_error)();
+
+            if previous == THREAD_CALL_FAILED {
+                Err(code)
+            } else {
+                Ok(previous)
             }
+        }
+    }
 
-            // Terminate the process (exit code 0 for clean shutdown)
-            let result = TerminateProcess(process_handle, 0);
-            CloseHandle(process_handle);
+    /// Resume a single thread once, returning its previous suspend count
+    fn resume_thread(&self, tid: u32) -> Option<u32> {
+        unsafe {
+            let thread = OwnedHandle::new(OpenThread(THREAD_SUSPEND_RESUME, 0, tid))?;
+            let previous = ResumeThread(thread.raw());
 
-            if result != 0 {
-                Ok(1) // Successfully terminated
+            if previous == THREAD_CALL_FAILED {
+                None
             } else {
-                Err(SmartFreezeError::FreezeFailed {
+                Some(previous)
+            }
+        }
+    }
+
+    /// Whether a process is still running
+    ///
+    /// `OpenProcess` fails with `ERROR_INVALID_PARAMETER` for PIDs that don't
+    /// exist; access denied means it exists but is protected.
+    fn process_exists(&self, pid: u32) -> bool {
+        unsafe {
+            let Some(process) =
+                OwnedHandle::new(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid))
+            else {
+                return GetLastError() != ERROR_INVALID_PARAMETER;
+            };
+
+            let mut exit_code: u32 = 0;
+            let queried = GetExitCodeProcess(process.raw(), &mut exit_code);
+
+            queried == 0 || exit_code == STILL_ACTIVE as u32
+        }
+    }
+
+    /// Suspend all threads of a process and record what was suspended
+    fn freeze_process_internal(&self, pid: u32) -> Result<usize> {
+        if !self.process_exists(pid) {
+            return Err(SmartFreezeError::ProcessNotFound(pid));
+        }
+
+        if self.suspended.lock().unwrap().contains_key(&pid) {
+            return Err(SmartFreezeError::AlreadyFrozen { pid });
+        }
+
+        let threads = self.list_threads(pid)?;
+
+        // Frozen by another SmartFreeze instance (e.g. the daemon) or another tool
+        let counts: Vec<Option<u32>> = threads
+            .iter()
+            .map(|&tid| thread_suspend_count(tid))
+            .collect();
+        if threads_suspended(&counts) {
+            return Err(SmartFreezeError::AlreadyFrozen { pid });
+        }
+
+        let mut suspended = Vec::new();
+        let mut last_failure = None;
+        for tid in threads {
+            match self.suspend_thread(tid) {
+                Ok(previous) => suspended.push((tid, previous)),
+                Err(code) => last_failure = Some(code),
+            }
+        }
+
+        if suspended.is_empty() {
+            // The process may have exited between the checks above
+            if !self.process_exists(pid) {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+            return Err(match last_failure {
+                Some(code) => freeze_error(pid, code),
+                None => SmartFreezeError::FreezeFailed {
                     pid,
-                    reason: "TerminateProcess failed".to_string(),
-                })
+                    reason: "Process has no threads".to_string(),
+                },
+            });
+        }
+
+        let count = suspended.len();
+        self.suspended
+            .lock()
+            .unwrap()
+            .entry(pid)
+            .or_default()
+            .extend(suspended);
+
+        Ok(count)
+    }
+
+    /// Resume the threads this controller suspended
+    ///
+    /// A PID with no record here (e.g. frozen by another run that wasn't
+    /// adopted with `adopt_freeze`) is left alone and resumes 0 threads:
+    /// its suspensions may belong to someone else. Threads already back at
+    /// their pre-freeze suspend count were resumed by someone else and are
+    /// skipped, so a debugger's or another tool's suspension is never undone.
+    fn resume_process_internal(&self, pid: u32) -> Result<usize> {
+        let Some(entries) = self.suspended.lock().unwrap().remove(&pid) else {
+            return Ok(0);
+        };
+
+        let mut resumed = 0;
+        let mut released = 0;
+        for (tid, previous) in entries {
+            if thread_suspend_count(tid).is_some_and(|count| count <= previous) {
+                released += 1;
+            } else if self.resume_thread(tid).is_some() {
+                resumed += 1;
+            }
+        }
+
+        if resumed == 0 && released == 0 {
+            // Killed while frozen; callers may relaunch it
+            if !self.process_exists(pid) {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+            return Err(SmartFreezeError::ResumeFailed {
+                pid,
+                reason: "Failed to resume any threads (process may have exited)".to_string(),
+            });
+        }
+
+        Ok(resumed)
+    }
+
+    /// Freeze a group of processes (e.g. a game and its anti-cheat) as one unit
+    ///
+    /// The processes are assigned to a new job object, so children they spawn
+    /// join it too, then every member is suspended. Windows has no public
+    /// job-wide suspend, so members are frozen one by one; if any of them
+    /// fails, the ones already frozen are resumed and the error is returned.
+    /// Processes that can't be assigned (e.g. already in a job that forbids
+    /// nesting) are still frozen with the rest.
+    pub fn freeze_job(&self, pids: &[u32]) -> Result<()> {
+        if pids.is_empty() {
+            return Ok(());
+        }
+        {
+            let jobs = self.jobs.lock().unwrap();
+            if let Some(&pid) = pids
+                .iter()
+                .find(|pid| jobs.iter().any(|frozen| frozen.pids.contains(pid)))
+            {
+                return Err(SmartFreezeError::AlreadyFrozen { pid });
+            }
+        }
+
+        let job = unsafe { OwnedHandle::new(CreateJobObjectW(std::ptr::null(), std::ptr::null())) };
+        let Some(job) = job else {
+            return Err(call_failed(
+                pids[0],
+                "CreateJobObjectW",
+                (self.last_error)(),
+            ));
+        };
+
+        let mut unassigned = Vec::new();
+        for &pid in pids {
+            if !self.process_exists(pid) {
+                return Err(SmartFreezeError::ProcessNotFound(pid));
+            }
+            if let Err(e) = self.assign_to_job(&job, pid) {
+                log::debug!("Freezing {} outside the job: {}", pid, e);
+                unassigned.push(pid);
+            }
+        }
+
+        let mut members = self.job_members(&job);
+        for pid in pids.iter().chain(&unassigned) {
+            if !members.contains(pid) {
+                members.push(*pid);
+            }
+        }
+
+        let mut frozen = Vec::with_capacity(members.len());
+        for &pid in &members {
+            match self.freeze_process_internal(pid) {
+                Ok(_) => frozen.push(pid),
+                Err(e) => {
+                    for &done in &frozen {
+                        let _ = self.resume_process_internal(done);
+                    }
+                    return Err(e);
+                }
             }
         }
+
+        self.jobs
+            .lock()
+            .unwrap()
+            .push(FrozenJob { job, pids: frozen });
+        Ok(())
     }
 
-    /// Restart a terminated process by launching it again
-    fn resume_process_internal(&self, _pid: u32) -> Result<usize> {
-        // Note: We can't actually restart by PID since the process is gone
-        // Resume will need the executable path from persistence
-        // For now, just return Ok to indicate the "resume" was attempted
-        // The actual restart logic is in the persistence layer
-        Ok(0)
+    /// Resume every process frozen with `pids` by `freeze_job`
+    ///
+    /// Members that joined the job while it was frozen are resumed as well.
+    /// PIDs without a recorded job (e.g. frozen before a restart) are resumed
+    /// one by one. All members are attempted; the first failure is returned.
+    pub fn resume_job(&self, pids: &[u32]) -> Result<()> {
+        let mut targets = Vec::new();
+        for &pid in pids {
+            if targets.contains(&pid) {
+                continue;
+            }
+            let recorded = {
+                let mut jobs = self.jobs.lock().unwrap();
+                jobs.iter()
+                    .position(|frozen| frozen.pids.contains(&pid))
+                    .map(|index| jobs.remove(index))
+            };
+            match recorded {
+                Some(frozen) => {
+                    let mut members = frozen.pids;
+                    for pid in self.job_members(&frozen.job) {
+                        if !members.contains(&pid) {
+                            members.push(pid);
+                        }
+                    }
+                    targets.extend(members.into_iter().filter(|p| !targets.contains(p)));
+                }
+                None => targets.push(pid),
+            }
+        }
+
+        let mut first_error = None;
+        for pid in targets {
+            if let Err(e) = self.resume_process_internal(pid) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn assign_to_job(&self, job: &OwnedHandle, pid: u32) -> Result<()> {
+        unsafe {
+            let process = self.open_process(PROCESS_SET_QUOTA | PROCESS_TERMINATE, pid)?;
+
+            let assigned = AssignProcessToJobObject(job.raw(), process.raw());
+            let code = (self.last_error)();
+
+            if assigned == 0 {
+                return Err(call_failed(pid, "AssignProcessToJobObject", code));
+            }
+            Ok(())
+        }
+    }
+
+    /// PIDs currently in the job, empty if the query fails
+    fn job_members(&self, job: &OwnedHandle) -> Vec<u32> {
+        unsafe {
+            let mut list: JobProcessIdList = mem::zeroed();
+            let queried = QueryInformationJobObject(
+                job.raw(),
+                JobObjectBasicProcessIdList,
+                &mut list as *mut _ as *mut _,
+                mem::size_of::<JobProcessIdList>() as u32,
+                std::ptr::null_mut(),
+            );
+            if queried == 0 && (self.last_error)() != ERROR_MORE_DATA {
+                return Vec::new();
+            }
+
+            let listed = (list.listed as usize).min(MAX_JOB_MEMBERS);
+            list.pids[..listed].iter().map(|&pid| pid as u32).collect()
+        }
+    }
+}
+
+/// Restrict `requested` to cores that exist, falling back to the lowest one
+fn usable_affinity(requested: usize, system: usize) -> usize {
+    match requested & system {
+        0 => system & system.wrapping_neg(),
+        mask => mask,
     }
 }
 
@@ -73,12 +507,306 @@ impl Default for WindowsProcessController {
 }
 
 impl ProcessController for WindowsProcessController {
+    /// Gives up after `PROCESS_TIMEOUT` so a stuck process can't stall the caller
     fn freeze(&self, pid: u32) -> Result<usize> {
-        self.freeze_process_internal(pid)
+        let worker = self.clone();
+        let undo = self.clone();
+        with_timeout_or_else(
+            PROCESS_TIMEOUT,
+            move || worker.freeze_process_internal(pid),
+            // Already reported as failed, so it mustn't stay suspended
+            move |late| {
+                if late.is_ok() {
+                    let _ = undo.resume_process_internal(pid);
+                }
+            },
+        )
+        .unwrap_or_else(|| Err(timed_out(pid, "Freeze")))
     }
 
+    /// Gives up after `PROCESS_TIMEOUT` so a stuck process can't stall the caller;
+    /// a resume finishing later is reported by `take_late_resumes`
     fn resume(&self, pid: u32) -> Result<usize> {
-        self.resume_process_internal(pid)
+        let worker = self.clone();
+        let late_resumes = Arc::clone(&self.late_resumes);
+        with_timeout_or_else(
+            PROCESS_TIMEOUT,
+            move || worker.resume_process_internal(pid),
+            move |late| {
+                if late.is_ok() {
+                    late_resumes.lock().unwrap().push(pid);
+                }
+            },
+        )
+        .unwrap_or_else(|| Err(timed_out(pid, "Resume")))
+    }
+
+    fn take_late_resumes(&self) -> Vec<u32> {
+        mem::take(&mut *self.late_resumes.lock().unwrap())
+    }
+
+    fn capabilities(&self) -> FreezeCapabilities {
+        FreezeCapabilities::from_modes(&[
+            FreezeMode::Suspend,
+            FreezeMode::Throttle,
+            FreezeMode::Soft,
+        ])
+    }
+
+    /// Empty the working set via `EmptyWorkingSet`; the process keeps running
+    fn trim_working_set(&self, pid: u32) -> Result<WorkingSetTrim> {
+        unsafe {
+            let process =
+                self.open_process(PROCESS_SET_QUOTA | PROCESS_QUERY_LIMITED_INFORMATION, pid)?;
+
+            let before_mb = working_set_mb(process.raw());
+            let trimmed = EmptyWorkingSet(process.raw());
+            let code = (self.last_error)();
+            let after_mb = working_set_mb(process.raw());
+
+            if trimmed == 0 {
+                return Err(call_failed(pid, "EmptyWorkingSet", code));
+            }
+
+            Ok(WorkingSetTrim {
+                before_mb,
+                after_mb,
+            })
+        }
+    }
+
+    /// Restart a process from its executable path, returning the new PID
+    ///
+    /// Falls back to an elevated `ShellExecuteExW` launch when the executable
+    /// requires elevation.
+    fn restart_process(&self, exe_path: &str) -> Result<u32> {
+        if !Path::new(exe_path).is_file() {
+            return Err(SmartFreezeError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Executable not found: {}", exe_path),
+            )));
+        }
+
+        let application = WindowsRegistry::to_wide_string(exe_path);
+        let mut command_line = WindowsRegistry::to_wide_string(&format!("\"{}\"", exe_path));
+
+        unsafe {
+            let mut startup_info: STARTUPINFOW = mem::zeroed();
+            startup_info.cb = mem::size_of::<STARTUPINFOW>() as u32;
+            let mut process_info: PROCESS_INFORMATION = mem::zeroed();
+
+            let result = CreateProcessW(
+                application.as_ptr(),
+                command_line.as_mut_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+                DETACHED_PROCESS,
+                std::ptr::null(),
+                std::ptr::null(),
+                &startup_info,
+                &mut process_info,
+            );
+
+            if result == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() == Some(ERROR_ELEVATION_REQUIRED as i32) {
+                    return self.restart_process_elevated(&application);
+                }
+                return Err(SmartFreezeError::Io(error));
+            }
+
+            // Neither handle is needed; owning them closes both
+            let _thread = OwnedHandle::new(process_info.hThread);
+            let _process = OwnedHandle::new(process_info.hProcess);
+
+            Ok(process_info.dwProcessId)
+        }
+    }
+
+    /// Apply `priority_class` and `affinity_mask`, saving the originals on first use
+    ///
+    /// Affinity bits for cores the system lacks are dropped.
+    fn set_priority_and_affinity(
+        &self,
+        pid: u32,
+        priority_class: u32,
+        affinity_mask: usize,
+    ) -> Result<()> {
+        unsafe {
+            let process = self.open_process(
+                PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION,
+                pid,
+            )?;
+
+            let original_class = GetPriorityClass(process.raw());
+            let mut process_mask = 0;
+            let mut system_mask = 0;
+
+            let mut failure = None;
+            if original_class == 0 {
+                failure = Some(("GetPriorityClass", (self.last_error)()));
+            } else if GetProcessAffinityMask(process.raw(), &mut process_mask, &mut system_mask)
+                == 0
+            {
+                failure = Some(("GetProcessAffinityMask", (self.last_error)()));
+            } else if SetPriorityClass(process.raw(), priority_class) == 0 {
+                failure = Some(("SetPriorityClass", (self.last_error)()));
+            } else if SetProcessAffinityMask(
+                process.raw(),
+                usable_affinity(affinity_mask, system_mask),
+            ) == 0
+            {
+                failure = Some(("SetProcessAffinityMask", (self.last_error)()));
+                SetPriorityClass(process.raw(), original_class);
+            }
+
+            if let Some((call, code)) = failure {
+                return Err(call_failed(pid, call, code));
+            }
+            self.original_priority
+                .lock()
+                .unwrap()
+                .entry(pid)
+                .or_insert((original_class, process_mask));
+            Ok(())
+        }
+    }
+
+    /// Restore the saved priority and affinity
+    ///
+    /// Without a record (e.g. soft-frozen before a crash) this falls back to
+    /// `NORMAL_PRIORITY_CLASS` on every core.
+    fn restore_priority_and_affinity(&self, pid: u32) -> Result<()> {
+        let saved = self.original_priority.lock().unwrap().remove(&pid);
+
+        unsafe {
+            let process = self.open_process(
+                PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION,
+                pid,
+            )?;
+
+            let (priority_class, affinity_mask) = match saved {
+                Some(saved) => saved,
+                None => {
+                    let mut process_mask = 0;
+                    let mut system_mask = 0;
+                    GetProcessAffinityMask(process.raw(), &mut process_mask, &mut system_mask);
+                    (NORMAL_PRIORITY_CLASS, system_mask)
+                }
+            };
+
+            let mut failure = None;
+            if SetPriorityClass(process.raw(), priority_class) == 0 {
+                failure = Some(("SetPriorityClass", (self.last_error)()));
+            }
+            if affinity_mask != 0
+                && SetProcessAffinityMask(process.raw(), affinity_mask) == 0
+                && failure.is_none()
+            {
+                failure = Some(("SetProcessAffinityMask", (self.last_error)()));
+            }
+
+            match failure {
+                Some((call, code)) => Err(call_failed(pid, call, code)),
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn freeze_record(&self, pid: u32) -> FreezeRecord {
+        FreezeRecord {
+            threads: self
+                .suspended
+                .lock()
+                .unwrap()
+                .get(&pid)
+                .cloned()
+                .unwrap_or_default(),
+            priority: self.original_priority.lock().unwrap().get(&pid).copied(),
+        }
+    }
+
+    /// Records already held for `pid` win over the adopted one
+    fn adopt_freeze(&self, pid: u32, record: &FreezeRecord) {
+        if !record.threads.is_empty() {
+            self.suspended
+                .lock()
+                .unwrap()
+                .entry(pid)
+                .or_insert_with(|| record.threads.clone());
+        }
+        if let Some(priority) = record.priority {
+            self.original_priority
+                .lock()
+                .unwrap()
+                .entry(pid)
+                .or_insert(priority);
+        }
+    }
+
+    fn exe_path(&self, pid: u32) -> Option<String> {
+        image_path(pid)
+    }
+
+    /// Toggle EcoQoS execution-speed throttling and idle priority
+    ///
+    /// Enabling saves the original priority class; disabling restores it,
+    /// falling back to `NORMAL_PRIORITY_CLASS` without a record.
+    fn set_efficiency_mode(&self, pid: u32, enabled: bool) -> Result<()> {
+        unsafe {
+            let process = self.open_process(
+                PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION,
+                pid,
+            )?;
+
+            let state = PROCESS_POWER_THROTTLING_STATE {
+                Version: PROCESS_POWER_THROTTLING_CURRENT_VERSION,
+                ControlMask: PROCESS_POWER_THROTTLING_EXECUTION_SPEED,
+                StateMask: if enabled {
+                    PROCESS_POWER_THROTTLING_EXECUTION_SPEED
+                } else {
+                    0
+                },
+            };
+
+            let mut failure = None;
+            if SetProcessInformation(
+                process.raw(),
+                ProcessPowerThrottling,
+                &state as *const PROCESS_POWER_THROTTLING_STATE as *const std::ffi::c_void,
+                mem::size_of::<PROCESS_POWER_THROTTLING_STATE>() as u32,
+            ) == 0
+            {
+                failure = Some(("SetProcessInformation", (self.last_error)()));
+            }
+            let priority = if enabled {
+                let original_class = GetPriorityClass(process.raw());
+                if original_class != 0 {
+                    // Affinity 0: throttling leaves the affinity alone
+                    self.original_priority
+                        .lock()
+                        .unwrap()
+                        .entry(pid)
+                        .or_insert((original_class, 0));
+                }
+                IDLE_PRIORITY_CLASS
+            } else {
+                self.original_priority
+                    .lock()
+                    .unwrap()
+                    .remove(&pid)
+                    .map_or(NORMAL_PRIORITY_CLASS, |(class, _)| class)
+            };
+            if SetPriorityClass(process.raw(), priority) == 0 && failure.is_none() {
+                failure = Some(("SetPriorityClass", (self.last_error)()));
+            }
+
+            match failure {
+                Some((call, code)) => Err(call_failed(pid, call, code)),
+                None => Ok(()),
+            }
+        }
     }
 }
 
@@ -89,10 +817,304 @@ mod tests {
     #[test]
     fn test_controller_creation() {
         let controller = WindowsProcessController::new();
-        assert!(std::mem::size_of_val(&controller) == 0); // Zero-sized type
+        assert!(controller.suspended.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_controller_capabilities() {
+        let controller = WindowsProcessController::new();
+        assert!(controller.supports_mode(FreezeMode::Suspend));
+        assert!(controller.supports_mode(FreezeMode::Throttle));
+        assert!(controller.supports_mode(FreezeMode::Soft));
+    }
+
+    #[test]
+    fn test_freeze_missing_process_is_not_found() {
+        let controller = WindowsProcessController::new();
+
+        // PIDs are multiples of 4, so this one never exists
+        assert!(matches!(
+            controller.freeze(0xFFFF_FFF1),
+            Err(SmartFreezeError::ProcessNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_freeze_error_mapping() {
+        assert!(matches!(
+            freeze_error(42, ERROR_ACCESS_DENIED),
+            SmartFreezeError::AccessDenied { pid: 42 }
+        ));
+        assert!(matches!(
+            freeze_error(42, ERROR_INVALID_PARAMETER),
+            SmartFreezeError::FreezeFailed { pid: 42, .. }
+        ));
+        assert_eq!(
+            freeze_error(42, 31).to_string(),
+            "Failed to freeze process 42: SuspendThread failed (error code 31)"
+        );
+    }
+
+    #[test]
+    fn test_suspend_thread_reports_injected_error() {
+        let controller = WindowsProcessController::with_last_error(|| ERROR_ACCESS_DENIED);
+
+        // Thread ID 0 never exists, so OpenThread fails
+        let code = controller.suspend_thread(0).unwrap_err();
+        assert!(matches!(
+            freeze_error(7, code),
+            SmartFreezeError::AccessDenied { pid: 7 }
+        ));
+    }
+
+    #[test]
+    fn test_trim_working_set_on_child_process() {
+        let mut child = std::process::Command::new("cmd.exe")
+            .args(["/C", "ping -n 30 127.0.0.1 >NUL"])
+            .spawn()
+            .unwrap();
+        let controller = WindowsProcessController::new();
+
+        let trim = controller.trim_working_set(child.id()).unwrap();
+        assert!(trim.reclaimed_mb() <= trim.before_mb);
+
+        let _ = child.kill();
     }
 
-    // Note: We don't test actual freeze/resume as it requires admin privileges
-    // and could affect system stability. These are tested via integration tests
-    // with controlled test processes.
+    #[test]
+    fn test_efficiency_mode_on_child_process() {
+        use windows_sys::Win32::System::Threading::BELOW_NORMAL_PRIORITY_CLASS;
+
+        let mut child = std::process::Command::new("cmd.exe")
+            .args(["/C", "ping -n 30 127.0.0.1 >NUL"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let controller = WindowsProcessController::new();
+
+        // Start below normal so a blanket NORMAL_PRIORITY_CLASS restore is caught
+        unsafe {
+            let process = controller
+                .open_process(PROCESS_SET_INFORMATION, pid)
+                .unwrap();
+            SetPriorityClass(process.raw(), BELOW_NORMAL_PRIORITY_CLASS);
+        }
+
+        assert!(controller.set_efficiency_mode(pid, true).is_ok());
+        assert_eq!(priority_and_affinity(pid).0, IDLE_PRIORITY_CLASS);
+        assert!(controller.set_efficiency_mode(pid, false).is_ok());
+        assert_eq!(priority_and_affinity(pid).0, BELOW_NORMAL_PRIORITY_CLASS);
+        assert!(controller.original_priority.lock().unwrap().is_empty());
+
+        let _ = child.kill();
+    }
+
+    /// Current (priority class, affinity mask) of a process
+    fn priority_and_affinity(pid: u32) -> (u32, usize) {
+        unsafe {
+            let process =
+                OwnedHandle::new(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid)).unwrap();
+            let class = GetPriorityClass(process.raw());
+            let mut process_mask = 0;
+            let mut system_mask = 0;
+            GetProcessAffinityMask(process.raw(), &mut process_mask, &mut system_mask);
+            (class, process_mask)
+        }
+    }
+
+    #[test]
+    fn test_usable_affinity() {
+        assert_eq!(usable_affinity(0b0001, 0b1111), 0b0001);
+        assert_eq!(usable_affinity(0b0011, 0b0010), 0b0010);
+        // Requested cores don't exist: lowest available core
+        assert_eq!(usable_affinity(0b0001, 0b1100), 0b0100);
+    }
+
+    #[test]
+    fn test_soft_freeze_constants_match_windows() {
+        use crate::freeze_engine::SOFT_FREEZE_PRIORITY_CLASS;
+        use windows_sys::Win32::System::Threading::BELOW_NORMAL_PRIORITY_CLASS;
+
+        assert_eq!(SOFT_FREEZE_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS);
+    }
+
+    #[test]
+    fn test_priority_and_affinity_are_restored() {
+        use windows_sys::Win32::System::Threading::BELOW_NORMAL_PRIORITY_CLASS;
+
+        let mut child = std::process::Command::new("cmd.exe")
+            .args(["/C", "ping -n 30 127.0.0.1 >NUL"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let controller = WindowsProcessController::new();
+        let original = priority_and_affinity(pid);
+
+        controller
+            .set_priority_and_affinity(pid, BELOW_NORMAL_PRIORITY_CLASS, 1)
+            .unwrap();
+        assert_eq!(priority_and_affinity(pid), (BELOW_NORMAL_PRIORITY_CLASS, 1));
+        // A second call must not overwrite the saved originals
+        controller
+            .set_priority_and_affinity(pid, IDLE_PRIORITY_CLASS, 1)
+            .unwrap();
+        assert_eq!(controller.original_priority.lock().unwrap()[&pid], original);
+
+        controller.restore_priority_and_affinity(pid).unwrap();
+        assert_eq!(priority_and_affinity(pid), original);
+        assert!(controller.original_priority.lock().unwrap().is_empty());
+
+        let _ = child.kill();
+    }
+
+    #[test]
+    fn test_adopted_record_is_resumed_by_a_new_controller() {
+        let mut child = spawn_sleeper();
+        let pid = child.id();
+
+        let before_crash = WindowsProcessController::new();
+        let frozen = before_crash.freeze(pid).unwrap();
+        let record = before_crash.freeze_record(pid);
+        assert_eq!(record.threads.len(), frozen);
+
+        let after_crash = WindowsProcessController::new();
+        after_crash.adopt_freeze(pid, &record);
+        assert_eq!(after_crash.resume(pid).unwrap(), frozen);
+        assert_eq!(
+            after_crash.exe_path(pid).map(|path| path.to_lowercase()),
+            image_path(pid).map(|path| path.to_lowercase())
+        );
+
+        let _ = child.kill();
+    }
+
+    #[test]
+    fn test_freeze_resume_only_undoes_own_suspensions() {
+        let mut child = std::process::Command::new("cmd.exe")
+            .args(["/C", "ping -n 30 127.0.0.1 >NUL"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let controller = WindowsProcessController::new();
+
+        let frozen = controller.freeze(pid).unwrap();
+        assert!(frozen > 0);
+        assert_eq!(controller.suspended.lock().unwrap()[&pid].len(), frozen);
+
+        // A second freeze must not deepen the suspension
+        assert!(matches!(
+            controller.freeze(pid),
+            Err(SmartFreezeError::AlreadyFrozen { .. })
+        ));
+        assert!(matches!(
+            WindowsProcessController::new().freeze(pid),
+            Err(SmartFreezeError::AlreadyFrozen { .. })
+        ));
+
+        let resumed = controller.resume(pid).unwrap();
+        assert_eq!(resumed, frozen);
+        assert!(!controller.suspended.lock().unwrap().contains_key(&pid));
+
+        // Nothing recorded: a blind resume could undo someone else's suspension
+        assert_eq!(WindowsProcessController::new().resume(pid).unwrap(), 0);
+
+        let _ = child.kill();
+    }
+
+    #[test]
+    fn test_resume_keeps_suspensions_made_before_the_freeze() {
+        let mut child = std::process::Command::new("powershell.exe")
+            .args(["-NoProfile", "-Command", "Start-Sleep 30"])
+            .spawn()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        let pid = child.id();
+        let controller = WindowsProcessController::new();
+
+        // Another tool suspended one thread before we froze the process
+        let threads = controller.list_threads(pid).unwrap();
+        assert!(threads.len() > 1);
+        let other = threads[0];
+        assert_eq!(controller.suspend_thread(other), Ok(0));
+
+        let frozen = controller.freeze(pid).unwrap();
+        assert!(controller.suspended.lock().unwrap()[&pid].contains(&(other, 1)));
+
+        assert_eq!(controller.resume(pid).unwrap(), frozen);
+        assert_eq!(thread_suspend_count(other), Some(1));
+
+        assert!(controller.resume_thread(other).is_some());
+        let _ = child.kill();
+    }
+
+    fn spawn_sleeper() -> std::process::Child {
+        std::process::Command::new("cmd.exe")
+            .args(["/C", "ping -n 30 127.0.0.1 >NUL"])
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_freeze_job_freezes_and_resumes_together() {
+        let mut game = spawn_sleeper();
+        let mut anti_cheat = spawn_sleeper();
+        let pids = [game.id(), anti_cheat.id()];
+        let controller = WindowsProcessController::new();
+
+        controller.freeze_job(&pids).unwrap();
+        {
+            let suspended = controller.suspended.lock().unwrap();
+            assert!(pids.iter().all(|pid| suspended.contains_key(pid)));
+        }
+        assert_eq!(controller.jobs.lock().unwrap().len(), 1);
+        assert!(matches!(
+            controller.freeze_job(&pids[1..]),
+            Err(SmartFreezeError::AlreadyFrozen { .. })
+        ));
+
+        // Resuming through either member resumes the whole job
+        controller.resume_job(&pids[1..]).unwrap();
+        assert!(controller.jobs.lock().unwrap().is_empty());
+        assert!(controller.suspended.lock().unwrap().is_empty());
+
+        let _ = game.kill();
+        let _ = anti_cheat.kill();
+    }
+
+    #[test]
+    fn test_freeze_job_is_all_or_nothing() {
+        let mut child = spawn_sleeper();
+        let controller = WindowsProcessController::new();
+
+        // PIDs are multiples of 4, so this one never exists
+        let result = controller.freeze_job(&[child.id(), 0xFFFF_FFF1]);
+
+        assert!(matches!(result, Err(SmartFreezeError::ProcessNotFound(_))));
+        assert!(controller.suspended.lock().unwrap().is_empty());
+        assert!(controller.jobs.lock().unwrap().is_empty());
+
+        let _ = child.kill();
+    }
+
+    #[test]
+    fn test_restart_process_launches_executable() {
+        let controller = WindowsProcessController::new();
+        let pid = controller
+            .restart_process("C:\\Windows\\System32\\notepad.exe")
+            .unwrap();
+        assert!(pid > 0);
+
+        let status = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_restart_process_missing_executable() {
+        let controller = WindowsProcessController::new();
+        let result = controller.restart_process("C:\\does\\not\\exist.exe");
+        assert!(matches!(result, Err(SmartFreezeError::Io(_))));
+    }
 }
@@ -1,89 +1,547 @@
 //! Windows process enumeration implementation
 
+use super::handle::OwnedHandle;
+use super::signature::PublisherCache;
 use crate::categorization::{DefaultCategorizer, ProcessCategorizer};
 use crate::freeze_engine::ProcessEnumerator;
-use crate::process::ProcessInfo;
+use crate::process::{
+    pids_with_visible_windows, threads_suspended, ProcessCategory, ProcessInfo, TopLevelWindow,
+};
+use crate::timeout::{TimeoutWorker, PROCESS_TIMEOUT};
 use crate::{Result, SmartFreezeError};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
 use std::mem;
-use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, HWND};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use windows_sys::Wdk::System::Threading::{
+    NtQueryInformationProcess, NtQueryInformationThread, ProcessCommandLineInformation,
+    ThreadSuspendCount,
+};
+use windows_sys::Win32::Foundation::{
+    GetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FILETIME, HANDLE, HWND, LPARAM, RECT,
+    UNICODE_STRING,
+};
 use windows_sys::Win32::System::Diagnostics::ToolHelp::{
-    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, Thread32First, Thread32Next,
+    PROCESSENTRY32W, TH32CS_SNAPPROCESS, TH32CS_SNAPTHREAD, THREADENTRY32,
 };
 use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+use windows_sys::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+use windows_sys::Win32::System::SystemInformation::{
+    GetTickCount, GlobalMemoryStatusEx, MEMORYSTATUSEX,
+};
 use windows_sys::Win32::System::Threading::{
-    OpenProcess, QueryFullProcessImageNameW, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
+    GetCurrentProcessId, GetProcessHandleCount, GetProcessTimes, OpenProcess, OpenThread,
+    QueryFullProcessImageNameW, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_VM_READ, THREAD_QUERY_LIMITED_INFORMATION,
 };
-use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetSystemMetrics,
+    GetWindowRect, GetWindowThreadProcessId, IsIconic, IsWindowVisible, SM_CXSCREEN, SM_CYSCREEN,
+};
+
+/// Seconds between 1601-01-01 (FILETIME epoch) and 1970-01-01
+const FILETIME_UNIX_OFFSET_SECS: u64 = 11_644_473_600;
+
+/// Seconds elapsed since a FILETIME creation timestamp (0 if in the future)
+fn age_secs_since(created: u64, now: SystemTime) -> u64 {
+    let now_secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        + FILETIME_UNIX_OFFSET_SECS;
+
+    now_secs.saturating_sub(created / 10_000_000)
+}
+
+/// Seconds since the last input, from `GetTickCount` readings in milliseconds
+///
+/// The tick counter wraps every 49.7 days; wrapping subtraction keeps the
+/// difference right across the wrap.
+pub fn idle_seconds(now_ticks: u32, last_input_ticks: u32) -> u64 {
+    u64::from(now_ticks.wrapping_sub(last_input_ticks)) / 1000
+}
+
+/// Whether a window rect (left, top, right, bottom) covers the whole screen
+fn covers_screen(rect: (i32, i32, i32, i32), screen_width: i32, screen_height: i32) -> bool {
+    let (left, top, right, bottom) = rect;
+    screen_width > 0
+        && screen_height > 0
+        && left <= 0
+        && top <= 0
+        && right >= screen_width
+        && bottom >= screen_height
+}
+
+/// Compute CPU usage as percent of one core between two samples
+///
+/// CPU times are in 100-nanosecond units (as reported by `GetProcessTimes`).
+fn cpu_percent_between(previous: (u64, Instant), current: (u64, Instant)) -> f64 {
+    let elapsed = current
+        .1
+        .saturating_duration_since(previous.1)
+        .as_secs_f64();
+    if elapsed <= 0.0 {
+        return 0.0;
+    }
+
+    let cpu_secs = current.0.saturating_sub(previous.0) as f64 / 10_000_000.0;
+    cpu_secs / elapsed * 100.0
+}
+
+/// Initial path buffer size (MAX_PATH), in UTF-16 units
+const INITIAL_PATH_LEN: usize = 260;
+/// Longest path the NT API accepts, in UTF-16 units
+const MAX_NT_PATH_LEN: usize = 32_767;
+
+/// Outcome of one fixed-size buffer query
+enum BufferQuery {
+    /// Succeeded, writing this many units
+    Done(usize),
+    /// Failed with `ERROR_INSUFFICIENT_BUFFER`
+    TooSmall,
+    Failed,
+}
+
+/// Run `query` with a MAX_PATH buffer, doubling it up to the NT path limit while too small
+fn query_growing(mut query: impl FnMut(&mut [u16]) -> BufferQuery) -> Option<Vec<u16>> {
+    let mut len = INITIAL_PATH_LEN;
+
+    loop {
+        let mut buffer = vec![0u16; len];
+        match query(&mut buffer) {
+            BufferQuery::Done(written) => {
+                buffer.truncate(written);
+                return Some(buffer);
+            }
+            BufferQuery::TooSmall if len < MAX_NT_PATH_LEN => {
+                len = (len * 2).min(MAX_NT_PATH_LEN);
+            }
+            BufferQuery::TooSmall | BufferQuery::Failed => return None,
+        }
+    }
+}
+
+/// Terminal Services session of a process
+fn session_of(pid: u32) -> Option<u32> {
+    let mut session_id = 0;
+    if unsafe { ProcessIdToSessionId(pid, &mut session_id) } != 0 {
+        Some(session_id)
+    } else {
+        None
+    }
+}
+
+/// Thread IDs of every process, from one thread snapshot
+fn threads_by_process() -> HashMap<u32, Vec<u32>> {
+    let mut threads: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    unsafe {
+        let Some(snapshot) = OwnedHandle::new(CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0))
+        else {
+            return threads;
+        };
+
+        let mut entry: THREADENTRY32 = mem::zeroed();
+        entry.dwSize = mem::size_of::<THREADENTRY32>() as u32;
+
+        if Thread32First(snapshot.raw(), &mut entry) != 0 {
+            loop {
+                threads
+                    .entry(entry.th32OwnerProcessID)
+                    .or_default()
+                    .push(entry.th32ThreadID);
+
+                if Thread32Next(snapshot.raw(), &mut entry) == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    threads
+}
+
+/// Suspend count of a thread, read without suspending it (`None` if unreadable)
+pub(super) fn thread_suspend_count(tid: u32) -> Option<u32> {
+    unsafe {
+        let Some(thread_handle) =
+            OwnedHandle::new(OpenThread(THREAD_QUERY_LIMITED_INFORMATION, 0, tid))
+        else {
+            return None;
+        };
+
+        let mut count: u32 = 0;
+        let status = NtQueryInformationThread(
+            thread_handle.raw(),
+            ThreadSuspendCount,
+            &mut count as *mut u32 as *mut c_void,
+            mem::size_of::<u32>() as u32,
+            std::ptr::null_mut(),
+        );
+
+        if status >= 0 {
+            Some(count)
+        } else {
+            None
+        }
+    }
+}
+
+/// Command line of an open process (`None` for protected processes or on any failure)
+///
+/// ProcessCommandLineInformation copies the PEB's ProcessParameters.CommandLine
+/// for us, and only needs limited query rights.
+fn command_line_of(process_handle: HANDLE) -> Option<String> {
+    unsafe {
+        // The first call fails with the size needed in `size`
+        let mut size: u32 = 0;
+        NtQueryInformationProcess(
+            process_handle,
+            ProcessCommandLineInformation,
+            std::ptr::null_mut(),
+            0,
+            &mut size,
+        );
+        if (size as usize) < mem::size_of::<UNICODE_STRING>() {
+            return None;
+        }
+
+        // u64 backing keeps the UNICODE_STRING header suitably aligned
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let status = NtQueryInformationProcess(
+            process_handle,
+            ProcessCommandLineInformation,
+            buffer.as_mut_ptr() as *mut c_void,
+            size,
+            &mut size,
+        );
+        if status < 0 {
+            return None;
+        }
+
+        let command_line = &*(buffer.as_ptr() as *const UNICODE_STRING);
+        if command_line.Buffer.is_null() || command_line.Length == 0 {
+            return None;
+        }
+        let units = std::slice::from_raw_parts(
+            command_line.Buffer,
+            command_line.Length as usize / mem::size_of::<u16>(),
+        );
+        Some(String::from_utf16_lossy(units))
+    }
+}
+
+/// Every top-level window with its owner PID and visibility
+fn top_level_windows() -> Vec<TopLevelWindow> {
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam as *mut Vec<TopLevelWindow>);
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        windows.push(TopLevelWindow {
+            pid,
+            visible: IsWindowVisible(hwnd) != 0,
+            minimized: IsIconic(hwnd) != 0,
+        });
+        1
+    }
+
+    let mut windows = Vec::new();
+    unsafe {
+        EnumWindows(
+            Some(collect),
+            &mut windows as *mut Vec<TopLevelWindow> as LPARAM,
+        );
+    }
+    windows
+}
+
+/// (total kernel + user CPU time, creation time) of an open process, as FILETIME ticks
+fn process_times_of(process_handle: HANDLE) -> Option<(u64, u64)> {
+    unsafe {
+        let mut creation: FILETIME = mem::zeroed();
+        let mut exit: FILETIME = mem::zeroed();
+        let mut kernel: FILETIME = mem::zeroed();
+        let mut user: FILETIME = mem::zeroed();
+
+        let result = GetProcessTimes(
+            process_handle,
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        );
+
+        if result != 0 {
+            Some((
+                filetime_to_u64(&kernel) + filetime_to_u64(&user),
+                filetime_to_u64(&creation),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Name, path and memory usage (MB) of an open process
+///
+/// `None` if its image name can't be read.
+fn image_and_memory_of(process_handle: HANDLE) -> Option<(String, String, u64)> {
+    unsafe {
+        let full_path = image_path_of(process_handle)?;
+        let name = full_path
+            .rsplit('\\')
+            .next()
+            .unwrap_or("unknown.exe")
+            .to_string();
+
+        let mut pmc: PROCESS_MEMORY_COUNTERS = mem::zeroed();
+        pmc.cb = mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+
+        let memory_mb = if GetProcessMemoryInfo(process_handle, &mut pmc, pmc.cb) != 0 {
+            pmc.WorkingSetSize as u64 / (1024 * 1024)
+        } else {
+            0
+        };
+
+        Some((name, full_path, memory_mb))
+    }
+}
+
+/// Full executable path of an open process
+fn image_path_of(process_handle: HANDLE) -> Option<String> {
+    let path_buffer = query_growing(|buffer| {
+        let mut path_len = buffer.len() as u32;
+        if unsafe {
+            QueryFullProcessImageNameW(process_handle, 0, buffer.as_mut_ptr(), &mut path_len)
+        } != 0
+        {
+            BufferQuery::Done(path_len as usize)
+        } else if unsafe { GetLastError() } == ERROR_INSUFFICIENT_BUFFER {
+            BufferQuery::TooSmall
+        } else {
+            BufferQuery::Failed
+        }
+    })?;
+
+    Some(String::from_utf16_lossy(&path_buffer))
+}
+
+/// Full executable path of a running process (`None` if it can't be opened)
+pub(super) fn image_path(pid: u32) -> Option<String> {
+    let process_handle =
+        unsafe { OwnedHandle::new(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid)) }?;
+    image_path_of(process_handle.raw())
+}
+
+/// Number of open handles in an open process (0 if it can't be read)
+fn handle_count_of(process_handle: HANDLE) -> u32 {
+    let mut count = 0;
+    if unsafe { GetProcessHandleCount(process_handle, &mut count) } == 0 {
+        count = 0;
+    }
+    count
+}
+
+/// Everything enumeration reads through a process handle
+struct ProcessDetails {
+    name: String,
+    full_path: String,
+    memory_mb: u64,
+    session_id: u32,
+    handle_count: u32,
+    command_line: Option<String>,
+    is_suspended: bool,
+    /// (total CPU time, creation time) as FILETIME ticks
+    times: Option<(u64, u64)>,
+}
+
+/// Query one process through a single handle (`None` if it can't be opened or
+/// its image name is empty)
+///
+/// `is_suspended` is only checked when `thread_ids` are given.
+fn process_details(pid: u32, thread_ids: Option<Vec<u32>>) -> Option<ProcessDetails> {
+    let process_handle = unsafe {
+        OwnedHandle::new(OpenProcess(
+            PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+            0,
+            pid,
+        ))
+    }?;
+    let (name, full_path, memory_mb) =
+        image_and_memory_of(process_handle.raw()).filter(|(name, _, _)| !name.is_empty())?;
+
+    Some(ProcessDetails {
+        name,
+        full_path,
+        memory_mb,
+        session_id: session_of(pid).unwrap_or(0),
+        handle_count: handle_count_of(process_handle.raw()),
+        command_line: command_line_of(process_handle.raw()),
+        is_suspended: thread_ids.is_some_and(|tids| {
+            let counts: Vec<Option<u32>> =
+                tids.iter().map(|&tid| thread_suspend_count(tid)).collect();
+            threads_suspended(&counts)
+        }),
+        times: process_times_of(process_handle.raw()),
+    })
+}
+
+/// Placeholder for a process whose handle or image name couldn't be read
+///
+/// Named after its PID, with no memory and the `Inaccessible` category so it
+/// is listed but never frozen.
+fn inaccessible_process(pid: u32, parent_pid: u32, thread_count: u32) -> ProcessInfo {
+    let mut info = ProcessInfo::new(
+        pid,
+        format!("<PID {}>", pid),
+        String::new(),
+        0,
+        false,
+        ProcessCategory::Inaccessible,
+    );
+    info.parent_pid = parent_pid;
+    info.session_id = session_of(pid).unwrap_or(0);
+    info.thread_count = thread_count;
+    info.category_reason = Some("couldn't be opened".to_string());
+    info
+}
+
+/// Convert a FILETIME to a 64-bit count of 100-nanosecond intervals
+fn filetime_to_u64(ft: &FILETIME) -> u64 {
+    ((ft.dwHighDateTime as u64) << 32) | ft.dwLowDateTime as u64
+}
 
 /// Windows-specific process enumerator
 pub struct WindowsProcessEnumerator {
-    categorizer: DefaultCategorizer,
+    categorizer: Box<dyn ProcessCategorizer>,
     parent_map: HashMap<u32, u32>,
+    /// Previous CPU sample per PID: (kernel + user time, sample instant)
+    cpu_samples: HashMap<u32, (u64, Instant)>,
+    publishers: PublisherCache,
+    /// Session this process runs in (`None` if it couldn't be read)
+    session_id: Option<u32>,
+    /// Fill `is_suspended` (queries every thread, so off by default)
+    detect_suspended: bool,
+    /// List processes that can't be opened instead of skipping them
+    show_inaccessible: bool,
+    /// Runs `process_details` so a stuck process can't stall enumeration
+    details_worker: TimeoutWorker,
+    /// PIDs whose query is still running on an abandoned worker
+    in_flight: Arc<Mutex<HashSet<u32>>>,
 }
 
 impl WindowsProcessEnumerator {
     pub fn new() -> Self {
+        Self::with_categorizer(Box::new(DefaultCategorizer::new()))
+    }
+
+    /// Create an enumerator that categorizes with a custom categorizer
+    pub fn with_categorizer(categorizer: Box<dyn ProcessCategorizer>) -> Self {
         Self {
-            categorizer: DefaultCategorizer::new(),
+            categorizer,
             parent_map: HashMap::new(),
+            cpu_samples: HashMap::new(),
+            publishers: PublisherCache::new(),
+            session_id: session_of(unsafe { GetCurrentProcessId() }),
+            detect_suspended: false,
+            show_inaccessible: false,
+            details_worker: TimeoutWorker::new(),
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// `process_details` on the worker, giving up after `PROCESS_TIMEOUT`
+    ///
+    /// A PID whose earlier query is still stuck is skipped until it returns,
+    /// so a process that hangs for good costs one thread, not one per pass.
+    fn timed_details(&mut self, pid: u32, thread_ids: Option<Vec<u32>>) -> Option<ProcessDetails> {
+        if !self.in_flight.lock().unwrap().insert(pid) {
+            log::debug!("Skipped PID {}: an earlier query is still running", pid);
+            return None;
         }
+
+        let in_flight = Arc::clone(&self.in_flight);
+        let details = self.details_worker.run(PROCESS_TIMEOUT, move || {
+            let details = process_details(pid, thread_ids);
+            in_flight.lock().unwrap().remove(&pid);
+            details
+        });
+
+        details.unwrap_or_else(|| {
+            log::warn!(
+                "Skipped PID {}: querying it took longer than {} ms",
+                pid,
+                PROCESS_TIMEOUT.as_millis()
+            );
+            None
+        })
     }
 
-    /// Get process name and path
-    fn get_process_info(&self, pid: u32) -> (String, String) {
+    /// Check each process's threads for `is_suspended` during enumeration
+    pub fn set_detect_suspended(&mut self, enabled: bool) {
+        self.detect_suspended = enabled;
+    }
+
+    /// Include processes that can't be opened as placeholders, so the count
+    /// matches Task Manager
+    pub fn set_show_inaccessible(&mut self, enabled: bool) {
+        self.show_inaccessible = enabled;
+    }
+
+    /// Total and available physical memory in MB
+    pub fn system_memory_mb(&self) -> (u64, u64) {
         unsafe {
-            let process_handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, 0, pid);
+            let mut status: MEMORYSTATUSEX = mem::zeroed();
+            status.dwLength = mem::size_of::<MEMORYSTATUSEX>() as u32;
 
-            if process_handle.is_null() {
-                return (String::new(), String::new());
+            if GlobalMemoryStatusEx(&mut status) == 0 {
+                return (0, 0);
             }
 
-            let mut path_buffer: [u16; 260] = [0; 260];
-            let mut path_len = path_buffer.len() as u32;
-
-            if QueryFullProcessImageNameW(
-                process_handle,
-                0,
-                path_buffer.as_mut_ptr(),
-                &mut path_len,
-            ) != 0
-            {
-                let full_path = String::from_utf16_lossy(&path_buffer[..path_len as usize]);
-                let name = full_path
-                    .rsplit('\\')
-                    .next()
-                    .unwrap_or("unknown.exe")
-                    .to_string();
-
-                CloseHandle(process_handle);
-                (name, full_path)
-            } else {
-                CloseHandle(process_handle);
-                (String::new(), String::new())
-            }
+            (
+                status.ullTotalPhys / (1024 * 1024),
+                status.ullAvailPhys / (1024 * 1024),
+            )
         }
     }
 
-    /// Get process memory usage in MB
-    fn get_memory_usage(&self, pid: u32) -> u64 {
+    /// Seconds since the last keyboard or mouse input in this session
+    pub fn idle_seconds(&self) -> Option<u64> {
         unsafe {
-            let process_handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, 0, pid);
-
-            if process_handle.is_null() {
-                return 0;
+            let mut info = LASTINPUTINFO {
+                cbSize: mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            if GetLastInputInfo(&mut info) == 0 {
+                return None;
             }
+            Some(idle_seconds(GetTickCount(), info.dwTime))
+        }
+    }
 
-            let mut pmc: PROCESS_MEMORY_COUNTERS = mem::zeroed();
-            pmc.cb = mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    /// Check whether the foreground window is borderless/exclusive fullscreen
+    ///
+    /// The desktop and shell windows also cover the screen and are ignored.
+    pub fn is_foreground_fullscreen(&self) -> bool {
+        unsafe {
+            let hwnd: HWND = GetForegroundWindow();
+            if hwnd.is_null() || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+                return false;
+            }
 
-            if GetProcessMemoryInfo(process_handle, &mut pmc, pmc.cb) != 0 {
-                CloseHandle(process_handle);
-                pmc.WorkingSetSize as u64 / (1024 * 1024)
-            } else {
-                CloseHandle(process_handle);
-                0
+            let mut rect: RECT = mem::zeroed();
+            if GetWindowRect(hwnd, &mut rect) == 0 {
+                return false;
             }
+
+            covers_screen(
+                (rect.left, rect.top, rect.right, rect.bottom),
+                GetSystemMetrics(SM_CXSCREEN),
+                GetSystemMetrics(SM_CYSCREEN),
+            )
         }
     }
 
@@ -116,54 +574,120 @@ impl Default for WindowsProcessEnumerator {
 impl ProcessEnumerator for WindowsProcessEnumerator {
     fn enumerate(&mut self) -> Result<Vec<ProcessInfo>> {
         unsafe {
-            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
-            if snapshot.is_null() || snapshot == (-1isize) as HANDLE {
-                return Err(SmartFreezeError::ProcessEnumeration(
-                    "Failed to create process snapshot".to_string(),
-                ));
-            }
+            let Some(snapshot) = OwnedHandle::new(CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0))
+            else {
+                return Err(super::last_error("CreateToolhelp32Snapshot"));
+            };
 
             let mut processes = Vec::new();
+            let mut inaccessible = Vec::new();
+            let mut snapshot_pids = HashSet::new();
             let mut entry: PROCESSENTRY32W = mem::zeroed();
             entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as u32;
 
             let foreground_pid = self.get_foreground_pid_internal();
+            let visible_window_pids = pids_with_visible_windows(&top_level_windows());
+            let mut cpu_samples = HashMap::new();
+            let threads = if self.detect_suspended {
+                threads_by_process()
+            } else {
+                HashMap::new()
+            };
 
-            if Process32FirstW(snapshot, &mut entry) != 0 {
+            if Process32FirstW(snapshot.raw(), &mut entry) != 0 {
                 loop {
                     let pid = entry.th32ProcessID;
                     let parent_pid = entry.th32ParentProcessID;
+                    snapshot_pids.insert(pid);
 
                     // Store parent relationship
                     self.parent_map.insert(pid, parent_pid);
                     self.categorizer.update_parent_map(pid, parent_pid);
 
-                    if pid != 0 {
-                        let (name, full_path) = self.get_process_info(pid);
-
-                        if !name.is_empty() {
-                            let memory_mb = self.get_memory_usage(pid);
-                            let is_foreground = foreground_pid == Some(pid);
-                            let category = self.categorizer.categorize(pid, &name, &full_path);
-
-                            processes.push(ProcessInfo::new(
-                                pid,
-                                name,
-                                full_path,
-                                memory_mb,
-                                is_foreground,
-                                category,
-                            ));
+                    let details = match pid {
+                        // System Idle Process
+                        0 => None,
+                        pid => self.timed_details(pid, threads.get(&pid).cloned()),
+                    };
+                    if details.is_none() && self.show_inaccessible {
+                        inaccessible.push(inaccessible_process(pid, parent_pid, entry.cntThreads));
+                    }
+
+                    if let Some(details) = details {
+                        let is_foreground = foreground_pid == Some(pid);
+                        self.categorizer
+                            .record_process(pid, &details.name, &details.full_path);
+
+                        // Categorized once the whole tree is known (see below)
+                        let mut info = ProcessInfo::new(
+                            pid,
+                            details.name,
+                            details.full_path,
+                            details.memory_mb,
+                            is_foreground,
+                            ProcessCategory::Unknown,
+                        );
+                        info.parent_pid = parent_pid;
+                        info.session_id = details.session_id;
+                        info.thread_count = entry.cntThreads;
+                        info.handle_count = details.handle_count;
+                        info.has_visible_window = visible_window_pids.contains(&pid);
+                        info.command_line = details.command_line;
+                        info.is_suspended = details.is_suspended;
+
+                        // First sample for a PID has no baseline and reports 0.0
+                        if let Some((cpu_time, created)) = details.times {
+                            info.age_secs = Some(age_secs_since(created, SystemTime::now()));
+                            let sample = (cpu_time, Instant::now());
+                            if let Some(&previous) = self.cpu_samples.get(&pid) {
+                                info.cpu_percent = cpu_percent_between(previous, sample);
+                            }
+                            cpu_samples.insert(pid, sample);
                         }
+
+                        processes.push(info);
                     }
 
-                    if Process32NextW(snapshot, &mut entry) == 0 {
+                    if Process32NextW(snapshot.raw(), &mut entry) == 0 {
                         break;
                     }
                 }
             }
+            drop(snapshot);
+
+            // Exited PIDs can be reused, so stale links must not outlive them
+            self.parent_map.retain(|pid, _| snapshot_pids.contains(pid));
+            self.categorizer.retain_processes(&snapshot_pids);
+
+            // Parents may appear after their children in the snapshot
+            for info in &mut processes {
+                let (mut category, mut reason) = self.categorizer.categorize_with_cmdline(
+                    info.pid,
+                    &info.name,
+                    &info.full_path,
+                    info.command_line.as_deref(),
+                );
+
+                // Signature checks are slow, so only done when they could change the outcome
+                if category != ProcessCategory::Critical && category != ProcessCategory::Gaming {
+                    info.publisher = self.publishers.get(&info.full_path);
+                    if let Some(publisher) = &info.publisher {
+                        if let Some(signed) = self.categorizer.categorize_publisher(publisher) {
+                            category = signed;
+                            reason = format!("signed by {}", publisher);
+                        }
+                    }
+                }
+
+                info.category = category;
+                info.category_reason = Some(reason);
+            }
+
+            // Drop samples of exited processes
+            self.cpu_samples = cpu_samples;
+
+            processes.extend(inaccessible);
 
-            CloseHandle(snapshot);
             Ok(processes)
         }
     }
@@ -171,18 +695,221 @@ impl ProcessEnumerator for WindowsProcessEnumerator {
     fn get_foreground_pid(&self) -> Option<u32> {
         self.get_foreground_pid_internal()
     }
+
+    fn is_foreground_fullscreen(&self) -> bool {
+        WindowsProcessEnumerator::is_foreground_fullscreen(self)
+    }
+
+    fn current_session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+
+    fn idle_seconds(&self) -> Option<u64> {
+        WindowsProcessEnumerator::idle_seconds(self)
+    }
+
+    fn system_memory_mb(&self) -> Option<(u64, u64)> {
+        match WindowsProcessEnumerator::system_memory_mb(self) {
+            (0, _) => None,
+            memory => Some(memory),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Simulates a path query that needs `path.len()` units
+    fn simulated_query(path: &[u16], calls: &mut Vec<usize>, buffer: &mut [u16]) -> BufferQuery {
+        calls.push(buffer.len());
+        if buffer.len() < path.len() {
+            return BufferQuery::TooSmall;
+        }
+        buffer[..path.len()].copy_from_slice(path);
+        BufferQuery::Done(path.len())
+    }
+
+    #[test]
+    fn test_query_growing_retries_with_larger_buffer() {
+        let path: Vec<u16> = format!("C:\\{}\\game.exe", "nested\\".repeat(60))
+            .encode_utf16()
+            .collect();
+        assert!(path.len() > INITIAL_PATH_LEN);
+
+        let mut calls = Vec::new();
+        let result = query_growing(|buffer| simulated_query(&path, &mut calls, buffer));
+
+        assert_eq!(result, Some(path));
+        assert_eq!(calls, vec![260, 520]);
+    }
+
+    #[test]
+    fn test_query_growing_stops_at_nt_limit() {
+        let path = vec![b'a' as u16; MAX_NT_PATH_LEN + 1];
+
+        let mut calls = Vec::new();
+        let result = query_growing(|buffer| simulated_query(&path, &mut calls, buffer));
+
+        assert_eq!(result, None);
+        assert_eq!(calls.last(), Some(&MAX_NT_PATH_LEN));
+    }
+
+    #[test]
+    fn test_query_growing_gives_up_on_failure() {
+        assert_eq!(query_growing(|_| BufferQuery::Failed), None);
+    }
+
+    #[test]
+    fn test_repeated_enumeration_is_consistent() {
+        let mut enumerator = WindowsProcessEnumerator::new();
+        let own_pid = unsafe { GetCurrentProcessId() };
+
+        let first = enumerator.enumerate().unwrap();
+        let second = enumerator.enumerate().unwrap();
+
+        // Processes present in both snapshots report the same identity
+        for process in &first {
+            if let Some(again) = second.iter().find(|p| p.pid == process.pid) {
+                assert_eq!(again.name, process.name);
+                assert_eq!(again.full_path, process.full_path);
+            }
+        }
+
+        let own = second.iter().find(|p| p.pid == own_pid).unwrap();
+        assert!(own.memory_mb > 0);
+        assert!(!own.name.is_empty());
+    }
+
+    #[test]
+    fn test_current_process_counts() {
+        let mut enumerator = WindowsProcessEnumerator::new();
+        let own_pid = unsafe { GetCurrentProcessId() };
+
+        let processes = enumerator.enumerate().unwrap();
+        let own = processes.iter().find(|p| p.pid == own_pid).unwrap();
+
+        assert!(own.thread_count > 0);
+        assert!(own.handle_count > 0);
+    }
+
+    #[test]
+    fn test_own_command_line_is_read() {
+        use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+        let command_line = command_line_of(unsafe { GetCurrentProcess() }).unwrap();
+        assert!(!command_line.is_empty());
+    }
+
+    #[test]
+    fn test_running_process_is_not_suspended() {
+        let mut enumerator = WindowsProcessEnumerator::new();
+        enumerator.set_detect_suspended(true);
+        let own_pid = unsafe { GetCurrentProcessId() };
+
+        let processes = enumerator.enumerate().unwrap();
+        let own = processes.iter().find(|p| p.pid == own_pid).unwrap();
+
+        assert!(!own.is_suspended);
+    }
+
+    #[test]
+    fn test_inaccessible_placeholder() {
+        let own_pid = unsafe { GetCurrentProcessId() };
+        let info = inaccessible_process(own_pid, 4, 7);
+
+        assert_eq!(info.name, format!("<PID {}>", own_pid));
+        assert_eq!(info.memory_mb, 0);
+        assert_eq!(info.parent_pid, 4);
+        assert_eq!(info.thread_count, 7);
+        assert_eq!(info.category, ProcessCategory::Inaccessible);
+        assert!(!info.is_safe_to_freeze(false));
+    }
+
+    #[test]
+    fn test_show_inaccessible_lists_placeholders() {
+        let mut enumerator = WindowsProcessEnumerator::new();
+        let accessible = enumerator.enumerate().unwrap();
+        assert!(accessible
+            .iter()
+            .all(|p| p.category != ProcessCategory::Inaccessible));
+
+        enumerator.set_show_inaccessible(true);
+        let all = enumerator.enumerate().unwrap();
+
+        // The System Idle Process can never be opened
+        let idle = all.iter().find(|p| p.pid == 0).unwrap();
+        assert_eq!(idle.name, "<PID 0>");
+        assert_eq!(idle.category, ProcessCategory::Inaccessible);
+        assert!(all.len() > accessible.len());
+    }
+
+    #[test]
+    fn test_own_session_is_known() {
+        let enumerator = WindowsProcessEnumerator::new();
+        assert!(enumerator.current_session_id().is_some());
+    }
+
+    #[test]
+    fn test_system_memory_mb() {
+        let (total, available) = WindowsProcessEnumerator::new().system_memory_mb();
+        assert!(total > 0);
+        assert!(available <= total);
+    }
+
     #[test]
     fn test_enumerator_creation() {
         let enumerator = WindowsProcessEnumerator::new();
         assert!(enumerator.parent_map.is_empty());
     }
 
+    #[test]
+    fn test_age_secs_since() {
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        let created = (FILETIME_UNIX_OFFSET_SECS + 940) * 10_000_000;
+
+        assert_eq!(age_secs_since(created, now), 60);
+        assert_eq!(age_secs_since(created * 2, now), 0);
+    }
+
+    #[test]
+    fn test_covers_screen() {
+        assert!(covers_screen((0, 0, 1920, 1080), 1920, 1080));
+        // Exclusive fullscreen windows may extend past the edges
+        assert!(covers_screen((-8, -8, 1928, 1088), 1920, 1080));
+        assert!(!covers_screen((0, 0, 1920, 1040), 1920, 1080));
+        assert!(!covers_screen((100, 0, 1920, 1080), 1920, 1080));
+        assert!(!covers_screen((0, 0, 0, 0), 0, 0));
+    }
+
+    #[test]
+    fn test_idle_seconds() {
+        assert_eq!(idle_seconds(10_000, 10_000), 0);
+        assert_eq!(idle_seconds(75_999, 10_000), 65);
+        // Input just before the tick counter wrapped
+        assert_eq!(idle_seconds(4_000, u32::MAX - 5_999), 10);
+    }
+
+    #[test]
+    fn test_cpu_percent_between() {
+        let start = Instant::now();
+        let later = start + std::time::Duration::from_secs(2);
+
+        // 1 second of CPU time over 2 seconds = 50% of one core
+        assert_eq!(cpu_percent_between((0, start), (10_000_000, later)), 50.0);
+        // No elapsed time yields 0 rather than dividing by zero
+        assert_eq!(cpu_percent_between((0, start), (10_000_000, start)), 0.0);
+    }
+
+    #[test]
+    fn test_filetime_to_u64() {
+        let ft = FILETIME {
+            dwLowDateTime: 1,
+            dwHighDateTime: 1,
+        };
+        assert_eq!(filetime_to_u64(&ft), (1u64 << 32) + 1);
+    }
+
     #[test]
     #[cfg(windows)]
     fn test_enumerate_processes() {
@@ -0,0 +1,169 @@
+//! Handles that close themselves on drop, so no return path can leak them
+
+use std::marker::PhantomData;
+use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::System::Registry::{RegCloseKey, HKEY};
+
+/// How one kind of handle is released
+pub trait Closer {
+    type Raw: Copy;
+
+    /// Release `raw`; called exactly once per owned handle
+    fn close(raw: Self::Raw);
+}
+
+/// An open handle, released with `C` when dropped
+pub struct Owned<C: Closer> {
+    raw: C::Raw,
+    closer: PhantomData<C>,
+}
+
+impl<C: Closer> Owned<C> {
+    /// Take ownership of an open handle
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be open and must not be closed anywhere else.
+    pub unsafe fn from_raw(raw: C::Raw) -> Self {
+        Self {
+            raw,
+            closer: PhantomData,
+        }
+    }
+
+    /// The handle, still owned by `self`
+    pub fn raw(&self) -> C::Raw {
+        self.raw
+    }
+}
+
+impl<C: Closer> Drop for Owned<C> {
+    fn drop(&mut self) {
+        C::close(self.raw);
+    }
+}
+
+/// Kernel objects: processes, threads, snapshots, jobs
+pub enum KernelObject {}
+
+impl Closer for KernelObject {
+    type Raw = HANDLE;
+
+    fn close(raw: HANDLE) {
+        unsafe {
+            CloseHandle(raw);
+        }
+    }
+}
+
+/// Open registry keys
+pub enum RegistryKey {}
+
+impl Closer for RegistryKey {
+    type Raw = HKEY;
+
+    fn close(raw: HKEY) {
+        unsafe {
+            RegCloseKey(raw);
+        }
+    }
+}
+
+/// A kernel object handle closed with `CloseHandle`
+pub type OwnedHandle = Owned<KernelObject>;
+/// A registry key closed with `RegCloseKey`
+pub type OwnedKey = Owned<RegistryKey>;
+
+// Kernel handles may be used and closed from any thread
+unsafe impl Send for OwnedHandle {}
+
+impl OwnedHandle {
+    /// Own the handle returned by a Win32 call; `None` if the call failed
+    /// (null or `INVALID_HANDLE_VALUE`), leaving its last error intact
+    ///
+    /// # Safety
+    ///
+    /// A valid `raw` must not be closed anywhere else.
+    pub unsafe fn new(raw: HANDLE) -> Option<Self> {
+        if raw.is_null() || raw == INVALID_HANDLE_VALUE {
+            None
+        } else {
+            Some(Self::from_raw(raw))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static CLOSED: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Records closed values instead of calling Win32
+    enum CountingCloser {}
+
+    impl Closer for CountingCloser {
+        type Raw = u32;
+
+        fn close(raw: u32) {
+            CLOSED.with(|closed| closed.borrow_mut().push(raw));
+        }
+    }
+
+    fn closed() -> Vec<u32> {
+        CLOSED.with(|closed| closed.borrow().clone())
+    }
+
+    /// Opens two handles and bails out between them when `fail` is set
+    fn open_two(fail: bool) -> Result<u32, ()> {
+        let first = unsafe { Owned::<CountingCloser>::from_raw(1) };
+        if fail {
+            return Err(());
+        }
+        let second = unsafe { Owned::<CountingCloser>::from_raw(2) };
+        Ok(first.raw() + second.raw())
+    }
+
+    #[test]
+    fn test_drop_closes_once() {
+        {
+            let handle = unsafe { Owned::<CountingCloser>::from_raw(7) };
+            assert_eq!(handle.raw(), 7);
+            assert!(closed().is_empty());
+        }
+        assert_eq!(closed(), vec![7]);
+    }
+
+    #[test]
+    fn test_every_return_path_closes() {
+        assert_eq!(open_two(false), Ok(3));
+        assert_eq!(closed(), vec![2, 1]);
+
+        assert_eq!(open_two(true), Err(()));
+        assert_eq!(closed(), vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn test_failed_calls_are_not_owned() {
+        unsafe {
+            assert!(OwnedHandle::new(std::ptr::null_mut()).is_none());
+            assert!(OwnedHandle::new(INVALID_HANDLE_VALUE).is_none());
+        }
+    }
+
+    #[test]
+    fn test_owned_process_handle() {
+        use windows_sys::Win32::System::Threading::{
+            GetProcessId, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+
+        let pid = std::process::id();
+        let handle =
+            unsafe { OwnedHandle::new(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid)) }
+                .unwrap();
+        assert_eq!(unsafe { GetProcessId(handle.raw()) }, pid);
+    }
+}
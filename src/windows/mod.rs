@@ -2,8 +2,23 @@
 
 pub mod controller;
 pub mod enumerator;
+pub mod handle;
 pub mod registry;
+pub mod scheduler;
+pub mod signature;
 
 pub use controller::WindowsProcessController;
 pub use enumerator::WindowsProcessEnumerator;
-pub use registry::WindowsRegistry;
+pub use registry::{RegistryStatePersistence, StartupOptions, WindowsRegistry};
+pub use scheduler::ScheduledTaskRegistrar;
+pub use signature::PublisherCache;
+
+use crate::SmartFreezeError;
+
+/// `SmartFreezeError::Win32` for `call`, with the calling thread's last error code
+pub(crate) fn last_error(call: &'static str) -> SmartFreezeError {
+    SmartFreezeError::Win32 {
+        call,
+        code: unsafe { windows_sys::Win32::Foundation::GetLastError() },
+    }
+}
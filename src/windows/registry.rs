@@ -1,13 +1,102 @@
 //! Windows registry management
 
+use super::handle::OwnedKey;
+use crate::cli::{resolve_state_location, Args};
+use crate::persistence::{FileStatePersistence, PersistentState, StateLocation, StatePersistence};
 use crate::{Result, SmartFreezeError};
+use std::path::PathBuf;
+use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND};
 use windows_sys::Win32::System::Registry::{
-    RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
-    KEY_SET_VALUE, KEY_WRITE, REG_SZ,
+    RegCreateKeyExW, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
+    HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_QUERY_VALUE, KEY_SET_VALUE, KEY_WRITE,
+    REG_OPTION_NON_VOLATILE, REG_SZ,
 };
 
 const STARTUP_KEY_PATH: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
 const APP_NAME: &str = "SmartFreeze";
+const STATE_KEY_PATH: &str = "Software\\SmartFreeze";
+const STATE_VALUE_NAME: &str = "State";
+
+/// Daemon options baked into the startup command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupOptions {
+    pub interval: u64,
+    pub threshold: u64,
+    pub keep_communication: bool,
+    /// State file other than the default, so the boot-time daemon uses it too
+    pub state_file: Option<PathBuf>,
+    pub state_registry: bool,
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        Self {
+            interval: 60,
+            threshold: 100,
+            keep_communication: false,
+            state_file: None,
+            state_registry: false,
+        }
+    }
+}
+
+impl StartupOptions {
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            interval: args.interval,
+            threshold: args.threshold,
+            keep_communication: args.keep_communication,
+            ..Self::default()
+        }
+        .with_state(&resolve_state_location(args))
+    }
+
+    /// Carry the saved state location; the default state file needs no flag
+    pub fn with_state(mut self, location: &StateLocation) -> Self {
+        match location {
+            StateLocation::File(path) => {
+                self.state_file =
+                    (*path != FileStatePersistence::default_path()).then(|| path.clone());
+                self.state_registry = false;
+            }
+            StateLocation::Registry => {
+                self.state_file = None;
+                self.state_registry = true;
+            }
+        }
+        self
+    }
+
+    /// Full command line for launching the daemon with these options
+    pub fn command_line(&self, exe_path: &str) -> String {
+        let mut cmd = format!(
+            "{} --daemon --interval {} --threshold {}",
+            quote_arg(exe_path),
+            self.interval,
+            self.threshold
+        );
+        if self.keep_communication {
+            cmd.push_str(" --keep-communication");
+        }
+        if let Some(path) = &self.state_file {
+            cmd.push_str(" --state-file ");
+            cmd.push_str(&quote_arg(&path.to_string_lossy()));
+        }
+        if self.state_registry {
+            cmd.push_str(" --state-registry");
+        }
+        cmd
+    }
+}
+
+/// Quote one command-line argument for `CommandLineToArgvW`
+///
+/// Paths can't contain `"`, but trailing backslashes must be doubled so they
+/// don't escape the closing quote.
+fn quote_arg(arg: &str) -> String {
+    let trailing = arg.len() - arg.trim_end_matches('\\').len();
+    format!("\"{}{}\"", arg, "\\".repeat(trailing))
+}
 
 /// Windows registry manager for startup entries
 pub struct WindowsRegistry;
@@ -18,37 +107,90 @@ impl WindowsRegistry {
     }
 
     /// Convert string to wide (UTF-16) string for Windows API
-    fn to_wide_string(s: &str) -> Vec<u16> {
+    pub(crate) fn to_wide_string(s: &str) -> Vec<u16> {
         s.encode_utf16().chain(std::iter::once(0)).collect()
     }
 
-    /// Install SmartFreeze to Windows startup
-    pub fn install_startup(&self, exe_path: &str) -> Result<()> {
+    /// Install SmartFreeze to Windows startup for the current user
+    pub fn install_startup(&self, exe_path: &str, options: &StartupOptions) -> Result<()> {
+        self.install_in(HKEY_CURRENT_USER, &options.command_line(exe_path))
+    }
+
+    /// Install SmartFreeze to Windows startup for all users (requires elevation)
+    pub fn install_startup_all_users(
+        &self,
+        exe_path: &str,
+        options: &StartupOptions,
+    ) -> Result<()> {
+        self.install_in(HKEY_LOCAL_MACHINE, &options.command_line(exe_path))
+    }
+
+    /// Read the current user's stored startup command, if installed
+    pub fn read_startup_command(&self) -> Result<Option<String>> {
+        Self::read_command_in(HKEY_CURRENT_USER)
+    }
+
+    /// Read the all-users stored startup command, if installed
+    pub fn read_startup_command_all_users(&self) -> Result<Option<String>> {
+        Self::read_command_in(HKEY_LOCAL_MACHINE)
+    }
+
+    /// Uninstall SmartFreeze from Windows startup for the current user
+    pub fn uninstall_startup(&self) -> Result<()> {
+        self.uninstall_from(HKEY_CURRENT_USER)
+    }
+
+    /// Uninstall the all-users startup entry (requires elevation)
+    pub fn uninstall_startup_all_users(&self) -> Result<()> {
+        self.uninstall_from(HKEY_LOCAL_MACHINE)
+    }
+
+    /// Check if SmartFreeze is installed in the current user's startup
+    pub fn is_installed(&self) -> bool {
+        self.is_installed_in(HKEY_CURRENT_USER)
+    }
+
+    /// Check if SmartFreeze is installed in the all-users startup
+    pub fn is_installed_all_users(&self) -> bool {
+        self.is_installed_in(HKEY_LOCAL_MACHINE)
+    }
+
+    /// Check both the current-user and all-users startup entries
+    pub fn is_installed_any(&self) -> bool {
+        self.is_installed() || self.is_installed_all_users()
+    }
+
+    /// Open the Run key in a hive, mapping access denied to a clear message
+    fn open_run_key(hive: HKEY, access: u32) -> Result<OwnedKey> {
         unsafe {
             let key_path = Self::to_wide_string(STARTUP_KEY_PATH);
             let mut hkey: HKEY = std::ptr::null_mut();
 
-            let result = RegOpenKeyExW(
-                HKEY_CURRENT_USER,
-                key_path.as_ptr(),
-                0,
-                KEY_WRITE,
-                &mut hkey,
-            );
+            let result = RegOpenKeyExW(hive, key_path.as_ptr(), 0, access, &mut hkey);
 
-            if result != 0 {
-                return Err(SmartFreezeError::Registry(format!(
-                    "Failed to open registry key: error code {}",
-                    result
-                )));
+            match result {
+                0 => Ok(OwnedKey::from_raw(hkey)),
+                ERROR_ACCESS_DENIED => Err(SmartFreezeError::Registry(
+                    "Access denied opening the startup key (run as administrator for --all-users)"
+                        .to_string(),
+                )),
+                code => Err(SmartFreezeError::Win32 {
+                    call: "RegOpenKeyExW",
+                    code,
+                }),
             }
+        }
+    }
 
+    fn install_in(&self, hive: HKEY, command: &str) -> Result<()> {
+        let hkey = Self::open_run_key(hive, KEY_WRITE)?;
+
+        unsafe {
             let app_name = Self::to_wide_string(APP_NAME);
-            let daemon_cmd = format!("\"{}\" --daemon", exe_path);
-            let value = Self::to_wide_string(&daemon_cmd);
+            let value = Self::to_wide_string(command);
 
             let result = RegSetValueExW(
-                hkey,
+                hkey.raw(),
                 app_name.as_ptr(),
                 0,
                 REG_SZ,
@@ -56,103 +198,255 @@ impl WindowsRegistry {
                 (value.len() * 2) as u32,
             );
 
-            RegCloseKey(hkey);
-
             if result != 0 {
-                Err(SmartFreezeError::Registry(format!(
-                    "Failed to set registry value: error code {}",
-                    result
-                )))
+                Err(SmartFreezeError::Win32 {
+                    call: "RegSetValueExW",
+                    code: result,
+                })
             } else {
                 Ok(())
             }
         }
     }
 
-    /// Uninstall SmartFreeze from Windows startup
-    pub fn uninstall_startup(&self) -> Result<()> {
+    fn uninstall_from(&self, hive: HKEY) -> Result<()> {
+        let hkey = Self::open_run_key(hive, KEY_SET_VALUE)?;
+
         unsafe {
-            let key_path = Self::to_wide_string(STARTUP_KEY_PATH);
-            let mut hkey: HKEY = std::ptr::null_mut();
+            let app_name = Self::to_wide_string(APP_NAME);
+            let result = RegDeleteValueW(hkey.raw(), app_name.as_ptr());
 
-            let result = RegOpenKeyExW(
-                HKEY_CURRENT_USER,
-                key_path.as_ptr(),
-                0,
-                KEY_SET_VALUE,
-                &mut hkey,
+            // ERROR_FILE_NOT_FOUND means already uninstalled
+            if result != 0 && result != ERROR_FILE_NOT_FOUND {
+                Err(SmartFreezeError::Win32 {
+                    call: "RegDeleteValueW",
+                    code: result,
+                })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn is_installed_in(&self, hive: HKEY) -> bool {
+        matches!(Self::read_command_in(hive), Ok(Some(_)))
+    }
+
+    /// Read the `REG_SZ` startup value from a hive (`None` if absent)
+    fn read_command_in(hive: HKEY) -> Result<Option<String>> {
+        let hkey = Self::open_run_key(hive, KEY_QUERY_VALUE)?;
+
+        unsafe {
+            let app_name = Self::to_wide_string(APP_NAME);
+
+            // First call reports the size in bytes
+            let mut size: u32 = 0;
+            let result = RegQueryValueExW(
+                hkey.raw(),
+                app_name.as_ptr(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut size,
             );
+            if result == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            }
+
+            let mut buffer: Vec<u16> = vec![0; (size as usize).div_ceil(2)];
+            let result = if result == 0 {
+                RegQueryValueExW(
+                    hkey.raw(),
+                    app_name.as_ptr(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    buffer.as_mut_ptr() as *mut u8,
+                    &mut size,
+                )
+            } else {
+                result
+            };
 
             if result != 0 {
-                return Err(SmartFreezeError::Registry(format!(
-                    "Failed to open registry key: error code {}",
-                    result
-                )));
+                return Err(SmartFreezeError::Win32 {
+                    call: "RegQueryValueExW",
+                    code: result,
+                });
             }
 
-            let app_name = Self::to_wide_string(APP_NAME);
-            let result = RegDeleteValueW(hkey, app_name.as_ptr());
+            Ok(Some(wide_to_string(&buffer)))
+        }
+    }
+}
 
-            RegCloseKey(hkey);
+/// Decode a (possibly NUL-terminated) UTF-16 buffer
+fn wide_to_string(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
 
-            if result != 0 {
-                // Error code 2 means "file not found" which is OK (already uninstalled)
-                if result == 2 {
-                    Ok(())
-                } else {
-                    Err(SmartFreezeError::Registry(format!(
-                        "Failed to delete registry value: error code {}",
-                        result
-                    )))
-                }
-            } else {
-                Ok(())
+impl Default for WindowsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State persistence in `HKCU\Software\SmartFreeze`, out of reach of temp-dir cleanup
+///
+/// The serialized `PersistentState` JSON is stored as a single `REG_SZ` value.
+pub struct RegistryStatePersistence {
+    key_path: String,
+}
+
+impl RegistryStatePersistence {
+    pub fn new(key_path: &str) -> Self {
+        Self {
+            key_path: key_path.to_string(),
+        }
+    }
+
+    pub fn with_default_key() -> Self {
+        Self::new(STATE_KEY_PATH)
+    }
+
+    /// Open the state key, or `None` if it doesn't exist yet
+    fn open_key(&self, access: u32) -> Result<Option<OwnedKey>> {
+        unsafe {
+            let key_path = WindowsRegistry::to_wide_string(&self.key_path);
+            let mut hkey: HKEY = std::ptr::null_mut();
+
+            match RegOpenKeyExW(HKEY_CURRENT_USER, key_path.as_ptr(), 0, access, &mut hkey) {
+                0 => Ok(Some(OwnedKey::from_raw(hkey))),
+                ERROR_FILE_NOT_FOUND => Ok(None),
+                code => Err(SmartFreezeError::Win32 {
+                    call: "RegOpenKeyExW",
+                    code,
+                }),
             }
         }
     }
 
-    /// Check if SmartFreeze is installed in startup
-    pub fn is_installed(&self) -> bool {
+    fn create_key(&self) -> Result<OwnedKey> {
         unsafe {
-            let key_path = Self::to_wide_string(STARTUP_KEY_PATH);
+            let key_path = WindowsRegistry::to_wide_string(&self.key_path);
             let mut hkey: HKEY = std::ptr::null_mut();
 
-            let result = RegOpenKeyExW(
+            let result = RegCreateKeyExW(
                 HKEY_CURRENT_USER,
                 key_path.as_ptr(),
                 0,
+                std::ptr::null(),
+                REG_OPTION_NON_VOLATILE,
                 KEY_SET_VALUE,
+                std::ptr::null(),
                 &mut hkey,
+                std::ptr::null_mut(),
             );
 
             if result != 0 {
-                return false;
+                return Err(SmartFreezeError::Win32 {
+                    call: "RegCreateKeyExW",
+                    code: result,
+                });
             }
+            Ok(OwnedKey::from_raw(hkey))
+        }
+    }
+}
 
-            // Try to query the value
-            let app_name = Self::to_wide_string(APP_NAME);
-            let mut buffer: [u16; 260] = [0; 260];
-            let mut buffer_size = (buffer.len() * 2) as u32;
+impl StatePersistence for RegistryStatePersistence {
+    fn save(&self, state: &PersistentState) -> Result<()> {
+        let json = serde_json::to_string(state)?;
+        let hkey = self.create_key()?;
 
-            let result = windows_sys::Win32::System::Registry::RegQueryValueExW(
-                hkey,
-                app_name.as_ptr(),
+        unsafe {
+            let value_name = WindowsRegistry::to_wide_string(STATE_VALUE_NAME);
+            let value = WindowsRegistry::to_wide_string(&json);
+
+            let result = RegSetValueExW(
+                hkey.raw(),
+                value_name.as_ptr(),
+                0,
+                REG_SZ,
+                value.as_ptr() as *const u8,
+                (value.len() * 2) as u32,
+            );
+
+            if result != 0 {
+                return Err(SmartFreezeError::Win32 {
+                    call: "RegSetValueExW",
+                    code: result,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<PersistentState>> {
+        let Some(hkey) = self.open_key(KEY_QUERY_VALUE)? else {
+            return Ok(None);
+        };
+
+        unsafe {
+            let value_name = WindowsRegistry::to_wide_string(STATE_VALUE_NAME);
+
+            // First call reports the size in bytes
+            let mut size: u32 = 0;
+            let result = RegQueryValueExW(
+                hkey.raw(),
+                value_name.as_ptr(),
+                std::ptr::null_mut(),
                 std::ptr::null_mut(),
                 std::ptr::null_mut(),
-                buffer.as_mut_ptr() as *mut u8,
-                &mut buffer_size,
+                &mut size,
             );
+            if result == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            }
 
-            RegCloseKey(hkey);
+            let mut buffer: Vec<u16> = vec![0; (size as usize).div_ceil(2)];
+            let result = if result == 0 {
+                RegQueryValueExW(
+                    hkey.raw(),
+                    value_name.as_ptr(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    buffer.as_mut_ptr() as *mut u8,
+                    &mut size,
+                )
+            } else {
+                result
+            };
 
-            result == 0
+            if result != 0 {
+                return Err(SmartFreezeError::Win32 {
+                    call: "RegQueryValueExW",
+                    code: result,
+                });
+            }
+
+            PersistentState::from_json(&wide_to_string(&buffer)).map(Some)
         }
     }
-}
 
-impl Default for WindowsRegistry {
-    fn default() -> Self {
-        Self::new()
+    fn delete(&self) -> Result<()> {
+        let Some(hkey) = self.open_key(KEY_SET_VALUE)? else {
+            return Ok(());
+        };
+
+        unsafe {
+            let value_name = WindowsRegistry::to_wide_string(STATE_VALUE_NAME);
+            let result = RegDeleteValueW(hkey.raw(), value_name.as_ptr());
+
+            // ERROR_FILE_NOT_FOUND means nothing was saved
+            if result != 0 && result != ERROR_FILE_NOT_FOUND {
+                return Err(SmartFreezeError::Win32 {
+                    call: "RegDeleteValueW",
+                    code: result,
+                });
+            }
+        }
+        Ok(())
     }
 }
 
@@ -173,6 +467,103 @@ mod tests {
         assert_eq!(wide[4], 0); // Null terminator
     }
 
-    // Note: Actual registry operations are tested in integration tests
+    #[test]
+    fn test_startup_command_wide_string() {
+        let cmd = StartupOptions::default().command_line("C:\\Program Files\\SmartFreeze\\sf.exe");
+        let wide = WindowsRegistry::to_wide_string(&cmd);
+
+        assert_eq!(wide.len(), cmd.encode_utf16().count() + 1);
+        assert_eq!(wide.last(), Some(&0));
+        assert_eq!(wide_to_string(&wide), cmd);
+    }
+
+    #[test]
+    fn test_startup_command_line() {
+        let exe = "C:\\Program Files\\SmartFreeze\\sf.exe";
+
+        assert_eq!(
+            StartupOptions::default().command_line(exe),
+            "\"C:\\Program Files\\SmartFreeze\\sf.exe\" --daemon --interval 60 --threshold 100"
+        );
+
+        let options = StartupOptions {
+            interval: 30,
+            threshold: 200,
+            keep_communication: true,
+            state_file: None,
+            state_registry: true,
+        };
+        assert_eq!(
+            options.command_line(exe),
+            "\"C:\\Program Files\\SmartFreeze\\sf.exe\" --daemon --interval 30 --threshold 200 --keep-communication --state-registry"
+        );
+    }
+
+    #[test]
+    fn test_startup_command_carries_state_location() {
+        use clap::Parser;
+
+        let args = Args::parse_from([
+            "smart-freeze",
+            "--state-file",
+            "R:\\Smart Freeze\\state.json",
+        ]);
+        assert_eq!(
+            StartupOptions::from_args(&args).command_line("C:\\sf.exe"),
+            "\"C:\\sf.exe\" --daemon --interval 60 --threshold 100 --state-file \"R:\\Smart Freeze\\state.json\""
+        );
+
+        let registry = StartupOptions::default().with_state(&StateLocation::Registry);
+        assert_eq!(registry.state_file, None);
+        assert!(registry.state_registry);
+
+        // The default state file needs no flag
+        let default = StartupOptions::default()
+            .with_state(&StateLocation::File(FileStatePersistence::default_path()));
+        assert_eq!(default, StartupOptions::default());
+    }
+
+    #[test]
+    fn test_quote_arg_keeps_trailing_backslashes_literal() {
+        assert_eq!(quote_arg("C:\\sf.exe"), "\"C:\\sf.exe\"");
+        assert_eq!(quote_arg("R:\\state\\"), "\"R:\\state\\\\\"");
+    }
+
+    #[test]
+    fn test_startup_options_from_args() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["smart-freeze", "--interval", "15", "--keep-communication"]);
+        let options = StartupOptions::from_args(&args);
+
+        assert_eq!(options.interval, 15);
+        assert_eq!(options.threshold, 100);
+        assert!(options.keep_communication);
+        assert!(!options.state_registry);
+    }
+
+    #[test]
+    fn test_registry_state_round_trip() {
+        use windows_sys::Win32::System::Registry::RegDeleteKeyW;
+
+        let key_path = "Software\\SmartFreezeTest";
+        let persistence = RegistryStatePersistence::new(key_path);
+        assert_eq!(persistence.load().unwrap(), None);
+
+        let mut state = PersistentState::new();
+        state.add(1234, "chrome.exe".to_string(), "C:\\chrome.exe".to_string());
+        persistence.save(&state).unwrap();
+        assert_eq!(persistence.load().unwrap(), Some(state));
+
+        persistence.delete().unwrap();
+        assert_eq!(persistence.load().unwrap(), None);
+
+        let wide = WindowsRegistry::to_wide_string(key_path);
+        unsafe {
+            RegDeleteKeyW(HKEY_CURRENT_USER, wide.as_ptr());
+        }
+    }
+
+    // Note: Startup registry operations are tested in integration tests
     // to avoid modifying the system during unit tests
 }
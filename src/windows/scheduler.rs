@@ -0,0 +1,161 @@
+//! Scheduled Task registration for elevated auto-start
+
+use super::registry::StartupOptions;
+use crate::{Result, SmartFreezeError};
+use std::process::{Command, Output};
+
+const TASK_NAME: &str = "SmartFreeze";
+
+/// Registers the daemon as a logon task running with highest privileges
+///
+/// Unlike the `Run` key this starts the daemon elevated, so it can freeze
+/// elevated processes. Creating the task itself requires administrator rights.
+pub struct ScheduledTaskRegistrar {
+    task_name: String,
+}
+
+impl ScheduledTaskRegistrar {
+    pub fn new() -> Self {
+        Self {
+            task_name: TASK_NAME.to_string(),
+        }
+    }
+
+    /// `schtasks` arguments creating (or replacing) the logon task
+    fn create_args(&self, command: &str) -> Vec<String> {
+        [
+            "/Create",
+            "/TN",
+            &self.task_name,
+            "/TR",
+            command,
+            "/SC",
+            "ONLOGON",
+            "/RL",
+            "HIGHEST",
+            "/F",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn delete_args(&self) -> Vec<String> {
+        ["/Delete", "/TN", &self.task_name, "/F"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn query_args(&self) -> Vec<String> {
+        ["/Query", "/TN", &self.task_name, "/FO", "CSV", "/NH"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// Register the daemon to start at logon
+    pub fn register(&self, exe_path: &str, options: &StartupOptions) -> Result<()> {
+        let output = run_schtasks(&self.create_args(&options.command_line(exe_path)))?;
+        check_status("create", &output)
+    }
+
+    /// Remove the logon task (succeeds if it doesn't exist)
+    pub fn unregister(&self) -> Result<()> {
+        if !self.is_registered() {
+            return Ok(());
+        }
+
+        let output = run_schtasks(&self.delete_args())?;
+        check_status("delete", &output)
+    }
+
+    /// Check whether the logon task exists
+    pub fn is_registered(&self) -> bool {
+        match run_schtasks(&self.query_args()) {
+            Ok(output) if output.status.success() => {
+                parse_query_output(&String::from_utf8_lossy(&output.stdout), &self.task_name)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for ScheduledTaskRegistrar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run_schtasks(args: &[String]) -> Result<Output> {
+    Command::new("schtasks.exe")
+        .args(args)
+        .output()
+        .map_err(|e| SmartFreezeError::TaskScheduler(format!("failed to run schtasks: {}", e)))
+}
+
+/// Turn a non-zero schtasks exit into an error carrying its message
+fn check_status(operation: &str, output: &Output) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(SmartFreezeError::TaskScheduler(format!(
+        "schtasks {} failed ({}): {}",
+        operation,
+        output
+            .status
+            .code()
+            .map_or("no exit code".to_string(), |c| format!("exit code {}", c)),
+        stderr.trim()
+    )))
+}
+
+/// Whether `/Query /FO CSV /NH` output lists the task
+///
+/// Lines look like `"\SmartFreeze","N/A","Ready"`; only the first field is
+/// compared, so localized status columns don't matter.
+fn parse_query_output(stdout: &str, task_name: &str) -> bool {
+    let expected = format!("\\{}", task_name);
+
+    stdout.lines().any(|line| {
+        line.split(',')
+            .next()
+            .map(|field| field.trim().trim_matches('"'))
+            .is_some_and(|name| name.eq_ignore_ascii_case(&expected))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_args() {
+        let registrar = ScheduledTaskRegistrar::new();
+        let command = StartupOptions::default().command_line("C:\\sf.exe");
+        let args = registrar.create_args(&command);
+
+        assert_eq!(args[0], "/Create");
+        assert_eq!(args[2], "SmartFreeze");
+        assert_eq!(
+            args[4],
+            "\"C:\\sf.exe\" --daemon --interval 60 --threshold 100"
+        );
+        assert!(args.windows(2).any(|w| w == ["/SC", "ONLOGON"]));
+        assert!(args.windows(2).any(|w| w == ["/RL", "HIGHEST"]));
+        assert_eq!(args.last().unwrap(), "/F");
+    }
+
+    #[test]
+    fn test_parse_query_output() {
+        let output = "\r\n\"\\SmartFreeze\",\"N/A\",\"Bereit\"\r\n";
+        assert!(parse_query_output(output, "SmartFreeze"));
+        assert!(!parse_query_output("", "SmartFreeze"));
+        assert!(!parse_query_output(
+            "\"\\SmartFreezeOld\",\"N/A\",\"Ready\"",
+            "SmartFreeze"
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+//! Authenticode signer lookup
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::fs::File;
+use std::mem;
+use std::os::windows::io::AsRawHandle;
+use std::ptr;
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::Security::Cryptography::Catalog::{
+    CryptCATAdminAcquireContext2, CryptCATAdminCalcHashFromFileHandle2,
+    CryptCATAdminEnumCatalogFromHash, CryptCATAdminReleaseCatalogContext,
+    CryptCATAdminReleaseContext, CryptCATCatalogInfoFromContext, CATALOG_INFO,
+};
+use windows_sys::Win32::Security::Cryptography::{
+    CertGetNameStringW, BCRYPT_SHA256_ALGORITHM, CERT_CONTEXT, CERT_NAME_SIMPLE_DISPLAY_TYPE,
+};
+use windows_sys::Win32::Security::WinTrust::{
+    WTHelperGetProvCertFromChain, WTHelperGetProvSignerFromChain, WTHelperProvDataFromStateData,
+    WinVerifyTrust, WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_CATALOG_INFO, WINTRUST_DATA,
+    WINTRUST_DATA_0, WINTRUST_DATA_UNION_CHOICE, WINTRUST_FILE_INFO, WTD_CACHE_ONLY_URL_RETRIEVAL,
+    WTD_CHOICE_CATALOG, WTD_CHOICE_FILE, WTD_REVOKE_NONE, WTD_STATEACTION_CLOSE,
+    WTD_STATEACTION_VERIFY, WTD_UI_NONE,
+};
+
+/// Subject name of the certificate that signed `path`
+///
+/// The signature must verify. Files without an embedded signature are looked
+/// up in the system catalogs, which is how most in-box Windows binaries are
+/// signed. Unsigned files and files whose signature doesn't verify return
+/// `None`.
+pub fn signer_subject(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+
+    let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut file_info: WINTRUST_FILE_INFO = mem::zeroed();
+        file_info.cbStruct = mem::size_of::<WINTRUST_FILE_INFO>() as u32;
+        file_info.pcwszFilePath = wide.as_ptr();
+
+        let embedded = verified_signer(
+            WTD_CHOICE_FILE,
+            WINTRUST_DATA_0 {
+                pFile: &mut file_info,
+            },
+        );
+        embedded.or_else(|| catalog_signer(path, &wide))
+    }
+}
+
+/// Verify the file or catalog member described by `choice`, then read its signer
+unsafe fn verified_signer(
+    choice: WINTRUST_DATA_UNION_CHOICE,
+    subject: WINTRUST_DATA_0,
+) -> Option<String> {
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    let mut data: WINTRUST_DATA = mem::zeroed();
+    data.cbStruct = mem::size_of::<WINTRUST_DATA>() as u32;
+    data.dwUIChoice = WTD_UI_NONE;
+    data.fdwRevocationChecks = WTD_REVOKE_NONE;
+    data.dwUnionChoice = choice;
+    data.Anonymous = subject;
+    data.dwStateAction = WTD_STATEACTION_VERIFY;
+    // Never go to the network while enumerating processes
+    data.dwProvFlags = WTD_CACHE_ONLY_URL_RETRIEVAL;
+
+    let status = WinVerifyTrust(
+        ptr::null_mut(),
+        &mut action,
+        &mut data as *mut WINTRUST_DATA as *mut c_void,
+    );
+    let signer = if status == 0 {
+        signer_from_state(data.hWVTStateData)
+    } else {
+        None
+    };
+
+    // The verify call allocates state even when it fails
+    data.dwStateAction = WTD_STATEACTION_CLOSE;
+    WinVerifyTrust(
+        ptr::null_mut(),
+        &mut action,
+        &mut data as *mut WINTRUST_DATA as *mut c_void,
+    );
+
+    signer
+}
+
+/// Display name of the leaf certificate of the first signer in a verified state
+unsafe fn signer_from_state(state: HANDLE) -> Option<String> {
+    let provider = WTHelperProvDataFromStateData(state);
+    if provider.is_null() {
+        return None;
+    }
+    let signer = WTHelperGetProvSignerFromChain(provider, 0, 0, 0);
+    if signer.is_null() {
+        return None;
+    }
+    let cert = WTHelperGetProvCertFromChain(signer, 0);
+    if cert.is_null() || (*cert).pCert.is_null() {
+        return None;
+    }
+    display_name((*cert).pCert)
+}
+
+/// Find `path` in the system catalogs and verify it as a catalog member
+unsafe fn catalog_signer(path: &str, wide: &[u16]) -> Option<String> {
+    let file = File::open(path).ok()?;
+
+    let mut admin = 0isize;
+    if CryptCATAdminAcquireContext2(
+        &mut admin,
+        ptr::null(),
+        BCRYPT_SHA256_ALGORITHM,
+        ptr::null(),
+        0,
+    ) == 0
+    {
+        return None;
+    }
+
+    let signer = catalog_member_signer(admin, &file, wide);
+
+    CryptCATAdminReleaseContext(admin, 0);
+    signer
+}
+
+/// Verify the catalog entry for `file` under an acquired catalog admin context
+unsafe fn catalog_member_signer(admin: isize, file: &File, wide: &[u16]) -> Option<String> {
+    let mut hash = [0u8; 64];
+    let mut hash_len = hash.len() as u32;
+    if CryptCATAdminCalcHashFromFileHandle2(
+        admin,
+        file.as_raw_handle() as HANDLE,
+        &mut hash_len,
+        hash.as_mut_ptr(),
+        0,
+    ) == 0
+    {
+        return None;
+    }
+
+    let catalog =
+        CryptCATAdminEnumCatalogFromHash(admin, hash.as_ptr(), hash_len, 0, ptr::null_mut());
+    if catalog == 0 {
+        return None;
+    }
+
+    let mut catalog_info: CATALOG_INFO = mem::zeroed();
+    catalog_info.cbStruct = mem::size_of::<CATALOG_INFO>() as u32;
+    let signer = if CryptCATCatalogInfoFromContext(catalog, &mut catalog_info, 0) != 0 {
+        // Catalog members are tagged with the hex digest of the file
+        let tag: Vec<u16> = hash[..hash_len as usize]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<String>()
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        let mut member: WINTRUST_CATALOG_INFO = mem::zeroed();
+        member.cbStruct = mem::size_of::<WINTRUST_CATALOG_INFO>() as u32;
+        member.pcwszCatalogFilePath = catalog_info.wszCatalogFile.as_ptr();
+        member.pcwszMemberTag = tag.as_ptr();
+        member.pcwszMemberFilePath = wide.as_ptr();
+        member.pbCalculatedFileHash = hash.as_mut_ptr();
+        member.cbCalculatedFileHash = hash_len;
+        member.hCatAdmin = admin;
+
+        verified_signer(
+            WTD_CHOICE_CATALOG,
+            WINTRUST_DATA_0 {
+                pCatalog: &mut member,
+            },
+        )
+    } else {
+        None
+    };
+
+    CryptCATAdminReleaseCatalogContext(admin, catalog, 0);
+    signer
+}
+
+/// Simple display name of `cert`
+unsafe fn display_name(cert: *const CERT_CONTEXT) -> Option<String> {
+    let mut name = [0u16; 256];
+    let len = CertGetNameStringW(
+        cert,
+        CERT_NAME_SIMPLE_DISPLAY_TYPE,
+        0,
+        ptr::null(),
+        name.as_mut_ptr(),
+        name.len() as u32,
+    );
+
+    // len includes the terminating null; 1 means an empty name
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+/// Signer lookups cached per executable path (they read and parse the file)
+#[derive(Default)]
+pub struct PublisherCache {
+    publishers: HashMap<String, Option<String>>,
+}
+
+impl PublisherCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signer of `path`, looked up on first use
+    pub fn get(&mut self, path: &str) -> Option<String> {
+        self.publishers
+            .entry(path.to_lowercase())
+            .or_insert_with(|| signer_subject(path))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsigned_or_missing_file_has_no_signer() {
+        assert_eq!(signer_subject(""), None);
+        assert_eq!(signer_subject("C:\\does\\not\\exist.exe"), None);
+    }
+
+    #[test]
+    fn test_catalog_signed_system_binary_has_signer() {
+        // cmd.exe carries no embedded signature; it is signed through a catalog
+        let signer = signer_subject("C:\\Windows\\System32\\cmd.exe");
+        assert_eq!(signer.as_deref(), Some("Microsoft Windows"));
+    }
+
+    #[test]
+    fn test_publisher_cache_reuses_lookup() {
+        let mut cache = PublisherCache::new();
+        let path = "C:\\does\\not\\exist.exe";
+
+        assert_eq!(cache.get(path), None);
+        assert!(cache.publishers.contains_key(&path.to_lowercase()));
+    }
+}
@@ -0,0 +1,105 @@
+//! End-to-end runs over the canned demo processes (any OS, `testing` feature)
+
+use clap::Parser;
+use smart_freeze::categorization::DefaultCategorizer;
+use smart_freeze::cli::Args;
+use smart_freeze::config::Config;
+use smart_freeze::output::{collect_processes, CsvFormatter, JsonFormatter};
+use smart_freeze::testing::{MockProcessController, MockProcessEnumerator, DEMO_GAME_PID};
+use smart_freeze::FreezeEngine;
+use std::process::Command;
+
+type DemoEngine = FreezeEngine<MockProcessEnumerator, MockProcessController, DefaultCategorizer>;
+
+fn demo_engine(args: &Args) -> DemoEngine {
+    FreezeEngine::new(
+        MockProcessEnumerator::demo(),
+        MockProcessController::new(),
+        DefaultCategorizer::new(),
+        Config::from_args(args).freeze,
+    )
+}
+
+fn names(processes: &[smart_freeze::ProcessInfo]) -> Vec<&str> {
+    processes.iter().map(|p| p.name.as_str()).collect()
+}
+
+#[test]
+fn test_demo_session_is_detected() {
+    let args = Args::parse_from(["smart-freeze"]);
+    let mut engine = demo_engine(&args);
+
+    assert!(engine.is_gaming_session().unwrap());
+    assert!(engine
+        .gaming_session_pids()
+        .unwrap()
+        .contains(&DEMO_GAME_PID));
+}
+
+#[test]
+fn test_demo_listing_honors_flags() {
+    let args = Args::parse_from(["smart-freeze"]);
+    let listed = collect_processes(&mut demo_engine(&args), &args).unwrap();
+    assert_eq!(
+        names(&listed),
+        ["chrome.exe", "Code.exe", "Discord.exe", "Spotify.exe"]
+    );
+
+    let args = Args::parse_from(["smart-freeze", "--keep-communication", "-t", "500"]);
+    let listed = collect_processes(&mut demo_engine(&args), &args).unwrap();
+    assert_eq!(names(&listed), ["chrome.exe", "Code.exe"]);
+}
+
+#[test]
+fn test_demo_output_formats() {
+    let args = Args::parse_from(["smart-freeze", "--format", "csv"]);
+    let mut engine = demo_engine(&args);
+    let listed = collect_processes(&mut engine, &args).unwrap();
+
+    let csv = CsvFormatter.render(&listed, &args);
+    assert!(csv.starts_with("PID,Name,MemoryMB,Category,Foreground,FullPath\n"));
+    assert!(csv.contains("\n3310,chrome.exe,1850,Productivity,false,"));
+
+    let plan = JsonFormatter.plan_to_value(&engine.plan().unwrap(), &args);
+    assert_eq!(plan["summary"]["would_freeze_count"], 4);
+    assert_eq!(
+        plan["summary"]["total_memory_to_free_mb"],
+        1850 + 960 + 420 + 310
+    );
+    assert!(plan["protected"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["process"]["pid"] == DEMO_GAME_PID && p["reason"] == "Foreground"));
+}
+
+#[test]
+fn test_demo_freeze_and_resume() {
+    let args = Args::parse_from(["smart-freeze"]);
+    let mut engine = demo_engine(&args);
+
+    let report = engine.freeze_session().unwrap();
+    assert_eq!(report.frozen_count(), 4);
+    assert!(report.failed.is_empty());
+
+    for process in &report.frozen {
+        assert_eq!(engine.resume_process(process.pid).unwrap(), 1);
+    }
+    // The controller only knows what it froze itself
+    assert_eq!(engine.resume_process(report.frozen[0].pid).unwrap(), 0);
+}
+
+#[test]
+fn test_demo_flag_runs_binary() {
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-freeze"))
+        .args(["--demo", "--format", "csv", "--top", "2"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].starts_with("3310,chrome.exe,"));
+    assert!(rows[2].starts_with("3790,Code.exe,"));
+}